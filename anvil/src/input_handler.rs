@@ -15,11 +15,14 @@ use smithay::backend::session::{auto::AutoSession, Session};
 use smithay::{
     backend::input::{
         self, Event, InputBackend, InputHandler, KeyState, KeyboardKeyEvent, PointerAxisEvent,
-        PointerButtonEvent, PointerMotionAbsoluteEvent, PointerMotionEvent,
+        PointerButtonEvent, PointerMotionAbsoluteEvent, PointerMotionEvent, TouchDownEvent, TouchMotionEvent,
+        TouchUpEvent,
     },
     reexports::wayland_server::protocol::wl_pointer,
     wayland::{
-        seat::{keysyms as xkb, AxisFrame, KeyboardHandle, Keysym, ModifiersState, PointerHandle},
+        seat::{
+            keysyms as xkb, AxisFrame, KeyboardHandle, Keysym, ModifiersState, PointerHandle, TouchHandle,
+        },
         SERIAL_COUNTER as SCOUNTER,
     },
 };
@@ -30,6 +33,7 @@ pub struct AnvilInputHandler {
     log: Logger,
     pointer: PointerHandle,
     keyboard: KeyboardHandle,
+    touch: TouchHandle,
     window_map: Rc<RefCell<MyWindowMap>>,
     pointer_location: Rc<RefCell<(f64, f64)>>,
     screen_size: (u32, u32),
@@ -43,6 +47,7 @@ impl AnvilInputHandler {
         log: Logger,
         pointer: PointerHandle,
         keyboard: KeyboardHandle,
+        touch: TouchHandle,
         window_map: Rc<RefCell<MyWindowMap>>,
         screen_size: (u32, u32),
         running: Arc<AtomicBool>,
@@ -52,6 +57,7 @@ impl AnvilInputHandler {
             log,
             pointer,
             keyboard,
+            touch,
             window_map,
             screen_size,
             running,
@@ -66,6 +72,7 @@ impl AnvilInputHandler {
         log: Logger,
         pointer: PointerHandle,
         keyboard: KeyboardHandle,
+        touch: TouchHandle,
         window_map: Rc<RefCell<MyWindowMap>>,
         screen_size: (u32, u32),
         running: Arc<AtomicBool>,
@@ -76,6 +83,7 @@ impl AnvilInputHandler {
             log,
             pointer,
             keyboard,
+            touch,
             window_map,
             screen_size,
             running,
@@ -259,20 +267,56 @@ impl<B: InputBackend> InputHandler<B> for AnvilInputHandler {
         }
     }
 
-    fn on_touch_down(&mut self, _: &input::Seat, _: B::TouchDownEvent) {
-        /* not done in this example */
+    fn on_touch_down(&mut self, _: &input::Seat, evt: B::TouchDownEvent) {
+        let (x, y) = {
+            #[cfg(feature = "udev")]
+            {
+                if self.session.is_some() {
+                    let (ux, uy) = evt.position_transformed(self.screen_size);
+                    (ux as f64, uy as f64)
+                } else {
+                    evt.position()
+                }
+            }
+            #[cfg(not(feature = "udev"))]
+            {
+                evt.position()
+            }
+        };
+        let serial = SCOUNTER.next_serial();
+        let under = self.window_map.borrow_mut().get_surface_and_bring_to_top((x, y));
+        self.keyboard
+            .set_focus(under.as_ref().map(|&(ref s, _)| s), serial);
+        self.touch.down(evt.slot(), (x, y), under, serial, evt.time());
     }
-    fn on_touch_motion(&mut self, _: &input::Seat, _: B::TouchMotionEvent) {
-        /* not done in this example */
+    fn on_touch_motion(&mut self, _: &input::Seat, evt: B::TouchMotionEvent) {
+        let (x, y) = {
+            #[cfg(feature = "udev")]
+            {
+                if self.session.is_some() {
+                    let (ux, uy) = evt.position_transformed(self.screen_size);
+                    (ux as f64, uy as f64)
+                } else {
+                    evt.position()
+                }
+            }
+            #[cfg(not(feature = "udev"))]
+            {
+                evt.position()
+            }
+        };
+        let under = self.window_map.borrow().get_surface_under((x, y));
+        self.touch.motion(evt.slot(), (x, y), under, evt.time());
     }
-    fn on_touch_up(&mut self, _: &input::Seat, _: B::TouchUpEvent) {
-        /* not done in this example */
+    fn on_touch_up(&mut self, _: &input::Seat, evt: B::TouchUpEvent) {
+        let serial = SCOUNTER.next_serial();
+        self.touch.up(evt.slot(), serial, evt.time());
     }
     fn on_touch_cancel(&mut self, _: &input::Seat, _: B::TouchCancelEvent) {
-        /* not done in this example */
+        self.touch.cancel();
     }
     fn on_touch_frame(&mut self, _: &input::Seat, _: B::TouchFrameEvent) {
-        /* not done in this example */
+        self.touch.frame();
     }
     fn on_input_config_changed(&mut self, _: &mut B::InputConfig) {
         /* not done in this example */
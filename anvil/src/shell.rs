@@ -85,6 +85,7 @@ pub fn init_shell(
                     size: None,
                     states: vec![],
                     serial: 42,
+                    decoration_mode: None,
                 });
                 xdg_window_map
                     .borrow_mut()
@@ -23,7 +23,7 @@ use smithay::{
             egl::{EglDevice, EglSurface},
             gbm::{egl::Gbm as EglGbmBackend, GbmDevice},
             legacy::LegacyDrmDevice,
-            DevPath, Device, DeviceHandler, Surface,
+            DevPath, Device, DeviceHandler, Surface, VblankTiming,
         },
         graphics::CursorBackend,
         input::InputBackend,
@@ -179,6 +179,8 @@ pub fn run_udev(mut display: Display, mut event_loop: EventLoop<()>, log: Logger
         })
         .expect("Failed to initialize the keyboard");
 
+    let touch = w_seat.add_touch();
+
     /*
      * Initialize a fake output (we render one screen to every device in this example)
      */
@@ -204,6 +206,7 @@ pub fn run_udev(mut display: Display, mut event_loop: EventLoop<()>, log: Logger
         }),
         None,
         None,
+        None,
     );
     output.set_preferred(Mode {
         width: w as i32,
@@ -223,6 +226,7 @@ pub fn run_udev(mut display: Display, mut event_loop: EventLoop<()>, log: Logger
         log.clone(),
         pointer,
         keyboard,
+        touch,
         window_map.clone(),
         (w, h),
         running.clone(),
@@ -534,7 +538,7 @@ pub struct DrmHandlerImpl {
 impl DeviceHandler for DrmHandlerImpl {
     type Device = RenderDevice;
 
-    fn vblank(&mut self, crtc: crtc::Handle) {
+    fn vblank(&mut self, crtc: crtc::Handle, _timing: VblankTiming) {
         if let Some(drawer) = self.backends.borrow().get(&crtc) {
             {
                 let (x, y) = *self.pointer_location.borrow();
@@ -92,6 +92,8 @@ pub fn run_winit(display: &mut Display, event_loop: &mut EventLoop<()>, log: Log
         })
         .expect("Failed to initialize the keyboard");
 
+    let touch = seat.add_touch();
+
     let (output, _) = Output::new(
         display,
         "Winit".into(),
@@ -113,6 +115,7 @@ pub fn run_winit(display: &mut Display, event_loop: &mut EventLoop<()>, log: Log
         }),
         None,
         None,
+        None,
     );
     output.set_preferred(Mode {
         width: w as i32,
@@ -126,6 +129,7 @@ pub fn run_winit(display: &mut Display, event_loop: &mut EventLoop<()>, log: Log
         log.clone(),
         pointer,
         keyboard,
+        touch,
         window_map.clone(),
         (0, 0),
         running.clone(),
@@ -32,6 +32,8 @@ fn main() {
                 "EGL_EXT_platform_wayland",
                 "EGL_EXT_platform_device",
                 "EGL_KHR_image_base",
+                "EGL_EXT_image_dma_buf_import",
+                "EGL_EXT_image_dma_buf_import_modifiers",
             ],
         )
         .write_bindings(gl_generator::GlobalGenerator, &mut file)
@@ -8,7 +8,7 @@ use smithay::{
     backend::drm::{
         device_bind,
         legacy::{error::Error, LegacyDrmDevice, LegacyDrmSurface},
-        Device, DeviceHandler, RawSurface, Surface,
+        Device, DeviceHandler, RawSurface, Surface, VblankTiming,
     },
     reexports::{
         calloop::EventLoop,
@@ -131,7 +131,7 @@ pub struct DrmHandlerImpl {
 impl DeviceHandler for DrmHandlerImpl {
     type Device = LegacyDrmDevice<File>;
 
-    fn vblank(&mut self, _crtc: crtc::Handle) {
+    fn vblank(&mut self, _crtc: crtc::Handle, _timing: VblankTiming) {
         {
             // Swap and map buffer
             let mut mapping = if self.current == self.front.1.handle() {
@@ -15,7 +15,7 @@ use std::rc::Rc;
 #[cfg(feature = "native_lib")]
 use wayland_server::Display;
 
-use super::{Device, DeviceHandler, Surface};
+use super::{Device, DeviceHandler, Surface, VblankTiming};
 use crate::backend::egl::context::GlAttributes;
 use crate::backend::egl::error::Result as EGLResult;
 use crate::backend::egl::native::{Backend, NativeDisplay, NativeSurface};
@@ -120,8 +120,8 @@ where
 {
     type Device = D;
 
-    fn vblank(&mut self, crtc: crtc::Handle) {
-        self.handler.vblank(crtc)
+    fn vblank(&mut self, crtc: crtc::Handle, timing: VblankTiming) {
+        self.handler.vblank(crtc, timing)
     }
     fn error(&mut self, error: <<D as Device>::Surface as Surface>::Error) {
         self.handler
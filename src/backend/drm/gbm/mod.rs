@@ -9,7 +9,7 @@
 //! of [`GbmSurface::page_flip`](::backend::drm::gbm::GbmSurface::page_flip).
 //!
 
-use super::{Device, DeviceHandler, RawDevice, ResourceHandles, ResourceInfo, Surface};
+use super::{Device, DeviceHandler, RawDevice, ResourceHandles, ResourceInfo, Surface, VblankTiming};
 
 use drm::control::{crtc, Device as ControlDevice};
 use gbm::{self, BufferObjectFlags, Format as GbmFormat};
@@ -90,12 +90,12 @@ struct InternalDeviceHandler<D: RawDevice + ControlDevice + 'static> {
 impl<D: RawDevice + ControlDevice + 'static> DeviceHandler for InternalDeviceHandler<D> {
     type Device = D;
 
-    fn vblank(&mut self, crtc: crtc::Handle) {
+    fn vblank(&mut self, crtc: crtc::Handle, timing: VblankTiming) {
         if let Some(backends) = self.backends.upgrade() {
             if let Some(surface) = backends.borrow().get(&crtc) {
                 if let Some(surface) = surface.upgrade() {
                     surface.unlock_buffer();
-                    self.handler.vblank(crtc);
+                    self.handler.vblank(crtc, timing);
                 }
             } else {
                 warn!(
@@ -8,7 +8,7 @@
 //! For an example how to use this standalone, take a look at the `raw_drm` example.
 //!
 
-use super::{DevPath, Device, DeviceHandler, RawDevice};
+use super::{DevPath, Device, DeviceHandler, RawDevice, VblankTiming};
 
 use drm::control::{connector, crtc, encoder, Device as ControlDevice, ResourceHandles, ResourceInfo};
 use drm::Device as BasicDevice;
@@ -242,7 +242,13 @@ impl<A: AsRawFd + 'static> Device for LegacyDrmDevice<A> {
                             {
                                 trace!(self.logger, "Handling event for backend {:?}", event.crtc);
                                 if let Some(handler) = self.handler.as_ref() {
-                                    handler.borrow_mut().vblank(event.crtc);
+                                    handler.borrow_mut().vblank(
+                                        event.crtc,
+                                        VblankTiming {
+                                            sequence: event.frame,
+                                            time: event.duration,
+                                        },
+                                    );
                                 }
                             } else {
                                 self.backends.borrow_mut().remove(&event.crtc);
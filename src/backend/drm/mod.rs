@@ -45,6 +45,7 @@ use std::error::Error;
 use std::iter::IntoIterator;
 use std::os::unix::io::AsRawFd;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use calloop::generic::{EventedFd, Generic};
 use calloop::mio::Ready;
@@ -60,6 +61,17 @@ pub mod gbm;
 #[cfg(feature = "backend_drm_legacy")]
 pub mod legacy;
 
+/// Timing information of a vblank event
+///
+/// This is the information reported by the kernel when a new frame was presented on a crtc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VblankTiming {
+    /// Sequence number of the frame, as counted by the kernel for this crtc
+    pub sequence: u32,
+    /// Time at which the vblank occured, from the `CLOCK_MONOTONIC` clock
+    pub time: Duration,
+}
+
 /// Trait to receive events of a bound [`Device`]
 ///
 /// See [`device_bind`]
@@ -68,7 +80,9 @@ pub trait DeviceHandler {
     type Device: Device + ?Sized;
 
     /// A vblank blank event on the provided crtc has happend
-    fn vblank(&mut self, crtc: crtc::Handle);
+    ///
+    /// The timing of the vblank, as reported by the kernel, is provided.
+    fn vblank(&mut self, crtc: crtc::Handle, timing: VblankTiming);
     /// An error happend while processing events
     fn error(&mut self, error: <<<Self as DeviceHandler>::Device as Device>::Surface as Surface>::Error);
 }
@@ -21,6 +21,8 @@ pub struct EGLContext<B: native::Backend, N: native::NativeDisplay<B>> {
     pub(crate) surface_attributes: Vec<c_int>,
     pixel_format: PixelFormat,
     pub(crate) wl_drm_support: bool,
+    pub(crate) dmabuf_import_support: bool,
+    pub(crate) dmabuf_modifiers_support: bool,
     logger: slog::Logger,
     _backend: PhantomData<B>,
 }
@@ -38,7 +40,7 @@ impl<B: native::Backend, N: native::NativeDisplay<B>> EGLContext<B, N> {
     {
         let log = crate::slog_or_stdlog(logger.into()).new(o!("smithay_module" => "renderer_egl"));
         let ptr = native.ptr()?;
-        let (context, display, config_id, surface_attributes, pixel_format, wl_drm_support) =
+        let (context, display, config_id, surface_attributes, pixel_format, extensions) =
             unsafe { EGLContext::<B, N>::new_internal(ptr, attributes, reqs, log.clone()) }?;
 
         Ok(EGLContext {
@@ -48,7 +50,11 @@ impl<B: native::Backend, N: native::NativeDisplay<B>> EGLContext<B, N> {
             config_id,
             surface_attributes,
             pixel_format,
-            wl_drm_support,
+            wl_drm_support: extensions.iter().any(|s| *s == "EGL_WL_bind_wayland_display"),
+            dmabuf_import_support: extensions.iter().any(|s| *s == "EGL_EXT_image_dma_buf_import"),
+            dmabuf_modifiers_support: extensions
+                .iter()
+                .any(|s| *s == "EGL_EXT_image_dma_buf_import_modifiers"),
             logger: log,
             _backend: PhantomData,
         })
@@ -65,7 +71,7 @@ impl<B: native::Backend, N: native::NativeDisplay<B>> EGLContext<B, N> {
         ffi::egl::types::EGLConfig,
         Vec<c_int>,
         PixelFormat,
-        bool,
+        Vec<String>,
     )> {
         // If no version is given, try OpenGLES 3.0, if available,
         // fallback to 2.0 otherwise
@@ -410,7 +416,7 @@ impl<B: native::Backend, N: native::NativeDisplay<B>> EGLContext<B, N> {
             config_id,
             surface_attributes,
             desc,
-            extensions.iter().any(|s| *s == "EGL_WL_bind_wayland_display"),
+            extensions,
         ))
     }
 
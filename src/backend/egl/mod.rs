@@ -17,9 +17,13 @@
 //!
 //! You may then use the resulting [`EGLDisplay`](::backend::egl::EGLDisplay) to receive [`EGLImages`](::backend::egl::EGLImages)
 //! of an EGL-based [`WlBuffer`](wayland_server::protocol::wl_buffer::WlBuffer) for rendering.
+//! Buffers shared by clients through the [`wayland::dmabuf`](::wayland::dmabuf) module are imported
+//! using the `EGL_EXT_image_dma_buf_import` extension.
 
 #[cfg(feature = "renderer_gl")]
 use crate::backend::graphics::gl::ffi as gl_ffi;
+#[cfg(feature = "native_lib")]
+use crate::wayland::dmabuf::{get_dmabuf, rgb_has_alpha, BufferFlags, Dmabuf, DRM_FORMAT_MOD_INVALID};
 use nix::libc::c_uint;
 use std::{
     ffi::CStr,
@@ -236,7 +240,7 @@ pub struct EGLImages {
     /// Format of these images
     pub format: Format,
     images: Vec<EGLImage>,
-    buffer: WlBuffer,
+    buffer: Option<WlBuffer>,
     #[cfg(feature = "renderer_gl")]
     gl: gl_ffi::Gles2,
     #[cfg(feature = "renderer_gl")]
@@ -300,7 +304,9 @@ impl Drop for EGLImages {
                 }
             }
         }
-        self.buffer.release();
+        if let Some(ref buffer) = self.buffer {
+            buffer.release();
+        }
     }
 }
 
@@ -334,6 +340,8 @@ pub struct EGLDisplay {
     gl: gl_ffi::Gles2,
     #[cfg(feature = "renderer_gl")]
     egl_to_texture_support: bool,
+    dmabuf_import_support: bool,
+    dmabuf_modifiers_support: bool,
 }
 
 #[cfg(feature = "native_lib")]
@@ -360,6 +368,8 @@ impl EGLDisplay {
             },
             #[cfg(feature = "renderer_gl")]
             gl,
+            dmabuf_import_support: context.dmabuf_import_support,
+            dmabuf_modifiers_support: context.dmabuf_modifiers_support,
         }
     }
 
@@ -368,10 +378,20 @@ impl EGLDisplay {
     /// In case the buffer is not managed by EGL (but e.g. the [`wayland::shm` module](::wayland::shm))
     /// a [`BufferAccessError::NotManaged`](::backend::egl::BufferAccessError::NotManaged) is returned with the original buffer
     /// to render it another way.
+    ///
+    /// Both buffers created through the `wl_drm` interface and buffers created by the
+    /// [`wayland::dmabuf` module](::wayland::dmabuf) are supported.
     pub fn egl_buffer_contents(
         &self,
         buffer: WlBuffer,
     ) -> ::std::result::Result<EGLImages, BufferAccessError> {
+        let dmabuf_images = get_dmabuf(&buffer).map(|dmabuf| self.import_dmabuf(dmabuf));
+        if let Some(images) = dmabuf_images {
+            let mut images = images?;
+            images.buffer = Some(buffer);
+            return Ok(images);
+        }
+
         if let Some(display) = self.egl.upgrade() {
             let mut format: i32 = 0;
             if unsafe {
@@ -462,7 +482,7 @@ impl EGLDisplay {
                 y_inverted: inverted != 0,
                 format,
                 images,
-                buffer,
+                buffer: Some(buffer),
                 #[cfg(feature = "renderer_gl")]
                 gl: self.gl.clone(),
                 #[cfg(feature = "renderer_gl")]
@@ -472,6 +492,119 @@ impl EGLDisplay {
             Err(BufferAccessError::ContextLost)
         }
     }
+
+    /// Try to import a [`Dmabuf`] as [`EGLImages`]
+    ///
+    /// This requires the `EGL_EXT_image_dma_buf_import` extension. Explicit modifiers are
+    /// only forwarded to EGL if `EGL_EXT_image_dma_buf_import_modifiers` is supported as well.
+    ///
+    /// The dmabuf is imported as a single image: multi-planar formats need to be sampled as
+    /// [`Format::External`] textures.
+    ///
+    /// This is typically used to validate the buffers created by the clients in the closure
+    /// provided to [`init_dmabuf_global`](::wayland::dmabuf::init_dmabuf_global).
+    pub fn import_dmabuf(&self, dmabuf: &Dmabuf) -> ::std::result::Result<EGLImages, BufferAccessError> {
+        if !self.dmabuf_import_support {
+            return Err(EglExtensionNotSupportedError(&["EGL_EXT_image_dma_buf_import"]).into());
+        }
+        let display = match self.egl.upgrade() {
+            Some(display) => display,
+            None => return Err(BufferAccessError::ContextLost),
+        };
+
+        let mut out: Vec<i32> = Vec::with_capacity(6 + dmabuf.planes.len() * 10 + 1);
+        out.extend(&[
+            ffi::egl::WIDTH as i32,
+            dmabuf.width,
+            ffi::egl::HEIGHT as i32,
+            dmabuf.height,
+            ffi::egl::LINUX_DRM_FOURCC_EXT as i32,
+            dmabuf.format as i32,
+        ]);
+
+        let names = [
+            [
+                ffi::egl::DMA_BUF_PLANE0_FD_EXT,
+                ffi::egl::DMA_BUF_PLANE0_OFFSET_EXT,
+                ffi::egl::DMA_BUF_PLANE0_PITCH_EXT,
+                ffi::egl::DMA_BUF_PLANE0_MODIFIER_LO_EXT,
+                ffi::egl::DMA_BUF_PLANE0_MODIFIER_HI_EXT,
+            ],
+            [
+                ffi::egl::DMA_BUF_PLANE1_FD_EXT,
+                ffi::egl::DMA_BUF_PLANE1_OFFSET_EXT,
+                ffi::egl::DMA_BUF_PLANE1_PITCH_EXT,
+                ffi::egl::DMA_BUF_PLANE1_MODIFIER_LO_EXT,
+                ffi::egl::DMA_BUF_PLANE1_MODIFIER_HI_EXT,
+            ],
+            [
+                ffi::egl::DMA_BUF_PLANE2_FD_EXT,
+                ffi::egl::DMA_BUF_PLANE2_OFFSET_EXT,
+                ffi::egl::DMA_BUF_PLANE2_PITCH_EXT,
+                ffi::egl::DMA_BUF_PLANE2_MODIFIER_LO_EXT,
+                ffi::egl::DMA_BUF_PLANE2_MODIFIER_HI_EXT,
+            ],
+            [
+                ffi::egl::DMA_BUF_PLANE3_FD_EXT,
+                ffi::egl::DMA_BUF_PLANE3_OFFSET_EXT,
+                ffi::egl::DMA_BUF_PLANE3_PITCH_EXT,
+                ffi::egl::DMA_BUF_PLANE3_MODIFIER_LO_EXT,
+                ffi::egl::DMA_BUF_PLANE3_MODIFIER_HI_EXT,
+            ],
+        ];
+
+        for (plane, names) in dmabuf.planes.iter().zip(names.iter()) {
+            out.extend(&[
+                names[0] as i32,
+                plane.fd,
+                names[1] as i32,
+                plane.offset as i32,
+                names[2] as i32,
+                plane.stride as i32,
+            ]);
+            if self.dmabuf_modifiers_support && plane.modifier != DRM_FORMAT_MOD_INVALID {
+                out.extend(&[
+                    names[3] as i32,
+                    (plane.modifier & 0xFFFF_FFFF) as i32,
+                    names[4] as i32,
+                    (plane.modifier >> 32) as i32,
+                ]);
+            }
+        }
+        out.push(ffi::egl::NONE as i32);
+
+        let image = unsafe {
+            ffi::egl::CreateImageKHR(
+                *display,
+                ffi::egl::NO_CONTEXT,
+                ffi::egl::LINUX_DMA_BUF_EXT,
+                ::std::ptr::null_mut(),
+                out.as_ptr(),
+            )
+        };
+        if image == ffi::egl::NO_IMAGE_KHR {
+            return Err(BufferAccessError::EGLImageCreationFailed);
+        }
+
+        Ok(EGLImages {
+            display: Rc::downgrade(&display),
+            width: dmabuf.width as u32,
+            height: dmabuf.height as u32,
+            y_inverted: dmabuf.flags & BufferFlags::YInvert as u32 != 0,
+            format: match rgb_has_alpha(dmabuf.format) {
+                Some(true) => Format::RGBA,
+                Some(false) => Format::RGB,
+                // multi-planar and YUV formats need to be sampled as external textures
+                None => Format::External,
+            },
+            images: vec![image],
+            buffer: None,
+            #[cfg(feature = "renderer_gl")]
+            gl: self.gl.clone(),
+            #[cfg(feature = "renderer_gl")]
+            egl_to_texture_support: self.egl_to_texture_support,
+        })
+    }
 }
 
 #[cfg(feature = "native_lib")]
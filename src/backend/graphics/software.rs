@@ -1,7 +1,15 @@
 //! Common traits and types used for software rendering on graphics backends
+//!
+//! This module also provides [`SoftwareRenderer`], an implementation of [`CpuGraphicsBackend`]
+//! composing client buffers into a framebuffer living in memory. It can be used as a fallback
+//! when no hardware acceleration is available, or as a deterministic renderer for tests.
 
-use std::error::Error;
-use wayland_server::protocol::wl_shm::Format;
+use crate::{
+    utils::Rectangle,
+    wayland::shm::{with_buffer_contents, BufferAccessError},
+};
+use std::{error::Error, fmt};
+use wayland_server::protocol::{wl_buffer::WlBuffer, wl_shm::Format};
 
 /// Trait that describes objects providing a software rendering implementation
 pub trait CpuGraphicsBackend<E: Error> {
@@ -15,3 +23,526 @@ pub trait CpuGraphicsBackend<E: Error> {
     /// Returns the dimensions of the framebuffer
     fn get_framebuffer_dimensions(&self) -> (u32, u32);
 }
+
+/// Errors thrown by the [`SoftwareRenderer`]
+#[derive(Debug)]
+pub enum SoftwareRendererError {
+    /// The provided buffer does not contain the amount of pixels required by its size
+    InvalidBufferSize {
+        /// Minimum length of the buffer, in bytes
+        expected: usize,
+        /// Actual length of the buffer, in bytes
+        got: usize,
+    },
+    /// The rendered area does not intersect the framebuffer
+    OutOfBounds,
+    /// The format of the buffer is not supported by this renderer
+    UnsupportedFormat(Format),
+    /// The contents of a `wl_buffer` could not be accessed
+    BufferAccess(BufferAccessError),
+}
+
+impl fmt::Display for SoftwareRendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SoftwareRendererError::InvalidBufferSize { expected, got } => write!(
+                f,
+                "{}: expected at least {} bytes, got {}",
+                self.description(),
+                expected,
+                got
+            ),
+            SoftwareRendererError::OutOfBounds => write!(f, "{}", self.description()),
+            SoftwareRendererError::UnsupportedFormat(format) => {
+                write!(f, "{}: {:?}", self.description(), format)
+            }
+            SoftwareRendererError::BufferAccess(ref err) => write!(f, "{}: {:?}", self.description(), err),
+        }
+    }
+}
+
+impl Error for SoftwareRendererError {
+    fn description(&self) -> &str {
+        match *self {
+            SoftwareRendererError::InvalidBufferSize { .. } => {
+                "The buffer size does not match its dimensions"
+            }
+            SoftwareRendererError::OutOfBounds => "The rendered area does not intersect the framebuffer",
+            SoftwareRendererError::UnsupportedFormat(_) => "The buffer format is not supported",
+            SoftwareRendererError::BufferAccess(_) => "The buffer contents could not be accessed",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        None
+    }
+}
+
+impl From<BufferAccessError> for SoftwareRendererError {
+    fn from(err: BufferAccessError) -> SoftwareRendererError {
+        SoftwareRendererError::BufferAccess(err)
+    }
+}
+
+/// Formats supported by the [`SoftwareRenderer`]
+const SUPPORTED_FORMATS: &[Format] = &[
+    Format::Argb8888,
+    Format::Xrgb8888,
+    Format::Abgr8888,
+    Format::Xbgr8888,
+    Format::Rgba8888,
+    Format::Rgbx8888,
+    Format::Bgra8888,
+    Format::Bgrx8888,
+    Format::Argb2101010,
+    Format::Xrgb2101010,
+    Format::Abgr2101010,
+    Format::Xbgr2101010,
+    Format::Rgb888,
+    Format::Bgr888,
+    Format::Rgb565,
+    Format::Bgr565,
+    Format::Argb1555,
+    Format::Xrgb1555,
+    Format::Argb4444,
+    Format::Xrgb4444,
+];
+
+/// A renderer composing buffers into an in-memory framebuffer
+///
+/// The framebuffer content is stored as premultiplied `ARGB8888` pixels, in row-major order.
+/// Client buffers are alpha-blended onto it, as `wl_shm` buffers carry premultiplied alpha.
+///
+/// ## Damage tracking
+///
+/// The renderer keeps track of the damaged areas of the framebuffer: all drawing operations
+/// are restricted to them. Once a frame has been drawn, call
+/// [`finish_frame`](SoftwareRenderer::finish_frame) to reset the damage, and
+/// [`damage`](SoftwareRenderer::damage) to mark the areas that need to be redrawn
+/// for the next frame. The whole framebuffer is considered damaged upon creation and resize.
+///
+/// ```
+/// # extern crate smithay;
+/// # extern crate wayland_server;
+/// use smithay::backend::graphics::software::SoftwareRenderer;
+/// use smithay::utils::Rectangle;
+/// use wayland_server::protocol::wl_shm::Format;
+///
+/// let mut renderer = SoftwareRenderer::new((4, 4), None);
+/// renderer.clear(0xFF00_0000);
+/// renderer.finish_frame();
+///
+/// // only the top-left quarter of the framebuffer is redrawn
+/// renderer.damage(Rectangle { x: 0, y: 0, width: 2, height: 2 });
+/// let white = [0xFF; 4 * 4 * 4];
+/// renderer.render_buffer(&white, Format::Argb8888, 16, (4, 4), (0, 0)).unwrap();
+///
+/// assert_eq!(renderer.pixel(1, 1), Some(0xFFFF_FFFF));
+/// assert_eq!(renderer.pixel(2, 2), Some(0xFF00_0000));
+/// ```
+pub struct SoftwareRenderer {
+    size: (u32, u32),
+    framebuffer: Vec<u32>,
+    damage: Vec<Rectangle>,
+    logger: ::slog::Logger,
+}
+
+impl SoftwareRenderer {
+    /// Create a new renderer with a framebuffer of given dimensions
+    ///
+    /// The framebuffer is initially filled with opaque black.
+    pub fn new<L>(size: (u32, u32), logger: L) -> SoftwareRenderer
+    where
+        L: Into<Option<::slog::Logger>>,
+    {
+        let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "renderer_software"));
+        info!(log, "Initializing a software renderer"; "width" => size.0, "height" => size.1);
+        let mut renderer = SoftwareRenderer {
+            size,
+            framebuffer: vec![0xFF00_0000; (size.0 * size.1) as usize],
+            damage: Vec::new(),
+            logger: log,
+        };
+        renderer.damage_all();
+        renderer
+    }
+
+    /// List of the `wl_shm` formats this renderer can draw
+    ///
+    /// This list is suitable to be given to
+    /// [`init_shm_global`](::wayland::shm::init_shm_global), to ensure every buffer
+    /// a client may attach can be rendered.
+    pub fn supported_formats() -> Vec<Format> {
+        SUPPORTED_FORMATS.to_vec()
+    }
+
+    /// Change the dimensions of the framebuffer
+    ///
+    /// The content of the framebuffer is reset to opaque black and fully damaged.
+    pub fn resize(&mut self, size: (u32, u32)) {
+        debug!(self.logger, "Resizing framebuffer"; "width" => size.0, "height" => size.1);
+        self.size = size;
+        self.framebuffer = vec![0xFF00_0000; (size.0 * size.1) as usize];
+        self.reset_damage();
+        self.damage_all();
+    }
+
+    /// Mark an area of the framebuffer as needing to be redrawn
+    ///
+    /// The part of the rectangle outside of the framebuffer is ignored.
+    pub fn damage(&mut self, rect: Rectangle) {
+        if let Some(rect) = rect.intersection(&self.bounds()) {
+            self.damage.push(rect);
+        }
+    }
+
+    /// Mark the whole framebuffer as needing to be redrawn
+    pub fn damage_all(&mut self) {
+        let bounds = self.bounds();
+        self.damage(bounds);
+    }
+
+    /// Forget about all the areas of the framebuffer currently damaged
+    pub fn reset_damage(&mut self) {
+        self.damage.clear();
+    }
+
+    /// Areas of the framebuffer currently damaged
+    ///
+    /// These rectangles may overlap.
+    pub fn pending_damage(&self) -> &[Rectangle] {
+        &self.damage
+    }
+
+    /// Finish the current frame
+    ///
+    /// Returns the areas of the framebuffer that have been damaged for this frame,
+    /// and resets the damage of the renderer.
+    pub fn finish_frame(&mut self) -> Vec<Rectangle> {
+        ::std::mem::replace(&mut self.damage, Vec::new())
+    }
+
+    /// Fill the damaged areas of the framebuffer with the given `ARGB8888` color
+    pub fn clear(&mut self, color: u32) {
+        let width = self.size.0 as i32;
+        for y in 0..self.size.1 as i32 {
+            for (start, end) in self.damaged_spans(y, 0, width) {
+                let offset = (y * width) as usize;
+                for pixel in &mut self.framebuffer[offset + start as usize..offset + end as usize] {
+                    *pixel = color;
+                }
+            }
+        }
+    }
+
+    /// Draw the contents of a buffer at the given location of the framebuffer
+    ///
+    /// `data` contains the rows of the buffer, each of them starting `stride` bytes
+    /// after the previous one. The location may be partially or totally outside of the
+    /// framebuffer, in which case the buffer is clipped. Only the damaged areas of the
+    /// framebuffer are updated.
+    pub fn render_buffer(
+        &mut self,
+        data: &[u8],
+        format: Format,
+        stride: u32,
+        size: (u32, u32),
+        at: (i32, i32),
+    ) -> Result<(), SoftwareRendererError> {
+        let bpp = bytes_per_pixel(format).ok_or(SoftwareRendererError::UnsupportedFormat(format))?;
+        let (width, height) = (size.0 as usize, size.1 as usize);
+        let stride = stride as usize;
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let expected = (height - 1) * stride + width * bpp;
+        if stride < width * bpp || data.len() < expected {
+            return Err(SoftwareRendererError::InvalidBufferSize {
+                expected,
+                got: data.len(),
+            });
+        }
+
+        let area = Rectangle {
+            x: at.0,
+            y: at.1,
+            width: size.0 as i32,
+            height: size.1 as i32,
+        };
+        let visible = match area.intersection(&self.bounds()) {
+            Some(visible) => visible,
+            None => return Ok(()),
+        };
+
+        trace!(self.logger, "Rendering buffer";
+            "x" => at.0, "y" => at.1, "width" => size.0, "height" => size.1, "format" => ?format);
+        let fb_width = self.size.0 as usize;
+        for y in visible.y..visible.y + visible.height {
+            let row = &data[(y - at.1) as usize * stride..];
+            for (start, end) in self.damaged_spans(y, visible.x, visible.x + visible.width) {
+                let offset = y as usize * fb_width;
+                for x in start..end {
+                    let src_offset = (x - at.0) as usize * bpp;
+                    let src = read_pixel(format, &row[src_offset..src_offset + bpp]);
+                    let dst = &mut self.framebuffer[offset + x as usize];
+                    *dst = blend(src, *dst);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw the contents of a `wl_shm` buffer at the given location of the framebuffer
+    ///
+    /// See [`render_buffer`](SoftwareRenderer::render_buffer) for details about clipping
+    /// and damage.
+    pub fn render_shm_buffer(
+        &mut self,
+        buffer: &WlBuffer,
+        at: (i32, i32),
+    ) -> Result<(), SoftwareRendererError> {
+        with_buffer_contents(buffer, |pool, data| {
+            let offset = data.offset as usize;
+            if offset > pool.len() {
+                return Err(SoftwareRendererError::InvalidBufferSize {
+                    expected: offset,
+                    got: pool.len(),
+                });
+            }
+            self.render_buffer(
+                &pool[offset..],
+                data.format,
+                data.stride as u32,
+                (data.width as u32, data.height as u32),
+                at,
+            )
+        })?
+    }
+
+    /// Access the contents of the framebuffer
+    ///
+    /// Pixels are premultiplied `ARGB8888` values, in row-major order.
+    pub fn framebuffer(&self) -> &[u32] {
+        &self.framebuffer
+    }
+
+    /// Read the `ARGB8888` value of a pixel of the framebuffer
+    ///
+    /// Returns `None` if the coordinates are out of the framebuffer.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<u32> {
+        if x < self.size.0 && y < self.size.1 {
+            Some(self.framebuffer[(y * self.size.0 + x) as usize])
+        } else {
+            None
+        }
+    }
+
+    fn bounds(&self) -> Rectangle {
+        Rectangle {
+            x: 0,
+            y: 0,
+            width: self.size.0 as i32,
+            height: self.size.1 as i32,
+        }
+    }
+
+    // Compute the damaged spans of the row `y` between `start` and `end`,
+    // merged so that no pixel is drawn twice
+    fn damaged_spans(&self, y: i32, start: i32, end: i32) -> Vec<(i32, i32)> {
+        let mut spans = self
+            .damage
+            .iter()
+            .filter(|rect| y >= rect.y && y < rect.y + rect.height)
+            .map(|rect| (rect.x.max(start), (rect.x + rect.width).min(end)))
+            .filter(|&(s, e)| s < e)
+            .collect::<Vec<_>>();
+        spans.sort();
+        let mut merged: Vec<(i32, i32)> = Vec::with_capacity(spans.len());
+        for (s, e) in spans {
+            match merged.last_mut() {
+                Some(last) if s <= last.1 => last.1 = last.1.max(e),
+                _ => merged.push((s, e)),
+            }
+        }
+        merged
+    }
+}
+
+impl CpuGraphicsBackend<SoftwareRendererError> for SoftwareRenderer {
+    /// Render a tightly packed buffer in the framebuffer
+    ///
+    /// The parts of the buffer not fitting in the framebuffer are clipped, an error
+    /// is only returned if the buffer does not intersect it at all.
+    fn render(
+        &mut self,
+        buffer: &[u8],
+        format: Format,
+        at: (u32, u32),
+        size: (u32, u32),
+    ) -> Result<(), SoftwareRendererError> {
+        let bpp = bytes_per_pixel(format).ok_or(SoftwareRendererError::UnsupportedFormat(format))?;
+        let expected = size.0 as usize * size.1 as usize * bpp;
+        if buffer.len() != expected {
+            return Err(SoftwareRendererError::InvalidBufferSize {
+                expected,
+                got: buffer.len(),
+            });
+        }
+        if at.0 >= self.size.0 || at.1 >= self.size.1 {
+            return Err(SoftwareRendererError::OutOfBounds);
+        }
+        self.render_buffer(
+            buffer,
+            format,
+            size.0 * bpp as u32,
+            size,
+            (at.0 as i32, at.1 as i32),
+        )
+    }
+
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        self.size
+    }
+}
+
+fn bytes_per_pixel(format: Format) -> Option<usize> {
+    match format {
+        Format::Argb8888
+        | Format::Xrgb8888
+        | Format::Abgr8888
+        | Format::Xbgr8888
+        | Format::Rgba8888
+        | Format::Rgbx8888
+        | Format::Bgra8888
+        | Format::Bgrx8888
+        | Format::Argb2101010
+        | Format::Xrgb2101010
+        | Format::Abgr2101010
+        | Format::Xbgr2101010 => Some(4),
+        Format::Rgb888 | Format::Bgr888 => Some(3),
+        Format::Rgb565
+        | Format::Bgr565
+        | Format::Argb1555
+        | Format::Xrgb1555
+        | Format::Argb4444
+        | Format::Xrgb4444 => Some(2),
+        _ => None,
+    }
+}
+
+// Scale a `bits`-wide channel value to 8 bits
+fn expand(value: u32, bits: u32) -> u32 {
+    let max = (1 << bits) - 1;
+    ((value & max) * 255 + max / 2) / max
+}
+
+// Read a pixel of given format and return it as a premultiplied ARGB8888 value
+//
+// The format names describe the layout of a little-endian integer, from the most
+// significant bits to the least significant ones.
+fn read_pixel(format: Format, pixel: &[u8]) -> u32 {
+    let value = match pixel.len() {
+        4 => {
+            u32::from(pixel[0])
+                | u32::from(pixel[1]) << 8
+                | u32::from(pixel[2]) << 16
+                | u32::from(pixel[3]) << 24
+        }
+        3 => u32::from(pixel[0]) | u32::from(pixel[1]) << 8 | u32::from(pixel[2]) << 16,
+        _ => u32::from(pixel[0]) | u32::from(pixel[1]) << 8,
+    };
+    let (a, r, g, b) = match format {
+        Format::Argb8888 => (value >> 24, value >> 16, value >> 8, value),
+        Format::Xrgb8888 => (0xFF, value >> 16, value >> 8, value),
+        Format::Abgr8888 => (value >> 24, value, value >> 8, value >> 16),
+        Format::Xbgr8888 => (0xFF, value, value >> 8, value >> 16),
+        Format::Rgba8888 => (value, value >> 24, value >> 16, value >> 8),
+        Format::Rgbx8888 => (0xFF, value >> 24, value >> 16, value >> 8),
+        Format::Bgra8888 => (value, value >> 8, value >> 16, value >> 24),
+        Format::Bgrx8888 => (0xFF, value >> 8, value >> 16, value >> 24),
+        Format::Argb2101010 => (
+            expand(value >> 30, 2),
+            expand(value >> 20, 10),
+            expand(value >> 10, 10),
+            expand(value, 10),
+        ),
+        Format::Xrgb2101010 => (
+            0xFF,
+            expand(value >> 20, 10),
+            expand(value >> 10, 10),
+            expand(value, 10),
+        ),
+        Format::Abgr2101010 => (
+            expand(value >> 30, 2),
+            expand(value, 10),
+            expand(value >> 10, 10),
+            expand(value >> 20, 10),
+        ),
+        Format::Xbgr2101010 => (
+            0xFF,
+            expand(value, 10),
+            expand(value >> 10, 10),
+            expand(value >> 20, 10),
+        ),
+        Format::Rgb888 => (0xFF, value >> 16, value >> 8, value),
+        Format::Bgr888 => (0xFF, value, value >> 8, value >> 16),
+        Format::Rgb565 => (
+            0xFF,
+            expand(value >> 11, 5),
+            expand(value >> 5, 6),
+            expand(value, 5),
+        ),
+        Format::Bgr565 => (
+            0xFF,
+            expand(value, 5),
+            expand(value >> 5, 6),
+            expand(value >> 11, 5),
+        ),
+        Format::Argb1555 => (
+            expand(value >> 15, 1),
+            expand(value >> 10, 5),
+            expand(value >> 5, 5),
+            expand(value, 5),
+        ),
+        Format::Xrgb1555 => (
+            0xFF,
+            expand(value >> 10, 5),
+            expand(value >> 5, 5),
+            expand(value, 5),
+        ),
+        Format::Argb4444 => (
+            expand(value >> 12, 4),
+            expand(value >> 8, 4),
+            expand(value >> 4, 4),
+            expand(value, 4),
+        ),
+        Format::Xrgb4444 => (
+            0xFF,
+            expand(value >> 8, 4),
+            expand(value >> 4, 4),
+            expand(value, 4),
+        ),
+        _ => unreachable!(),
+    };
+    (a & 0xFF) << 24 | (r & 0xFF) << 16 | (g & 0xFF) << 8 | (b & 0xFF)
+}
+
+// Blend a premultiplied source pixel over a premultiplied destination pixel
+fn blend(src: u32, dst: u32) -> u32 {
+    let alpha = src >> 24;
+    match alpha {
+        0xFF => src,
+        0 => dst,
+        _ => {
+            let inv = 255 - alpha;
+            let mut out = 0;
+            for shift in &[0, 8, 16, 24] {
+                let s = (src >> shift) & 0xFF;
+                let d = (dst >> shift) & 0xFF;
+                let c = (s + (d * inv + 127) / 255).min(0xFF);
+                out |= c << shift;
+            }
+            out
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Implementation of a headless graphics backend
+//!
+//! This backend does not need any display server or GPU: it renders into a framebuffer
+//! living in memory, that can then be inspected. It is mostly useful to exercise the
+//! drawing logic of a compositor in automated tests or on a CI machine.
+//!
+//! The framebuffer content is stored as `ARGB8888` pixels, in row-major order.
+//!
+//! A matching input backend allowing to inject input events is provided by the
+//! [`mock`](::backend::mock) module.
+
+use crate::backend::graphics::software::{CpuGraphicsBackend, SoftwareRenderer, SoftwareRendererError};
+use std::{error, fmt};
+use wayland_server::protocol::wl_shm::Format;
+
+/// Errors thrown by the [`HeadlessGraphicsBackend`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeadlessError {
+    /// The provided buffer does not contain the amount of pixels required by its size
+    InvalidBufferSize {
+        /// Expected length of the buffer, in bytes
+        expected: usize,
+        /// Actual length of the buffer, in bytes
+        got: usize,
+    },
+    /// The rendered area does not fit inside the framebuffer
+    OutOfBounds,
+    /// The format of the buffer is not supported by this backend
+    UnsupportedFormat(Format),
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use std::error::Error;
+        match *self {
+            HeadlessError::InvalidBufferSize { expected, got } => write!(
+                f,
+                "{}: expected {} bytes, got {}",
+                self.description(),
+                expected,
+                got
+            ),
+            HeadlessError::OutOfBounds => write!(f, "{}", self.description()),
+            HeadlessError::UnsupportedFormat(format) => write!(f, "{}: {:?}", self.description(), format),
+        }
+    }
+}
+
+impl error::Error for HeadlessError {
+    fn description(&self) -> &str {
+        match *self {
+            HeadlessError::InvalidBufferSize { .. } => "The buffer size does not match its dimensions",
+            HeadlessError::OutOfBounds => "The rendered area does not fit in the framebuffer",
+            HeadlessError::UnsupportedFormat(_) => "The buffer format is not supported",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl From<SoftwareRendererError> for HeadlessError {
+    fn from(err: SoftwareRendererError) -> HeadlessError {
+        match err {
+            SoftwareRendererError::InvalidBufferSize { expected, got } => {
+                HeadlessError::InvalidBufferSize { expected, got }
+            }
+            SoftwareRendererError::OutOfBounds => HeadlessError::OutOfBounds,
+            SoftwareRendererError::UnsupportedFormat(format) => HeadlessError::UnsupportedFormat(format),
+            // we only render raw pixel data, never accessing the contents of a wl_buffer
+            SoftwareRendererError::BufferAccess(_) => unreachable!(),
+        }
+    }
+}
+
+/// A graphics backend rendering into an in-memory framebuffer
+///
+/// Drawing is done by a [`SoftwareRenderer`], which can be accessed to control damage
+/// tracking or to draw `wl_shm` buffers directly.
+pub struct HeadlessGraphicsBackend {
+    renderer: SoftwareRenderer,
+}
+
+impl HeadlessGraphicsBackend {
+    /// Create a new headless backend with a framebuffer of given dimensions
+    ///
+    /// The framebuffer is initially filled with opaque black.
+    pub fn new<L>(size: (u32, u32), logger: L) -> HeadlessGraphicsBackend
+    where
+        L: Into<Option<::slog::Logger>>,
+    {
+        let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "backend_headless"));
+        info!(log, "Initializing a headless framebuffer"; "width" => size.0, "height" => size.1);
+        HeadlessGraphicsBackend {
+            renderer: SoftwareRenderer::new(size, log),
+        }
+    }
+
+    /// Fill the whole framebuffer with the given `ARGB8888` color
+    ///
+    /// The whole framebuffer is then considered damaged, replacing any damage
+    /// accumulated since the last call to [`SoftwareRenderer::finish_frame`].
+    pub fn clear(&mut self, color: u32) {
+        // the full damage covers all the previous one, no need to keep it around
+        self.renderer.reset_damage();
+        self.renderer.damage_all();
+        self.renderer.clear(color);
+    }
+
+    /// Change the dimensions of the framebuffer
+    ///
+    /// The content of the framebuffer is reset to opaque black.
+    pub fn resize(&mut self, size: (u32, u32)) {
+        self.renderer.resize(size);
+    }
+
+    /// Access the contents of the framebuffer
+    ///
+    /// Pixels are `ARGB8888` values, in row-major order.
+    pub fn framebuffer(&self) -> &[u32] {
+        self.renderer.framebuffer()
+    }
+
+    /// Read the `ARGB8888` value of a pixel of the framebuffer
+    ///
+    /// Returns `None` if the coordinates are out of the framebuffer.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<u32> {
+        self.renderer.pixel(x, y)
+    }
+
+    /// Access the renderer drawing into the framebuffer
+    pub fn renderer(&mut self) -> &mut SoftwareRenderer {
+        &mut self.renderer
+    }
+}
+
+impl CpuGraphicsBackend<HeadlessError> for HeadlessGraphicsBackend {
+    fn render(
+        &mut self,
+        buffer: &[u8],
+        format: Format,
+        at: (u32, u32),
+        size: (u32, u32),
+    ) -> Result<(), HeadlessError> {
+        self.renderer
+            .render(buffer, format, at, size)
+            .map_err(HeadlessError::from)
+    }
+
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        self.renderer.get_framebuffer_dimensions()
+    }
+}
@@ -1,6 +1,6 @@
 //! Common traits for input backends to receive input from.
 
-use std::{error::Error, string::ToString};
+use std::{error::Error, fmt, string::ToString};
 
 /// A seat describes a group of input devices and at least one
 /// graphics device belonging together.
@@ -29,6 +29,11 @@ impl Seat {
         }
     }
 
+    #[cfg(feature = "backend_mock")]
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
     pub(crate) fn capabilities_mut(&mut self) -> &mut SeatCapabilities {
         &mut self.capabilities
     }
@@ -332,6 +337,11 @@ impl TouchSlot {
     pub(crate) fn new(id: u64) -> Self {
         TouchSlot { id }
     }
+
+    #[cfg(feature = "wayland_frontend")]
+    pub(crate) fn id(self) -> u64 {
+        self.id
+    }
 }
 
 /// Trait for touch events starting at a given position.
@@ -487,12 +497,733 @@ pub trait TouchFrameEvent: Event {}
 
 impl TouchFrameEvent for UnusedEvent {}
 
+/// Trait for events starting a gesture
+pub trait GestureBeginEvent: Event {
+    /// Number of fingers used for the gesture
+    fn fingers(&self) -> u32;
+}
+
+impl GestureBeginEvent for UnusedEvent {
+    fn fingers(&self) -> u32 {
+        match *self {}
+    }
+}
+
+/// Trait for events ending a gesture
+pub trait GestureEndEvent: Event {
+    /// Whether the gesture was cancelled instead of being completed
+    fn cancelled(&self) -> bool;
+}
+
+impl GestureEndEvent for UnusedEvent {
+    fn cancelled(&self) -> bool {
+        match *self {}
+    }
+}
+
+/// Trait for swipe gesture events regarding movement of the fingers
+pub trait GestureSwipeUpdateEvent: Event {
+    /// Delta between the last and new logical center of the gesture
+    fn delta(&self) -> (f64, f64) {
+        (self.delta_x(), self.delta_y())
+    }
+
+    /// Delta on the x axis between the last and new logical center of the gesture
+    fn delta_x(&self) -> f64;
+
+    /// Delta on the y axis between the last and new logical center of the gesture
+    fn delta_y(&self) -> f64;
+}
+
+impl GestureSwipeUpdateEvent for UnusedEvent {
+    fn delta_x(&self) -> f64 {
+        match *self {}
+    }
+
+    fn delta_y(&self) -> f64 {
+        match *self {}
+    }
+}
+
+/// Trait for pinch gesture events regarding movement of the fingers
+pub trait GesturePinchUpdateEvent: Event {
+    /// Delta between the last and new logical center of the gesture
+    fn delta(&self) -> (f64, f64) {
+        (self.delta_x(), self.delta_y())
+    }
+
+    /// Delta on the x axis between the last and new logical center of the gesture
+    fn delta_x(&self) -> f64;
+
+    /// Delta on the y axis between the last and new logical center of the gesture
+    fn delta_y(&self) -> f64;
+
+    /// Absolute scale of the pinch, relative to the distance between the fingers
+    /// at the beginning of the gesture
+    fn scale(&self) -> f64;
+
+    /// Angle in degrees clockwise of the rotation since the last event
+    fn rotation(&self) -> f64;
+}
+
+impl GesturePinchUpdateEvent for UnusedEvent {
+    fn delta_x(&self) -> f64 {
+        match *self {}
+    }
+
+    fn delta_y(&self) -> f64 {
+        match *self {}
+    }
+
+    fn scale(&self) -> f64 {
+        match *self {}
+    }
+
+    fn rotation(&self) -> f64 {
+        match *self {}
+    }
+}
+
+/// Type of a tablet tool
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum TabletToolType {
+    /// A generic pen
+    Pen,
+    /// Eraser
+    Eraser,
+    /// A paintbrush-like tool
+    Brush,
+    /// Physical drawing tool, e.g. Wacom Inking Pen
+    Pencil,
+    /// An airbrush-like tool
+    Airbrush,
+    /// A mouse bound to the tablet
+    Mouse,
+    /// A mouse tool with a lens
+    Lens,
+}
+
+/// Axes a tablet tool is capable of reporting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TabletToolCapabilities {
+    /// Tool reports pressure
+    pub pressure: bool,
+    /// Tool reports distance to the tablet surface
+    pub distance: bool,
+    /// Tool reports tilt
+    pub tilt: bool,
+    /// Tool reports rotation around its axis
+    pub rotation: bool,
+    /// Tool has a slider
+    pub slider: bool,
+    /// Tool has a wheel
+    pub wheel: bool,
+}
+
+/// Description of a physical tablet tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TabletToolDescriptor {
+    /// Type of the tool
+    pub tool_type: TabletToolType,
+    /// Unique serial number of the tool, `0` if the hardware does not provide one
+    pub hardware_serial: u64,
+    /// Hardware id in Wacom's format, `0` if the hardware does not provide one
+    pub hardware_id_wacom: u64,
+    /// Axes the tool is capable of reporting
+    pub capabilities: TabletToolCapabilities,
+}
+
+/// Proximity state of a tablet tool
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ProximityState {
+    /// The tool left the proximity of the tablet
+    Out,
+    /// The tool entered the proximity of the tablet
+    In,
+}
+
+/// Contact state of the tip of a tablet tool
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TipState {
+    /// The tip is not in contact with the tablet surface
+    Up,
+    /// The tip is in contact with the tablet surface
+    Down,
+}
+
+/// Common methods of all events generated by a tablet tool
+pub trait TabletToolEvent: Event {
+    /// Description of the tool generating the event
+    fn tool(&self) -> TabletToolDescriptor;
+
+    /// Delta between the last and new position of the tool, in the device's native coordinate space
+    fn delta(&self) -> (f64, f64) {
+        (self.delta_x(), self.delta_y())
+    }
+
+    /// Delta on the x axis between the last and new position of the tool
+    fn delta_x(&self) -> f64;
+
+    /// Delta on the y axis between the last and new position of the tool
+    fn delta_y(&self) -> f64;
+
+    /// Position of the tool in the device's native coordinate space
+    ///
+    /// The actual format is defined by the implementation.
+    fn position(&self) -> (f64, f64) {
+        (self.x(), self.y())
+    }
+
+    /// Position of the tool converted into the target coordinate space.
+    /// E.g. the focused output's resolution.
+    fn position_transformed(&self, coordinate_space: (u32, u32)) -> (u32, u32) {
+        (
+            self.x_transformed(coordinate_space.0),
+            self.y_transformed(coordinate_space.1),
+        )
+    }
+
+    /// x-coordinate of the tool in the device's native coordinate space
+    fn x(&self) -> f64;
+
+    /// y-coordinate of the tool in the device's native coordinate space
+    fn y(&self) -> f64;
+
+    /// x position of the tool converted to the targets coordinate space's width.
+    fn x_transformed(&self, width: u32) -> u32;
+
+    /// y position of the tool converted to the targets coordinate space's height.
+    fn y_transformed(&self, height: u32) -> u32;
+
+    /// Normalized pressure applied by the tool, between `0.0` and `1.0`
+    fn pressure(&self) -> f64;
+
+    /// Normalized distance between the tool and the tablet surface, between `0.0` and `1.0`
+    fn distance(&self) -> f64;
+
+    /// Tilt of the tool, in degrees on both axes
+    fn tilt(&self) -> (f64, f64) {
+        (self.tilt_x(), self.tilt_y())
+    }
+
+    /// Tilt of the tool on the x axis, in degrees
+    fn tilt_x(&self) -> f64;
+
+    /// Tilt of the tool on the y axis, in degrees
+    fn tilt_y(&self) -> f64;
+
+    /// Rotation of the tool around its axis, in degrees clockwise
+    fn rotation(&self) -> f64;
+
+    /// Position of the slider of the tool, between `-1.0` and `1.0`
+    fn slider_position(&self) -> f64;
+
+    /// Delta of the wheel of the tool, in degrees
+    fn wheel_delta(&self) -> f64;
+
+    /// Delta of the wheel of the tool, in discrete steps
+    fn wheel_delta_discrete(&self) -> i32;
+}
+
+impl TabletToolEvent for UnusedEvent {
+    fn tool(&self) -> TabletToolDescriptor {
+        match *self {}
+    }
+
+    fn delta_x(&self) -> f64 {
+        match *self {}
+    }
+
+    fn delta_y(&self) -> f64 {
+        match *self {}
+    }
+
+    fn x(&self) -> f64 {
+        match *self {}
+    }
+
+    fn y(&self) -> f64 {
+        match *self {}
+    }
+
+    fn x_transformed(&self, _width: u32) -> u32 {
+        match *self {}
+    }
+
+    fn y_transformed(&self, _height: u32) -> u32 {
+        match *self {}
+    }
+
+    fn pressure(&self) -> f64 {
+        match *self {}
+    }
+
+    fn distance(&self) -> f64 {
+        match *self {}
+    }
+
+    fn tilt_x(&self) -> f64 {
+        match *self {}
+    }
+
+    fn tilt_y(&self) -> f64 {
+        match *self {}
+    }
+
+    fn rotation(&self) -> f64 {
+        match *self {}
+    }
+
+    fn slider_position(&self) -> f64 {
+        match *self {}
+    }
+
+    fn wheel_delta(&self) -> f64 {
+        match *self {}
+    }
+
+    fn wheel_delta_discrete(&self) -> i32 {
+        match *self {}
+    }
+}
+
+/// Trait for tablet tool events regarding changes of its axes
+pub trait TabletToolAxisEvent: TabletToolEvent {}
+
+impl TabletToolAxisEvent for UnusedEvent {}
+
+/// Trait for tablet tool events regarding its proximity to the tablet
+pub trait TabletToolProximityEvent: TabletToolEvent {
+    /// New proximity state of the tool
+    fn state(&self) -> ProximityState;
+}
+
+impl TabletToolProximityEvent for UnusedEvent {
+    fn state(&self) -> ProximityState {
+        match *self {}
+    }
+}
+
+/// Trait for tablet tool events regarding the contact of its tip with the tablet
+pub trait TabletToolTipEvent: TabletToolEvent {
+    /// New state of the tip of the tool
+    fn tip_state(&self) -> TipState;
+}
+
+impl TabletToolTipEvent for UnusedEvent {
+    fn tip_state(&self) -> TipState {
+        match *self {}
+    }
+}
+
+/// Trait for tablet tool events generated by pressed buttons
+pub trait TabletToolButtonEvent: TabletToolEvent {
+    /// Code of the button, see `linux/input-event-codes.h`
+    fn button(&self) -> u32;
+
+    /// State of the button
+    fn button_state(&self) -> MouseButtonState;
+
+    /// Total number of buttons pressed on all devices on the associated [`Seat`]
+    fn seat_button_count(&self) -> u32;
+}
+
+impl TabletToolButtonEvent for UnusedEvent {
+    fn button(&self) -> u32 {
+        match *self {}
+    }
+
+    fn button_state(&self) -> MouseButtonState {
+        match *self {}
+    }
+
+    fn seat_button_count(&self) -> u32 {
+        match *self {}
+    }
+}
+
+/// Source of a tablet pad ring or strip event
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TabletPadAxisSource {
+    /// The source of the event is unknown
+    Unknown,
+    /// The event is generated by a finger on the ring or strip
+    ///
+    /// A terminating event with a position of `-1` is sent when the finger is lifted.
+    Finger,
+}
+
+/// Trait for tablet pad events generated by pressed buttons
+pub trait TabletPadButtonEvent: Event {
+    /// Index of the button, starting at `0`
+    fn button(&self) -> u32;
+
+    /// State of the button
+    fn state(&self) -> MouseButtonState;
+
+    /// Mode of the mode group the button belongs to
+    fn mode(&self) -> u32;
+}
+
+impl TabletPadButtonEvent for UnusedEvent {
+    fn button(&self) -> u32 {
+        match *self {}
+    }
+
+    fn state(&self) -> MouseButtonState {
+        match *self {}
+    }
+
+    fn mode(&self) -> u32 {
+        match *self {}
+    }
+}
+
+/// Trait for tablet pad events generated by a ring
+pub trait TabletPadRingEvent: Event {
+    /// Index of the ring, starting at `0`
+    fn number(&self) -> u32;
+
+    /// Position of the ring in degrees clockwise from the north, `-1` on termination
+    fn position(&self) -> f64;
+
+    /// Source of the event
+    fn source(&self) -> TabletPadAxisSource;
+
+    /// Mode of the mode group the ring belongs to
+    fn mode(&self) -> u32;
+}
+
+impl TabletPadRingEvent for UnusedEvent {
+    fn number(&self) -> u32 {
+        match *self {}
+    }
+
+    fn position(&self) -> f64 {
+        match *self {}
+    }
+
+    fn source(&self) -> TabletPadAxisSource {
+        match *self {}
+    }
+
+    fn mode(&self) -> u32 {
+        match *self {}
+    }
+}
+
+/// Trait for tablet pad events generated by a strip
+pub trait TabletPadStripEvent: Event {
+    /// Index of the strip, starting at `0`
+    fn number(&self) -> u32;
+
+    /// Normalized position on the strip, between `0.0` and `1.0`, `-1` on termination
+    fn position(&self) -> f64;
+
+    /// Source of the event
+    fn source(&self) -> TabletPadAxisSource;
+
+    /// Mode of the mode group the strip belongs to
+    fn mode(&self) -> u32;
+}
+
+impl TabletPadStripEvent for UnusedEvent {
+    fn number(&self) -> u32 {
+        match *self {}
+    }
+
+    fn position(&self) -> f64 {
+        match *self {}
+    }
+
+    fn source(&self) -> TabletPadAxisSource {
+        match *self {}
+    }
+
+    fn mode(&self) -> u32 {
+        match *self {}
+    }
+}
+
+/// A physical switch
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Switch {
+    /// The lid of a laptop
+    Lid,
+    /// The tablet mode switch of a convertible laptop
+    TabletMode,
+}
+
+/// State of a switch
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SwitchState {
+    /// The switch is off, e.g. the lid is open or the device is in laptop mode
+    Off,
+    /// The switch is on, e.g. the lid is closed or the device is in tablet mode
+    On,
+}
+
+/// Trait for events generated by toggled switches
+pub trait SwitchToggleEvent: Event {
+    /// The switch that was toggled, if known
+    fn switch(&self) -> Option<Switch>;
+
+    /// New state of the switch
+    fn state(&self) -> SwitchState;
+}
+
+impl SwitchToggleEvent for UnusedEvent {
+    fn switch(&self) -> Option<Switch> {
+        match *self {}
+    }
+
+    fn state(&self) -> SwitchState {
+        match *self {}
+    }
+}
+
+/// Capability of an input device
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum DeviceCapability {
+    /// The device has keys
+    Keyboard,
+    /// The device controls a pointer
+    Pointer,
+    /// The device is a touchscreen
+    Touch,
+    /// The device is a tablet with a tool
+    TabletTool,
+    /// The device is the pad of a tablet
+    TabletPad,
+    /// The device supports gestures
+    Gesture,
+    /// The device has switches
+    Switch,
+}
+
+/// Acceleration profile of a pointer device
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum AccelProfile {
+    /// Constant acceleration factor, only depending on the configured speed
+    Flat,
+    /// Acceleration factor depending on the speed of the movement
+    Adaptive,
+}
+
+/// Errors that can occur when changing the configuration of an [`InputDevice`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DeviceConfigError {
+    /// The device does not support this configuration option
+    Unsupported,
+    /// The value given for this configuration option is invalid
+    Invalid,
+}
+
+impl fmt::Display for DeviceConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+impl Error for DeviceConfigError {
+    fn description(&self) -> &str {
+        match *self {
+            DeviceConfigError::Unsupported => "The configuration option is not supported by the device",
+            DeviceConfigError::Invalid => "The value of the configuration option is invalid",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        None
+    }
+}
+
+/// Trait for input devices whose configuration can be queried and changed
+///
+/// Every configuration option comes with a capability query: setting an option the
+/// device does not support returns [`DeviceConfigError::Unsupported`].
+pub trait InputDevice {
+    /// Human-readable name of the device
+    fn name(&self) -> &str;
+
+    /// Checks if the device has a given capability
+    fn has_capability(&self, capability: DeviceCapability) -> bool;
+
+    /// Number of fingers supported for tap-to-click, `0` if the device does not support it
+    fn tap_finger_count(&self) -> u32;
+    /// Checks if tap-to-click is enabled
+    fn tap_enabled(&self) -> bool;
+    /// Enable or disable tap-to-click
+    fn set_tap_enabled(&mut self, enabled: bool) -> Result<(), DeviceConfigError>;
+
+    /// Checks if the device supports natural scrolling
+    fn has_natural_scroll(&self) -> bool;
+    /// Checks if natural scrolling is enabled
+    fn natural_scroll_enabled(&self) -> bool;
+    /// Enable or disable natural scrolling
+    fn set_natural_scroll_enabled(&mut self, enabled: bool) -> Result<(), DeviceConfigError>;
+
+    /// Checks if the pointer acceleration of the device can be configured
+    fn has_accel(&self) -> bool;
+    /// Current pointer acceleration speed, between `-1.0` and `1.0`
+    fn accel_speed(&self) -> f64;
+    /// Set the pointer acceleration speed, between `-1.0` and `1.0`
+    fn set_accel_speed(&mut self, speed: f64) -> Result<(), DeviceConfigError>;
+    /// Acceleration profiles supported by the device
+    fn accel_profiles(&self) -> Vec<AccelProfile>;
+    /// Current acceleration profile, if any
+    fn accel_profile(&self) -> Option<AccelProfile>;
+    /// Set the acceleration profile
+    fn set_accel_profile(&mut self, profile: AccelProfile) -> Result<(), DeviceConfigError>;
+
+    /// Checks if the device supports the left-handed mode
+    fn has_left_handed(&self) -> bool;
+    /// Checks if the left-handed mode is enabled
+    fn left_handed(&self) -> bool;
+    /// Enable or disable the left-handed mode
+    fn set_left_handed(&mut self, enabled: bool) -> Result<(), DeviceConfigError>;
+
+    /// Checks if the device supports a calibration matrix
+    fn has_calibration_matrix(&self) -> bool;
+    /// Current calibration matrix, if any
+    ///
+    /// The matrix is given by its first two rows in row-major order, the last row
+    /// always being `[0, 0, 1]`.
+    fn calibration_matrix(&self) -> Option<[f32; 6]>;
+    /// Set the calibration matrix
+    ///
+    /// The matrix is applied to the normalized device coordinates of absolute events.
+    fn set_calibration_matrix(&mut self, matrix: [f32; 6]) -> Result<(), DeviceConfigError>;
+}
+
+/// Used as the device type of [`InputBackend`]s which do not expose any device.
+///
+/// Initialization is not possible, like for [`UnusedEvent`].
+pub enum UnusedDevice {}
+
+impl InputDevice for UnusedDevice {
+    fn name(&self) -> &str {
+        match *self {}
+    }
+
+    fn has_capability(&self, _capability: DeviceCapability) -> bool {
+        match *self {}
+    }
+
+    fn tap_finger_count(&self) -> u32 {
+        match *self {}
+    }
+
+    fn tap_enabled(&self) -> bool {
+        match *self {}
+    }
+
+    fn set_tap_enabled(&mut self, _enabled: bool) -> Result<(), DeviceConfigError> {
+        match *self {}
+    }
+
+    fn has_natural_scroll(&self) -> bool {
+        match *self {}
+    }
+
+    fn natural_scroll_enabled(&self) -> bool {
+        match *self {}
+    }
+
+    fn set_natural_scroll_enabled(&mut self, _enabled: bool) -> Result<(), DeviceConfigError> {
+        match *self {}
+    }
+
+    fn has_accel(&self) -> bool {
+        match *self {}
+    }
+
+    fn accel_speed(&self) -> f64 {
+        match *self {}
+    }
+
+    fn set_accel_speed(&mut self, _speed: f64) -> Result<(), DeviceConfigError> {
+        match *self {}
+    }
+
+    fn accel_profiles(&self) -> Vec<AccelProfile> {
+        match *self {}
+    }
+
+    fn accel_profile(&self) -> Option<AccelProfile> {
+        match *self {}
+    }
+
+    fn set_accel_profile(&mut self, _profile: AccelProfile) -> Result<(), DeviceConfigError> {
+        match *self {}
+    }
+
+    fn has_left_handed(&self) -> bool {
+        match *self {}
+    }
+
+    fn left_handed(&self) -> bool {
+        match *self {}
+    }
+
+    fn set_left_handed(&mut self, _enabled: bool) -> Result<(), DeviceConfigError> {
+        match *self {}
+    }
+
+    fn has_calibration_matrix(&self) -> bool {
+        match *self {}
+    }
+
+    fn calibration_matrix(&self) -> Option<[f32; 6]> {
+        match *self {}
+    }
+
+    fn set_calibration_matrix(&mut self, _matrix: [f32; 6]) -> Result<(), DeviceConfigError> {
+        match *self {}
+    }
+}
+
+/// Trait for the `InputConfig` of [`InputBackend`]s, giving access to their devices
+///
+/// This allows to configure devices independently of the backend in use, for example
+/// from [`InputHandler::on_input_config_changed`] when devices are plugged:
+///
+/// ```
+/// # extern crate smithay;
+/// use smithay::backend::input::{DeviceCapability, InputDevice, InputDevices};
+///
+/// fn enable_tap<C: InputDevices + ?Sized>(config: &mut C) {
+///     for device in config.devices() {
+///         if device.has_capability(DeviceCapability::Pointer) && device.tap_finger_count() > 0 {
+///             let _ = device.set_tap_enabled(true);
+///         }
+///     }
+/// }
+/// # fn main() {}
+/// ```
+pub trait InputDevices {
+    /// Type of the devices
+    type Device: InputDevice;
+
+    /// Access the devices currently known to the backend
+    fn devices(&mut self) -> &mut [Self::Device];
+}
+
+/// Backends without configurable devices
+impl InputDevices for () {
+    type Device = UnusedDevice;
+
+    fn devices(&mut self) -> &mut [UnusedDevice] {
+        &mut []
+    }
+}
+
 /// Trait that describes objects providing a source of input events. All input backends
 /// need to implement this and provide the same base guarantees about the precision of
 /// given events.
 pub trait InputBackend: Sized {
     /// Type of input device associated with the backend
-    type InputConfig: ?Sized;
+    type InputConfig: InputDevices + ?Sized;
 
     /// Type representing errors that may be returned when processing events
     type EventError: Error;
@@ -517,6 +1248,34 @@ pub trait InputBackend: Sized {
     type TouchCancelEvent: TouchCancelEvent;
     /// Type representing touch frame events
     type TouchFrameEvent: TouchFrameEvent;
+    /// Type representing the beginning of swipe gestures
+    type GestureSwipeBeginEvent: GestureBeginEvent;
+    /// Type representing motion events of swipe gestures
+    type GestureSwipeUpdateEvent: GestureSwipeUpdateEvent;
+    /// Type representing the end of swipe gestures
+    type GestureSwipeEndEvent: GestureEndEvent;
+    /// Type representing the beginning of pinch gestures
+    type GesturePinchBeginEvent: GestureBeginEvent;
+    /// Type representing motion events of pinch gestures
+    type GesturePinchUpdateEvent: GesturePinchUpdateEvent;
+    /// Type representing the end of pinch gestures
+    type GesturePinchEndEvent: GestureEndEvent;
+    /// Type representing axis events of tablet tools
+    type TabletToolAxisEvent: TabletToolAxisEvent;
+    /// Type representing proximity events of tablet tools
+    type TabletToolProximityEvent: TabletToolProximityEvent;
+    /// Type representing tip events of tablet tools
+    type TabletToolTipEvent: TabletToolTipEvent;
+    /// Type representing button events of tablet tools
+    type TabletToolButtonEvent: TabletToolButtonEvent;
+    /// Type representing button events of tablet pads
+    type TabletPadButtonEvent: TabletPadButtonEvent;
+    /// Type representing ring events of tablet pads
+    type TabletPadRingEvent: TabletPadRingEvent;
+    /// Type representing strip events of tablet pads
+    type TabletPadStripEvent: TabletPadStripEvent;
+    /// Type representing switch toggle events
+    type SwitchToggleEvent: SwitchToggleEvent;
 
     /// Sets a new handler for this [`InputBackend`]
     fn set_handler<H: InputHandler<Self> + 'static>(&mut self, handler: H);
@@ -619,6 +1378,164 @@ pub trait InputHandler<B: InputBackend> {
     /// - `event` - The touch frame event.
     fn on_touch_frame(&mut self, seat: &Seat, event: B::TouchFrameEvent);
 
+    /// Called when a new swipe gesture begin event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The swipe gesture begin event
+    fn on_gesture_swipe_begin(&mut self, seat: &Seat, event: B::GestureSwipeBeginEvent) {
+        let _ = (seat, event);
+    }
+    /// Called when a new swipe gesture update event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The swipe gesture update event
+    fn on_gesture_swipe_update(&mut self, seat: &Seat, event: B::GestureSwipeUpdateEvent) {
+        let _ = (seat, event);
+    }
+    /// Called when a new swipe gesture end event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The swipe gesture end event
+    fn on_gesture_swipe_end(&mut self, seat: &Seat, event: B::GestureSwipeEndEvent) {
+        let _ = (seat, event);
+    }
+    /// Called when a new pinch gesture begin event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The pinch gesture begin event
+    fn on_gesture_pinch_begin(&mut self, seat: &Seat, event: B::GesturePinchBeginEvent) {
+        let _ = (seat, event);
+    }
+    /// Called when a new pinch gesture update event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The pinch gesture update event
+    fn on_gesture_pinch_update(&mut self, seat: &Seat, event: B::GesturePinchUpdateEvent) {
+        let _ = (seat, event);
+    }
+    /// Called when a new pinch gesture end event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The pinch gesture end event
+    fn on_gesture_pinch_end(&mut self, seat: &Seat, event: B::GesturePinchEndEvent) {
+        let _ = (seat, event);
+    }
+
+    /// Called when a new tablet tool axis event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The tablet tool axis event
+    fn on_tablet_tool_axis(&mut self, seat: &Seat, event: B::TabletToolAxisEvent) {
+        let _ = (seat, event);
+    }
+    /// Called when a new tablet tool proximity event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The tablet tool proximity event
+    fn on_tablet_tool_proximity(&mut self, seat: &Seat, event: B::TabletToolProximityEvent) {
+        let _ = (seat, event);
+    }
+    /// Called when a new tablet tool tip event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The tablet tool tip event
+    fn on_tablet_tool_tip(&mut self, seat: &Seat, event: B::TabletToolTipEvent) {
+        let _ = (seat, event);
+    }
+    /// Called when a new tablet tool button event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The tablet tool button event
+    fn on_tablet_tool_button(&mut self, seat: &Seat, event: B::TabletToolButtonEvent) {
+        let _ = (seat, event);
+    }
+
+    /// Called when a new tablet pad button event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The tablet pad button event
+    fn on_tablet_pad_button(&mut self, seat: &Seat, event: B::TabletPadButtonEvent) {
+        let _ = (seat, event);
+    }
+    /// Called when a new tablet pad ring event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The tablet pad ring event
+    fn on_tablet_pad_ring(&mut self, seat: &Seat, event: B::TabletPadRingEvent) {
+        let _ = (seat, event);
+    }
+    /// Called when a new tablet pad strip event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The tablet pad strip event
+    fn on_tablet_pad_strip(&mut self, seat: &Seat, event: B::TabletPadStripEvent) {
+        let _ = (seat, event);
+    }
+
+    /// Called when a new switch toggle event was received.
+    ///
+    /// The default implementation ignores the event.
+    ///
+    /// # Arguments
+    ///
+    /// - `seat` - The [`Seat`] the event belongs to
+    /// - `event` - The switch toggle event
+    fn on_switch_toggle(&mut self, seat: &Seat, event: B::SwitchToggleEvent) {
+        let _ = (seat, event);
+    }
+
     /// Called when the `InputConfig` was changed through an external event.
     ///
     /// What kind of events can trigger this call is completely backend dependent.
@@ -679,6 +1596,62 @@ impl<B: InputBackend> InputHandler<B> for Box<dyn InputHandler<B>> {
         (**self).on_touch_frame(seat, event)
     }
 
+    fn on_gesture_swipe_begin(&mut self, seat: &Seat, event: B::GestureSwipeBeginEvent) {
+        (**self).on_gesture_swipe_begin(seat, event)
+    }
+
+    fn on_gesture_swipe_update(&mut self, seat: &Seat, event: B::GestureSwipeUpdateEvent) {
+        (**self).on_gesture_swipe_update(seat, event)
+    }
+
+    fn on_gesture_swipe_end(&mut self, seat: &Seat, event: B::GestureSwipeEndEvent) {
+        (**self).on_gesture_swipe_end(seat, event)
+    }
+
+    fn on_gesture_pinch_begin(&mut self, seat: &Seat, event: B::GesturePinchBeginEvent) {
+        (**self).on_gesture_pinch_begin(seat, event)
+    }
+
+    fn on_gesture_pinch_update(&mut self, seat: &Seat, event: B::GesturePinchUpdateEvent) {
+        (**self).on_gesture_pinch_update(seat, event)
+    }
+
+    fn on_gesture_pinch_end(&mut self, seat: &Seat, event: B::GesturePinchEndEvent) {
+        (**self).on_gesture_pinch_end(seat, event)
+    }
+
+    fn on_tablet_tool_axis(&mut self, seat: &Seat, event: B::TabletToolAxisEvent) {
+        (**self).on_tablet_tool_axis(seat, event)
+    }
+
+    fn on_tablet_tool_proximity(&mut self, seat: &Seat, event: B::TabletToolProximityEvent) {
+        (**self).on_tablet_tool_proximity(seat, event)
+    }
+
+    fn on_tablet_tool_tip(&mut self, seat: &Seat, event: B::TabletToolTipEvent) {
+        (**self).on_tablet_tool_tip(seat, event)
+    }
+
+    fn on_tablet_tool_button(&mut self, seat: &Seat, event: B::TabletToolButtonEvent) {
+        (**self).on_tablet_tool_button(seat, event)
+    }
+
+    fn on_tablet_pad_button(&mut self, seat: &Seat, event: B::TabletPadButtonEvent) {
+        (**self).on_tablet_pad_button(seat, event)
+    }
+
+    fn on_tablet_pad_ring(&mut self, seat: &Seat, event: B::TabletPadRingEvent) {
+        (**self).on_tablet_pad_ring(seat, event)
+    }
+
+    fn on_tablet_pad_strip(&mut self, seat: &Seat, event: B::TabletPadStripEvent) {
+        (**self).on_tablet_pad_strip(seat, event)
+    }
+
+    fn on_switch_toggle(&mut self, seat: &Seat, event: B::SwitchToggleEvent) {
+        (**self).on_switch_toggle(seat, event)
+    }
+
     fn on_input_config_changed(&mut self, config: &mut B::InputConfig) {
         (**self).on_input_config_changed(config)
     }
@@ -248,6 +248,416 @@ impl backend::Event for event::touch::TouchFrameEvent {
 
 impl backend::TouchFrameEvent for event::touch::TouchFrameEvent {}
 
+impl backend::InputDevice for libinput::Device {
+    fn name(&self) -> &str {
+        self.name()
+    }
+
+    fn has_capability(&self, capability: backend::DeviceCapability) -> bool {
+        self.has_capability(match capability {
+            backend::DeviceCapability::Keyboard => libinput::DeviceCapability::Keyboard,
+            backend::DeviceCapability::Pointer => libinput::DeviceCapability::Pointer,
+            backend::DeviceCapability::Touch => libinput::DeviceCapability::Touch,
+            backend::DeviceCapability::TabletTool => libinput::DeviceCapability::TabletTool,
+            backend::DeviceCapability::TabletPad => libinput::DeviceCapability::TabletPad,
+            backend::DeviceCapability::Gesture => libinput::DeviceCapability::Gesture,
+            backend::DeviceCapability::Switch => libinput::DeviceCapability::Switch,
+        })
+    }
+
+    fn tap_finger_count(&self) -> u32 {
+        self.config_tap_finger_count()
+    }
+
+    fn tap_enabled(&self) -> bool {
+        self.config_tap_enabled()
+    }
+
+    fn set_tap_enabled(&mut self, enabled: bool) -> Result<(), backend::DeviceConfigError> {
+        self.config_tap_set_enabled(enabled).map_err(Into::into)
+    }
+
+    fn has_natural_scroll(&self) -> bool {
+        self.config_scroll_has_natural_scroll()
+    }
+
+    fn natural_scroll_enabled(&self) -> bool {
+        self.config_scroll_natural_scroll_enabled()
+    }
+
+    fn set_natural_scroll_enabled(&mut self, enabled: bool) -> Result<(), backend::DeviceConfigError> {
+        self.config_scroll_set_natural_scroll_enabled(enabled)
+            .map_err(Into::into)
+    }
+
+    fn has_accel(&self) -> bool {
+        self.config_accel_is_available()
+    }
+
+    fn accel_speed(&self) -> f64 {
+        self.config_accel_speed()
+    }
+
+    fn set_accel_speed(&mut self, speed: f64) -> Result<(), backend::DeviceConfigError> {
+        self.config_accel_set_speed(speed).map_err(Into::into)
+    }
+
+    fn accel_profiles(&self) -> Vec<backend::AccelProfile> {
+        self.config_accel_profiles().into_iter().map(Into::into).collect()
+    }
+
+    fn accel_profile(&self) -> Option<backend::AccelProfile> {
+        self.config_accel_profile().map(Into::into)
+    }
+
+    fn set_accel_profile(
+        &mut self,
+        profile: backend::AccelProfile,
+    ) -> Result<(), backend::DeviceConfigError> {
+        self.config_accel_set_profile(profile.into()).map_err(Into::into)
+    }
+
+    fn has_left_handed(&self) -> bool {
+        self.config_left_handed_is_available()
+    }
+
+    fn left_handed(&self) -> bool {
+        self.config_left_handed()
+    }
+
+    fn set_left_handed(&mut self, enabled: bool) -> Result<(), backend::DeviceConfigError> {
+        self.config_left_handed_set(enabled).map_err(Into::into)
+    }
+
+    fn has_calibration_matrix(&self) -> bool {
+        self.config_calibration_has_matrix()
+    }
+
+    fn calibration_matrix(&self) -> Option<[f32; 6]> {
+        self.config_calibration_matrix()
+    }
+
+    fn set_calibration_matrix(&mut self, matrix: [f32; 6]) -> Result<(), backend::DeviceConfigError> {
+        self.config_calibration_set_matrix(matrix).map_err(Into::into)
+    }
+}
+
+impl backend::InputDevices for [libinput::Device] {
+    type Device = libinput::Device;
+
+    fn devices(&mut self) -> &mut [libinput::Device] {
+        self
+    }
+}
+
+impl backend::Event for event::gesture::GestureSwipeBeginEvent {
+    fn time(&self) -> u32 {
+        event::gesture::GestureEventTrait::time(self)
+    }
+}
+
+impl backend::GestureBeginEvent for event::gesture::GestureSwipeBeginEvent {
+    fn fingers(&self) -> u32 {
+        event::gesture::GestureEventTrait::finger_count(self) as u32
+    }
+}
+
+impl backend::Event for event::gesture::GestureSwipeUpdateEvent {
+    fn time(&self) -> u32 {
+        event::gesture::GestureEventTrait::time(self)
+    }
+}
+
+impl backend::GestureSwipeUpdateEvent for event::gesture::GestureSwipeUpdateEvent {
+    fn delta_x(&self) -> f64 {
+        event::gesture::GestureEventCoordinates::dx(self)
+    }
+
+    fn delta_y(&self) -> f64 {
+        event::gesture::GestureEventCoordinates::dy(self)
+    }
+}
+
+impl backend::Event for event::gesture::GestureSwipeEndEvent {
+    fn time(&self) -> u32 {
+        event::gesture::GestureEventTrait::time(self)
+    }
+}
+
+impl backend::GestureEndEvent for event::gesture::GestureSwipeEndEvent {
+    fn cancelled(&self) -> bool {
+        event::gesture::GestureEndEvent::cancelled(self)
+    }
+}
+
+impl backend::Event for event::gesture::GesturePinchBeginEvent {
+    fn time(&self) -> u32 {
+        event::gesture::GestureEventTrait::time(self)
+    }
+}
+
+impl backend::GestureBeginEvent for event::gesture::GesturePinchBeginEvent {
+    fn fingers(&self) -> u32 {
+        event::gesture::GestureEventTrait::finger_count(self) as u32
+    }
+}
+
+impl backend::Event for event::gesture::GesturePinchUpdateEvent {
+    fn time(&self) -> u32 {
+        event::gesture::GestureEventTrait::time(self)
+    }
+}
+
+impl backend::GesturePinchUpdateEvent for event::gesture::GesturePinchUpdateEvent {
+    fn delta_x(&self) -> f64 {
+        event::gesture::GestureEventCoordinates::dx(self)
+    }
+
+    fn delta_y(&self) -> f64 {
+        event::gesture::GestureEventCoordinates::dy(self)
+    }
+
+    fn scale(&self) -> f64 {
+        event::gesture::GesturePinchEventTrait::scale(self)
+    }
+
+    fn rotation(&self) -> f64 {
+        self.angle_delta()
+    }
+}
+
+impl backend::Event for event::gesture::GesturePinchEndEvent {
+    fn time(&self) -> u32 {
+        event::gesture::GestureEventTrait::time(self)
+    }
+}
+
+impl backend::GestureEndEvent for event::gesture::GesturePinchEndEvent {
+    fn cancelled(&self) -> bool {
+        event::gesture::GestureEndEvent::cancelled(self)
+    }
+}
+
+macro_rules! impl_tablet_tool_event {
+    ($ty:ty) => {
+        impl backend::Event for $ty {
+            fn time(&self) -> u32 {
+                event::tablet_tool::TabletToolEventTrait::time(self)
+            }
+        }
+
+        impl backend::TabletToolEvent for $ty {
+            fn tool(&self) -> backend::TabletToolDescriptor {
+                let tool = event::tablet_tool::TabletToolEventTrait::tool(self);
+                backend::TabletToolDescriptor {
+                    tool_type: tool.tool_type().into(),
+                    hardware_serial: tool.serial(),
+                    hardware_id_wacom: tool.tool_id(),
+                    capabilities: backend::TabletToolCapabilities {
+                        pressure: tool.has_pressure(),
+                        distance: tool.has_distance(),
+                        tilt: tool.has_tilt(),
+                        rotation: tool.has_rotation(),
+                        slider: tool.has_slider(),
+                        wheel: tool.has_wheel(),
+                    },
+                }
+            }
+
+            fn delta_x(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::dx(self)
+            }
+
+            fn delta_y(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::dy(self)
+            }
+
+            fn x(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::x(self)
+            }
+
+            fn y(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::y(self)
+            }
+
+            fn x_transformed(&self, width: u32) -> u32 {
+                event::tablet_tool::TabletToolEventTrait::x_transformed(self, width) as u32
+            }
+
+            fn y_transformed(&self, height: u32) -> u32 {
+                event::tablet_tool::TabletToolEventTrait::y_transformed(self, height) as u32
+            }
+
+            fn pressure(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::pressure(self)
+            }
+
+            fn distance(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::distance(self)
+            }
+
+            fn tilt_x(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::tilt_x(self)
+            }
+
+            fn tilt_y(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::tilt_y(self)
+            }
+
+            fn rotation(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::rotation(self)
+            }
+
+            fn slider_position(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::slider_position(self)
+            }
+
+            fn wheel_delta(&self) -> f64 {
+                event::tablet_tool::TabletToolEventTrait::wheel_delta(self)
+            }
+
+            fn wheel_delta_discrete(&self) -> i32 {
+                event::tablet_tool::TabletToolEventTrait::wheel_delta_discrete(self) as i32
+            }
+        }
+    };
+}
+
+impl_tablet_tool_event!(event::tablet_tool::TabletToolAxisEvent);
+impl_tablet_tool_event!(event::tablet_tool::TabletToolProximityEvent);
+impl_tablet_tool_event!(event::tablet_tool::TabletToolTipEvent);
+impl_tablet_tool_event!(event::tablet_tool::TabletToolButtonEvent);
+
+impl backend::TabletToolAxisEvent for event::tablet_tool::TabletToolAxisEvent {}
+
+impl backend::TabletToolProximityEvent for event::tablet_tool::TabletToolProximityEvent {
+    fn state(&self) -> backend::ProximityState {
+        match self.proximity_state() {
+            event::tablet_tool::ProximityState::In => backend::ProximityState::In,
+            event::tablet_tool::ProximityState::Out => backend::ProximityState::Out,
+        }
+    }
+}
+
+impl backend::TabletToolTipEvent for event::tablet_tool::TabletToolTipEvent {
+    fn tip_state(&self) -> backend::TipState {
+        match self.tip_state() {
+            event::tablet_tool::TipState::Up => backend::TipState::Up,
+            event::tablet_tool::TipState::Down => backend::TipState::Down,
+        }
+    }
+}
+
+impl backend::TabletToolButtonEvent for event::tablet_tool::TabletToolButtonEvent {
+    fn button(&self) -> u32 {
+        self.button()
+    }
+
+    fn button_state(&self) -> backend::MouseButtonState {
+        self.button_state().into()
+    }
+
+    fn seat_button_count(&self) -> u32 {
+        self.seat_button_count()
+    }
+}
+
+impl backend::Event for event::tablet_pad::TabletPadButtonEvent {
+    fn time(&self) -> u32 {
+        event::tablet_pad::TabletPadEventTrait::time(self)
+    }
+}
+
+impl backend::TabletPadButtonEvent for event::tablet_pad::TabletPadButtonEvent {
+    fn button(&self) -> u32 {
+        self.button_number()
+    }
+
+    fn state(&self) -> backend::MouseButtonState {
+        self.button_state().into()
+    }
+
+    fn mode(&self) -> u32 {
+        event::tablet_pad::TabletPadEventTrait::mode(self)
+    }
+}
+
+impl backend::Event for event::tablet_pad::TabletPadRingEvent {
+    fn time(&self) -> u32 {
+        event::tablet_pad::TabletPadEventTrait::time(self)
+    }
+}
+
+impl backend::TabletPadRingEvent for event::tablet_pad::TabletPadRingEvent {
+    fn number(&self) -> u32 {
+        self.number()
+    }
+
+    fn position(&self) -> f64 {
+        self.position()
+    }
+
+    fn source(&self) -> backend::TabletPadAxisSource {
+        match self.source() {
+            event::tablet_pad::RingAxisSource::Finger => backend::TabletPadAxisSource::Finger,
+            event::tablet_pad::RingAxisSource::Unknown => backend::TabletPadAxisSource::Unknown,
+        }
+    }
+
+    fn mode(&self) -> u32 {
+        event::tablet_pad::TabletPadEventTrait::mode(self)
+    }
+}
+
+impl backend::Event for event::tablet_pad::TabletPadStripEvent {
+    fn time(&self) -> u32 {
+        event::tablet_pad::TabletPadEventTrait::time(self)
+    }
+}
+
+impl backend::TabletPadStripEvent for event::tablet_pad::TabletPadStripEvent {
+    fn number(&self) -> u32 {
+        self.number()
+    }
+
+    fn position(&self) -> f64 {
+        self.position()
+    }
+
+    fn source(&self) -> backend::TabletPadAxisSource {
+        match self.source() {
+            event::tablet_pad::StripAxisSource::Finger => backend::TabletPadAxisSource::Finger,
+            event::tablet_pad::StripAxisSource::Unknown => backend::TabletPadAxisSource::Unknown,
+        }
+    }
+
+    fn mode(&self) -> u32 {
+        event::tablet_pad::TabletPadEventTrait::mode(self)
+    }
+}
+
+impl backend::Event for event::switch::SwitchToggleEvent {
+    fn time(&self) -> u32 {
+        event::switch::SwitchEventTrait::time(self)
+    }
+}
+
+impl backend::SwitchToggleEvent for event::switch::SwitchToggleEvent {
+    fn switch(&self) -> Option<backend::Switch> {
+        match self.switch() {
+            event::switch::Switch::Lid => Some(backend::Switch::Lid),
+            event::switch::Switch::TabletMode => Some(backend::Switch::TabletMode),
+        }
+    }
+
+    fn state(&self) -> backend::SwitchState {
+        match self.switch_state() {
+            event::switch::SwitchState::Off => backend::SwitchState::Off,
+            event::switch::SwitchState::On => backend::SwitchState::On,
+        }
+    }
+}
+
 impl InputBackend for LibinputInputBackend {
     type InputConfig = [libinput::Device];
     type EventError = IoError;
@@ -262,6 +672,20 @@ impl InputBackend for LibinputInputBackend {
     type TouchMotionEvent = event::touch::TouchMotionEvent;
     type TouchCancelEvent = event::touch::TouchCancelEvent;
     type TouchFrameEvent = event::touch::TouchFrameEvent;
+    type GestureSwipeBeginEvent = event::gesture::GestureSwipeBeginEvent;
+    type GestureSwipeUpdateEvent = event::gesture::GestureSwipeUpdateEvent;
+    type GestureSwipeEndEvent = event::gesture::GestureSwipeEndEvent;
+    type GesturePinchBeginEvent = event::gesture::GesturePinchBeginEvent;
+    type GesturePinchUpdateEvent = event::gesture::GesturePinchUpdateEvent;
+    type GesturePinchEndEvent = event::gesture::GesturePinchEndEvent;
+    type TabletToolAxisEvent = event::tablet_tool::TabletToolAxisEvent;
+    type TabletToolProximityEvent = event::tablet_tool::TabletToolProximityEvent;
+    type TabletToolTipEvent = event::tablet_tool::TabletToolTipEvent;
+    type TabletToolButtonEvent = event::tablet_tool::TabletToolButtonEvent;
+    type TabletPadButtonEvent = event::tablet_pad::TabletPadButtonEvent;
+    type TabletPadRingEvent = event::tablet_pad::TabletPadRingEvent;
+    type TabletPadStripEvent = event::tablet_pad::TabletPadStripEvent;
+    type SwitchToggleEvent = event::switch::SwitchToggleEvent;
 
     fn set_handler<H: backend::InputHandler<Self> + 'static>(&mut self, mut handler: H) {
         if self.handler.is_some() {
@@ -492,7 +916,142 @@ impl InputBackend for LibinputInputBackend {
                         }
                     }
                 }
-                _ => {} //FIXME: What to do with the rest.
+                libinput::Event::Gesture(gesture_event) => {
+                    use input::event::gesture::*;
+                    if let Some(ref mut handler) = self.handler {
+                        let device_seat = gesture_event.device().seat();
+                        if let Some(ref seat) = self.seats.get(&device_seat) {
+                            match gesture_event {
+                                GestureEvent::Swipe(GestureSwipeEvent::Begin(begin_event)) => {
+                                    trace!(
+                                        self.logger,
+                                        "Calling on_gesture_swipe_begin with {:?}",
+                                        begin_event
+                                    );
+                                    handler.on_gesture_swipe_begin(seat, begin_event);
+                                }
+                                GestureEvent::Swipe(GestureSwipeEvent::Update(update_event)) => {
+                                    trace!(
+                                        self.logger,
+                                        "Calling on_gesture_swipe_update with {:?}",
+                                        update_event
+                                    );
+                                    handler.on_gesture_swipe_update(seat, update_event);
+                                }
+                                GestureEvent::Swipe(GestureSwipeEvent::End(end_event)) => {
+                                    trace!(self.logger, "Calling on_gesture_swipe_end with {:?}", end_event);
+                                    handler.on_gesture_swipe_end(seat, end_event);
+                                }
+                                GestureEvent::Pinch(GesturePinchEvent::Begin(begin_event)) => {
+                                    trace!(
+                                        self.logger,
+                                        "Calling on_gesture_pinch_begin with {:?}",
+                                        begin_event
+                                    );
+                                    handler.on_gesture_pinch_begin(seat, begin_event);
+                                }
+                                GestureEvent::Pinch(GesturePinchEvent::Update(update_event)) => {
+                                    trace!(
+                                        self.logger,
+                                        "Calling on_gesture_pinch_update with {:?}",
+                                        update_event
+                                    );
+                                    handler.on_gesture_pinch_update(seat, update_event);
+                                }
+                                GestureEvent::Pinch(GesturePinchEvent::End(end_event)) => {
+                                    trace!(self.logger, "Calling on_gesture_pinch_end with {:?}", end_event);
+                                    handler.on_gesture_pinch_end(seat, end_event);
+                                }
+                            }
+                        } else {
+                            warn!(self.logger, "Received gesture event of non existing Seat");
+                            continue;
+                        }
+                    }
+                }
+                libinput::Event::Tablet(tablet_event) => {
+                    use input::event::tablet_tool::*;
+                    if let Some(ref mut handler) = self.handler {
+                        let device_seat = tablet_event.device().seat();
+                        if let Some(ref seat) = self.seats.get(&device_seat) {
+                            match tablet_event {
+                                TabletToolEvent::Axis(axis_event) => {
+                                    trace!(self.logger, "Calling on_tablet_tool_axis with {:?}", axis_event);
+                                    handler.on_tablet_tool_axis(seat, axis_event);
+                                }
+                                TabletToolEvent::Proximity(proximity_event) => {
+                                    trace!(
+                                        self.logger,
+                                        "Calling on_tablet_tool_proximity with {:?}",
+                                        proximity_event
+                                    );
+                                    handler.on_tablet_tool_proximity(seat, proximity_event);
+                                }
+                                TabletToolEvent::Tip(tip_event) => {
+                                    trace!(self.logger, "Calling on_tablet_tool_tip with {:?}", tip_event);
+                                    handler.on_tablet_tool_tip(seat, tip_event);
+                                }
+                                TabletToolEvent::Button(button_event) => {
+                                    trace!(
+                                        self.logger,
+                                        "Calling on_tablet_tool_button with {:?}",
+                                        button_event
+                                    );
+                                    handler.on_tablet_tool_button(seat, button_event);
+                                }
+                            }
+                        } else {
+                            warn!(self.logger, "Received tablet tool event of non existing Seat");
+                            continue;
+                        }
+                    }
+                }
+                libinput::Event::TabletPad(pad_event) => {
+                    use input::event::tablet_pad::*;
+                    if let Some(ref mut handler) = self.handler {
+                        let device_seat = pad_event.device().seat();
+                        if let Some(ref seat) = self.seats.get(&device_seat) {
+                            match pad_event {
+                                TabletPadEvent::Button(button_event) => {
+                                    trace!(
+                                        self.logger,
+                                        "Calling on_tablet_pad_button with {:?}",
+                                        button_event
+                                    );
+                                    handler.on_tablet_pad_button(seat, button_event);
+                                }
+                                TabletPadEvent::Ring(ring_event) => {
+                                    trace!(self.logger, "Calling on_tablet_pad_ring with {:?}", ring_event);
+                                    handler.on_tablet_pad_ring(seat, ring_event);
+                                }
+                                TabletPadEvent::Strip(strip_event) => {
+                                    trace!(self.logger, "Calling on_tablet_pad_strip with {:?}", strip_event);
+                                    handler.on_tablet_pad_strip(seat, strip_event);
+                                }
+                            }
+                        } else {
+                            warn!(self.logger, "Received tablet pad event of non existing Seat");
+                            continue;
+                        }
+                    }
+                }
+                libinput::Event::Switch(switch_event) => {
+                    use input::event::switch::*;
+                    if let Some(ref mut handler) = self.handler {
+                        let device_seat = switch_event.device().seat();
+                        if let Some(ref seat) = self.seats.get(&device_seat) {
+                            match switch_event {
+                                SwitchEvent::Toggle(toggle_event) => {
+                                    trace!(self.logger, "Calling on_switch_toggle with {:?}", toggle_event);
+                                    handler.on_switch_toggle(seat, toggle_event);
+                                }
+                            }
+                        } else {
+                            warn!(self.logger, "Received switch event of non existing Seat");
+                            continue;
+                        }
+                    }
+                }
             }
         }
         Ok(())
@@ -546,6 +1105,47 @@ impl From<event::pointer::ButtonState> for backend::MouseButtonState {
     }
 }
 
+impl From<event::tablet_tool::TabletToolType> for backend::TabletToolType {
+    fn from(libinput: event::tablet_tool::TabletToolType) -> Self {
+        match libinput {
+            event::tablet_tool::TabletToolType::Pen => backend::TabletToolType::Pen,
+            event::tablet_tool::TabletToolType::Eraser => backend::TabletToolType::Eraser,
+            event::tablet_tool::TabletToolType::Brush => backend::TabletToolType::Brush,
+            event::tablet_tool::TabletToolType::Pencil => backend::TabletToolType::Pencil,
+            event::tablet_tool::TabletToolType::Airbrush => backend::TabletToolType::Airbrush,
+            event::tablet_tool::TabletToolType::Mouse => backend::TabletToolType::Mouse,
+            event::tablet_tool::TabletToolType::Lens => backend::TabletToolType::Lens,
+        }
+    }
+}
+
+impl From<libinput::AccelProfile> for backend::AccelProfile {
+    fn from(libinput: libinput::AccelProfile) -> Self {
+        match libinput {
+            libinput::AccelProfile::Flat => backend::AccelProfile::Flat,
+            libinput::AccelProfile::Adaptive => backend::AccelProfile::Adaptive,
+        }
+    }
+}
+
+impl From<backend::AccelProfile> for libinput::AccelProfile {
+    fn from(profile: backend::AccelProfile) -> Self {
+        match profile {
+            backend::AccelProfile::Flat => libinput::AccelProfile::Flat,
+            backend::AccelProfile::Adaptive => libinput::AccelProfile::Adaptive,
+        }
+    }
+}
+
+impl From<libinput::DeviceConfigError> for backend::DeviceConfigError {
+    fn from(libinput: libinput::DeviceConfigError) -> Self {
+        match libinput {
+            libinput::DeviceConfigError::Unsupported => backend::DeviceConfigError::Unsupported,
+            libinput::DeviceConfigError::Invalid => backend::DeviceConfigError::Invalid,
+        }
+    }
+}
+
 #[cfg(feature = "backend_session")]
 impl SessionObserver for libinput::Libinput {
     fn pause(&mut self, device: Option<(u32, u32)>) {
@@ -0,0 +1,1936 @@
+//! Implementation of a mock input backend
+//!
+//! This backend does not read any input device, events are instead injected into it
+//! by the compositor. It is mostly useful to exercise the input handling logic of a
+//! compositor in automated tests, in combination with the [`headless`](::backend::headless)
+//! graphics backend.
+//!
+//! Events are queued using [`MockInputBackend::push_event`], or described by a simple text
+//! script given to [`MockInputBackend::push_script`]. They are delivered in order to the
+//! [`InputHandler`] on the next call to
+//! [`dispatch_new_events`](::backend::input::InputBackend::dispatch_new_events), including
+//! the creation and destruction of seats.
+
+use crate::backend::input::{
+    Axis, AxisSource, Event, GestureBeginEvent, GestureEndEvent, GesturePinchUpdateEvent,
+    GestureSwipeUpdateEvent, InputBackend, InputHandler, KeyState, KeyboardKeyEvent, MouseButton,
+    MouseButtonState, PointerAxisEvent, PointerButtonEvent, PointerMotionAbsoluteEvent, PointerMotionEvent,
+    ProximityState, Seat, SeatCapabilities, Switch, SwitchState, SwitchToggleEvent, TabletPadAxisSource,
+    TabletPadButtonEvent, TabletPadRingEvent, TabletPadStripEvent, TabletToolAxisEvent,
+    TabletToolButtonEvent, TabletToolCapabilities, TabletToolDescriptor, TabletToolEvent,
+    TabletToolProximityEvent, TabletToolTipEvent, TabletToolType, TipState, TouchCancelEvent, TouchDownEvent,
+    TouchFrameEvent, TouchMotionEvent, TouchSlot, TouchUpEvent,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    error, fmt,
+};
+
+/// Errors thrown by the [`MockInputBackend`]
+///
+/// Dispatching mock events cannot fail, this type cannot be instantiated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockInputError {}
+
+impl fmt::Display for MockInputError {
+    fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {}
+    }
+}
+
+impl error::Error for MockInputError {
+    fn description(&self) -> &str {
+        match *self {}
+    }
+}
+
+/// Mock keyboard event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockKeyboardKeyEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Code of the key, see `linux/input-event-codes.h`
+    pub key_code: u32,
+    /// State of the key
+    pub state: KeyState,
+    /// Total number of keys pressed on the seat
+    pub count: u32,
+}
+
+impl Event for MockKeyboardKeyEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl KeyboardKeyEvent for MockKeyboardKeyEvent {
+    fn key_code(&self) -> u32 {
+        self.key_code
+    }
+
+    fn state(&self) -> KeyState {
+        self.state
+    }
+
+    fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+/// Mock relative pointer motion event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockPointerMotionEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Relative motion of the pointer
+    pub delta: (i32, i32),
+}
+
+impl Event for MockPointerMotionEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl PointerMotionEvent for MockPointerMotionEvent {
+    fn delta_x(&self) -> i32 {
+        self.delta.0
+    }
+
+    fn delta_y(&self) -> i32 {
+        self.delta.1
+    }
+}
+
+/// Mock absolute pointer motion event
+///
+/// The position is expressed as a fraction of the target coordinate space, `(0.0, 0.0)`
+/// being its top-left corner and `(1.0, 1.0)` its bottom-right corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockPointerMotionAbsoluteEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Position of the pointer
+    pub position: (f64, f64),
+}
+
+impl Event for MockPointerMotionAbsoluteEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl PointerMotionAbsoluteEvent for MockPointerMotionAbsoluteEvent {
+    fn x(&self) -> f64 {
+        self.position.0
+    }
+
+    fn y(&self) -> f64 {
+        self.position.1
+    }
+
+    fn x_transformed(&self, width: u32) -> u32 {
+        (self.position.0 * f64::from(width)).max(0.0) as u32
+    }
+
+    fn y_transformed(&self, height: u32) -> u32 {
+        (self.position.1 * f64::from(height)).max(0.0) as u32
+    }
+}
+
+/// Mock pointer button event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockPointerButtonEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Button of the event
+    pub button: MouseButton,
+    /// State of the button
+    pub state: MouseButtonState,
+}
+
+impl Event for MockPointerButtonEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl PointerButtonEvent for MockPointerButtonEvent {
+    fn button(&self) -> MouseButton {
+        self.button
+    }
+
+    fn state(&self) -> MouseButtonState {
+        self.state
+    }
+}
+
+/// Mock pointer axis event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockPointerAxisEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Source of the scroll event
+    pub source: AxisSource,
+    /// Amount of scrolling on the horizontal and vertical axis, in pixels
+    pub amount: (Option<f64>, Option<f64>),
+    /// Amount of scrolling on the horizontal and vertical axis, in discrete steps
+    pub amount_discrete: (Option<f64>, Option<f64>),
+}
+
+impl Event for MockPointerAxisEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl PointerAxisEvent for MockPointerAxisEvent {
+    fn amount(&self, axis: &Axis) -> Option<f64> {
+        match *axis {
+            Axis::Horizontal => self.amount.0,
+            Axis::Vertical => self.amount.1,
+        }
+    }
+
+    fn amount_discrete(&self, axis: &Axis) -> Option<f64> {
+        match *axis {
+            Axis::Horizontal => self.amount_discrete.0,
+            Axis::Vertical => self.amount_discrete.1,
+        }
+    }
+
+    fn source(&self) -> AxisSource {
+        self.source
+    }
+}
+
+/// Mock touch down event
+///
+/// The position is expressed as a fraction of the target coordinate space, like for
+/// [`MockPointerMotionAbsoluteEvent`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockTouchDownEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Slot of the touch point, if the device is multi-touch
+    pub slot: Option<u64>,
+    /// Position of the touch point
+    pub position: (f64, f64),
+}
+
+impl Event for MockTouchDownEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl TouchDownEvent for MockTouchDownEvent {
+    fn slot(&self) -> Option<TouchSlot> {
+        self.slot.map(TouchSlot::new)
+    }
+
+    fn x(&self) -> f64 {
+        self.position.0
+    }
+
+    fn y(&self) -> f64 {
+        self.position.1
+    }
+
+    fn x_transformed(&self, width: u32) -> u32 {
+        (self.position.0 * f64::from(width)).max(0.0) as u32
+    }
+
+    fn y_transformed(&self, height: u32) -> u32 {
+        (self.position.1 * f64::from(height)).max(0.0) as u32
+    }
+}
+
+/// Mock touch motion event
+///
+/// The position is expressed as a fraction of the target coordinate space, like for
+/// [`MockPointerMotionAbsoluteEvent`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockTouchMotionEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Slot of the touch point, if the device is multi-touch
+    pub slot: Option<u64>,
+    /// Position of the touch point
+    pub position: (f64, f64),
+}
+
+impl Event for MockTouchMotionEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl TouchMotionEvent for MockTouchMotionEvent {
+    fn slot(&self) -> Option<TouchSlot> {
+        self.slot.map(TouchSlot::new)
+    }
+
+    fn x(&self) -> f64 {
+        self.position.0
+    }
+
+    fn y(&self) -> f64 {
+        self.position.1
+    }
+
+    fn x_transformed(&self, width: u32) -> u32 {
+        (self.position.0 * f64::from(width)).max(0.0) as u32
+    }
+
+    fn y_transformed(&self, height: u32) -> u32 {
+        (self.position.1 * f64::from(height)).max(0.0) as u32
+    }
+}
+
+/// Mock touch up event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockTouchUpEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Slot of the touch point, if the device is multi-touch
+    pub slot: Option<u64>,
+}
+
+impl Event for MockTouchUpEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl TouchUpEvent for MockTouchUpEvent {
+    fn slot(&self) -> Option<TouchSlot> {
+        self.slot.map(TouchSlot::new)
+    }
+}
+
+/// Mock touch cancel event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockTouchCancelEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Slot of the touch point, if the device is multi-touch
+    pub slot: Option<u64>,
+}
+
+impl Event for MockTouchCancelEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl TouchCancelEvent for MockTouchCancelEvent {
+    fn slot(&self) -> Option<TouchSlot> {
+        self.slot.map(TouchSlot::new)
+    }
+}
+
+/// Mock touch frame event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockTouchFrameEvent {
+    /// Time of the event
+    pub time: u32,
+}
+
+impl Event for MockTouchFrameEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl TouchFrameEvent for MockTouchFrameEvent {}
+
+/// Mock event starting a swipe or pinch gesture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockGestureBeginEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Number of fingers used for the gesture
+    pub fingers: u32,
+}
+
+impl Event for MockGestureBeginEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl GestureBeginEvent for MockGestureBeginEvent {
+    fn fingers(&self) -> u32 {
+        self.fingers
+    }
+}
+
+/// Mock event ending a swipe or pinch gesture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockGestureEndEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Whether the gesture was cancelled
+    pub cancelled: bool,
+}
+
+impl Event for MockGestureEndEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl GestureEndEvent for MockGestureEndEvent {
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+/// Mock swipe gesture update event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockGestureSwipeUpdateEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Motion of the center of the gesture
+    pub delta: (f64, f64),
+}
+
+impl Event for MockGestureSwipeUpdateEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl GestureSwipeUpdateEvent for MockGestureSwipeUpdateEvent {
+    fn delta_x(&self) -> f64 {
+        self.delta.0
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.delta.1
+    }
+}
+
+/// Mock pinch gesture update event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockGesturePinchUpdateEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Motion of the center of the gesture
+    pub delta: (f64, f64),
+    /// Absolute scale of the pinch
+    pub scale: f64,
+    /// Rotation since the last event, in degrees clockwise
+    pub rotation: f64,
+}
+
+impl Event for MockGesturePinchUpdateEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl GesturePinchUpdateEvent for MockGesturePinchUpdateEvent {
+    fn delta_x(&self) -> f64 {
+        self.delta.0
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.delta.1
+    }
+
+    fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn rotation(&self) -> f64 {
+        self.rotation
+    }
+}
+
+/// Mock tablet tool axis event
+///
+/// It also holds the state of the tool for the other tablet tool events. The position is
+/// expressed as a fraction of the target coordinate space, like for
+/// [`MockPointerMotionAbsoluteEvent`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockTabletToolEvent {
+    /// Time of the event
+    pub time: u32,
+    /// The tool generating the event
+    pub tool: TabletToolDescriptor,
+    /// Position of the tool
+    pub position: (f64, f64),
+    /// Motion of the tool since the last event
+    pub delta: (f64, f64),
+    /// Pressure applied by the tool
+    pub pressure: f64,
+    /// Distance between the tool and the tablet
+    pub distance: f64,
+    /// Tilt of the tool, in degrees
+    pub tilt: (f64, f64),
+    /// Rotation of the tool, in degrees
+    pub rotation: f64,
+    /// Position of the slider of the tool
+    pub slider_position: f64,
+    /// Delta of the wheel of the tool, in degrees
+    pub wheel_delta: f64,
+    /// Delta of the wheel of the tool, in discrete steps
+    pub wheel_delta_discrete: i32,
+}
+
+impl Event for MockTabletToolEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl TabletToolEvent for MockTabletToolEvent {
+    fn tool(&self) -> TabletToolDescriptor {
+        self.tool
+    }
+
+    fn delta_x(&self) -> f64 {
+        self.delta.0
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.delta.1
+    }
+
+    fn x(&self) -> f64 {
+        self.position.0
+    }
+
+    fn y(&self) -> f64 {
+        self.position.1
+    }
+
+    fn x_transformed(&self, width: u32) -> u32 {
+        (self.position.0 * f64::from(width)).max(0.0) as u32
+    }
+
+    fn y_transformed(&self, height: u32) -> u32 {
+        (self.position.1 * f64::from(height)).max(0.0) as u32
+    }
+
+    fn pressure(&self) -> f64 {
+        self.pressure
+    }
+
+    fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    fn tilt_x(&self) -> f64 {
+        self.tilt.0
+    }
+
+    fn tilt_y(&self) -> f64 {
+        self.tilt.1
+    }
+
+    fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    fn slider_position(&self) -> f64 {
+        self.slider_position
+    }
+
+    fn wheel_delta(&self) -> f64 {
+        self.wheel_delta
+    }
+
+    fn wheel_delta_discrete(&self) -> i32 {
+        self.wheel_delta_discrete
+    }
+}
+
+impl TabletToolAxisEvent for MockTabletToolEvent {}
+
+// The other tablet tool events wrap a `MockTabletToolEvent` in their `tool_event` field
+macro_rules! forward_tablet_tool_event(
+    ($event:ty) => {
+        impl Event for $event {
+            fn time(&self) -> u32 {
+                self.tool_event.time
+            }
+        }
+
+        impl TabletToolEvent for $event {
+            fn tool(&self) -> TabletToolDescriptor {
+                self.tool_event.tool()
+            }
+
+            fn delta_x(&self) -> f64 {
+                self.tool_event.delta_x()
+            }
+
+            fn delta_y(&self) -> f64 {
+                self.tool_event.delta_y()
+            }
+
+            fn x(&self) -> f64 {
+                self.tool_event.x()
+            }
+
+            fn y(&self) -> f64 {
+                self.tool_event.y()
+            }
+
+            fn x_transformed(&self, width: u32) -> u32 {
+                self.tool_event.x_transformed(width)
+            }
+
+            fn y_transformed(&self, height: u32) -> u32 {
+                self.tool_event.y_transformed(height)
+            }
+
+            fn pressure(&self) -> f64 {
+                self.tool_event.pressure()
+            }
+
+            fn distance(&self) -> f64 {
+                self.tool_event.distance()
+            }
+
+            fn tilt_x(&self) -> f64 {
+                self.tool_event.tilt_x()
+            }
+
+            fn tilt_y(&self) -> f64 {
+                self.tool_event.tilt_y()
+            }
+
+            fn rotation(&self) -> f64 {
+                self.tool_event.rotation()
+            }
+
+            fn slider_position(&self) -> f64 {
+                self.tool_event.slider_position()
+            }
+
+            fn wheel_delta(&self) -> f64 {
+                self.tool_event.wheel_delta()
+            }
+
+            fn wheel_delta_discrete(&self) -> i32 {
+                self.tool_event.wheel_delta_discrete()
+            }
+        }
+    }
+);
+
+/// Mock tablet tool proximity event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockTabletToolProximityEvent {
+    /// State of the tool
+    pub tool_event: MockTabletToolEvent,
+    /// New proximity state of the tool
+    pub state: ProximityState,
+}
+
+forward_tablet_tool_event!(MockTabletToolProximityEvent);
+
+impl TabletToolProximityEvent for MockTabletToolProximityEvent {
+    fn state(&self) -> ProximityState {
+        self.state
+    }
+}
+
+/// Mock tablet tool tip event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockTabletToolTipEvent {
+    /// State of the tool
+    pub tool_event: MockTabletToolEvent,
+    /// New state of the tip of the tool
+    pub tip_state: TipState,
+}
+
+forward_tablet_tool_event!(MockTabletToolTipEvent);
+
+impl TabletToolTipEvent for MockTabletToolTipEvent {
+    fn tip_state(&self) -> TipState {
+        self.tip_state
+    }
+}
+
+/// Mock tablet tool button event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockTabletToolButtonEvent {
+    /// State of the tool
+    pub tool_event: MockTabletToolEvent,
+    /// Code of the button, see `linux/input-event-codes.h`
+    pub button: u32,
+    /// State of the button
+    pub state: MouseButtonState,
+    /// Total number of tool buttons pressed on the seat
+    pub seat_button_count: u32,
+}
+
+forward_tablet_tool_event!(MockTabletToolButtonEvent);
+
+impl TabletToolButtonEvent for MockTabletToolButtonEvent {
+    fn button(&self) -> u32 {
+        self.button
+    }
+
+    fn button_state(&self) -> MouseButtonState {
+        self.state
+    }
+
+    fn seat_button_count(&self) -> u32 {
+        self.seat_button_count
+    }
+}
+
+/// Mock tablet pad button event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockTabletPadButtonEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Index of the button
+    pub button: u32,
+    /// State of the button
+    pub state: MouseButtonState,
+    /// Mode of the mode group of the button
+    pub mode: u32,
+}
+
+impl Event for MockTabletPadButtonEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl TabletPadButtonEvent for MockTabletPadButtonEvent {
+    fn button(&self) -> u32 {
+        self.button
+    }
+
+    fn state(&self) -> MouseButtonState {
+        self.state
+    }
+
+    fn mode(&self) -> u32 {
+        self.mode
+    }
+}
+
+/// Mock tablet pad ring or strip event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockTabletPadAxisEvent {
+    /// Time of the event
+    pub time: u32,
+    /// Index of the ring or strip
+    pub number: u32,
+    /// Position on the ring or strip
+    pub position: f64,
+    /// Source of the event
+    pub source: TabletPadAxisSource,
+    /// Mode of the mode group of the ring or strip
+    pub mode: u32,
+}
+
+impl Event for MockTabletPadAxisEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl TabletPadRingEvent for MockTabletPadAxisEvent {
+    fn number(&self) -> u32 {
+        self.number
+    }
+
+    fn position(&self) -> f64 {
+        self.position
+    }
+
+    fn source(&self) -> TabletPadAxisSource {
+        self.source
+    }
+
+    fn mode(&self) -> u32 {
+        self.mode
+    }
+}
+
+impl TabletPadStripEvent for MockTabletPadAxisEvent {
+    fn number(&self) -> u32 {
+        self.number
+    }
+
+    fn position(&self) -> f64 {
+        self.position
+    }
+
+    fn source(&self) -> TabletPadAxisSource {
+        self.source
+    }
+
+    fn mode(&self) -> u32 {
+        self.mode
+    }
+}
+
+/// Mock switch toggle event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockSwitchToggleEvent {
+    /// Time of the event
+    pub time: u32,
+    /// The toggled switch
+    pub switch: Option<Switch>,
+    /// New state of the switch
+    pub state: SwitchState,
+}
+
+impl Event for MockSwitchToggleEvent {
+    fn time(&self) -> u32 {
+        self.time
+    }
+}
+
+impl SwitchToggleEvent for MockSwitchToggleEvent {
+    fn switch(&self) -> Option<Switch> {
+        self.switch
+    }
+
+    fn state(&self) -> SwitchState {
+        self.state
+    }
+}
+
+/// An event that can be injected into a [`MockInputBackend`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockEvent {
+    /// A new seat is created
+    ///
+    /// This is ignored if a seat with the same id already exists.
+    SeatCreated {
+        /// Name of the seat
+        name: String,
+        /// Capabilities of the seat
+        capabilities: SeatCapabilities,
+    },
+    /// The capabilities of the seat changed
+    SeatChanged(SeatCapabilities),
+    /// The seat is destroyed
+    SeatDestroyed,
+    /// A keyboard key event
+    KeyboardKey(MockKeyboardKeyEvent),
+    /// A relative pointer motion event
+    PointerMotion(MockPointerMotionEvent),
+    /// An absolute pointer motion event
+    PointerMotionAbsolute(MockPointerMotionAbsoluteEvent),
+    /// A pointer button event
+    PointerButton(MockPointerButtonEvent),
+    /// A pointer axis event
+    PointerAxis(MockPointerAxisEvent),
+    /// A touch down event
+    TouchDown(MockTouchDownEvent),
+    /// A touch motion event
+    TouchMotion(MockTouchMotionEvent),
+    /// A touch up event
+    TouchUp(MockTouchUpEvent),
+    /// A touch cancel event
+    TouchCancel(MockTouchCancelEvent),
+    /// A touch frame event
+    TouchFrame(MockTouchFrameEvent),
+    /// A swipe gesture begin event
+    GestureSwipeBegin(MockGestureBeginEvent),
+    /// A swipe gesture update event
+    GestureSwipeUpdate(MockGestureSwipeUpdateEvent),
+    /// A swipe gesture end event
+    GestureSwipeEnd(MockGestureEndEvent),
+    /// A pinch gesture begin event
+    GesturePinchBegin(MockGestureBeginEvent),
+    /// A pinch gesture update event
+    GesturePinchUpdate(MockGesturePinchUpdateEvent),
+    /// A pinch gesture end event
+    GesturePinchEnd(MockGestureEndEvent),
+    /// A tablet tool axis event
+    TabletToolAxis(MockTabletToolEvent),
+    /// A tablet tool proximity event
+    TabletToolProximity(MockTabletToolProximityEvent),
+    /// A tablet tool tip event
+    TabletToolTip(MockTabletToolTipEvent),
+    /// A tablet tool button event
+    TabletToolButton(MockTabletToolButtonEvent),
+    /// A tablet pad button event
+    TabletPadButton(MockTabletPadButtonEvent),
+    /// A tablet pad ring event
+    TabletPadRing(MockTabletPadAxisEvent),
+    /// A tablet pad strip event
+    TabletPadStrip(MockTabletPadAxisEvent),
+    /// A switch toggle event
+    SwitchToggle(MockSwitchToggleEvent),
+}
+
+/// Errors that can occur when parsing a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    /// The command of a line is not known
+    UnknownCommand {
+        /// Line of the script, starting at 1
+        line: usize,
+        /// The unknown command
+        command: String,
+    },
+    /// An argument of a command could not be parsed
+    InvalidArgument {
+        /// Line of the script, starting at 1
+        line: usize,
+        /// The invalid argument
+        argument: String,
+    },
+    /// A command is missing some of its arguments
+    MissingArgument {
+        /// Line of the script, starting at 1
+        line: usize,
+    },
+    /// A command was given more arguments than it accepts
+    UnexpectedArgument {
+        /// Line of the script, starting at 1
+        line: usize,
+        /// The first extra argument
+        argument: String,
+    },
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use std::error::Error;
+        match *self {
+            ScriptError::UnknownCommand { line, ref command } => {
+                write!(f, "{} at line {}: {}", self.description(), line, command)
+            }
+            ScriptError::InvalidArgument { line, ref argument }
+            | ScriptError::UnexpectedArgument { line, ref argument } => {
+                write!(f, "{} at line {}: {}", self.description(), line, argument)
+            }
+            ScriptError::MissingArgument { line } => write!(f, "{} at line {}", self.description(), line),
+        }
+    }
+}
+
+impl error::Error for ScriptError {
+    fn description(&self) -> &str {
+        match *self {
+            ScriptError::UnknownCommand { .. } => "Unknown command",
+            ScriptError::InvalidArgument { .. } => "Invalid argument",
+            ScriptError::MissingArgument { .. } => "Missing argument",
+            ScriptError::UnexpectedArgument { .. } => "Unexpected argument",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+/// An input backend delivering injected events
+///
+/// It initially exposes a single seat with id `0`, named `"mock"`, with pointer, keyboard
+/// and touch capabilities. Additional seats can be created by injecting
+/// [`MockEvent::SeatCreated`] events.
+pub struct MockInputBackend {
+    seats: Vec<Seat>,
+    events: VecDeque<(u64, MockEvent)>,
+    handler: Option<Box<dyn InputHandler<MockInputBackend> + 'static>>,
+    input_config: (),
+    logger: ::slog::Logger,
+}
+
+impl MockInputBackend {
+    /// Create a new mock input backend
+    pub fn new<L>(logger: L) -> MockInputBackend
+    where
+        L: Into<Option<::slog::Logger>>,
+    {
+        let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "backend_mock"));
+        MockInputBackend {
+            seats: vec![Seat::new(
+                0,
+                "mock",
+                SeatCapabilities {
+                    pointer: true,
+                    keyboard: true,
+                    touch: true,
+                },
+            )],
+            events: VecDeque::new(),
+            handler: None,
+            input_config: (),
+            logger: log,
+        }
+    }
+
+    /// Queue an event for the default seat, to be delivered on the next dispatch
+    pub fn push_event(&mut self, event: MockEvent) {
+        self.push_seat_event(0, event);
+    }
+
+    /// Queue an event for the seat of given id, to be delivered on the next dispatch
+    pub fn push_seat_event(&mut self, seat: u64, event: MockEvent) {
+        trace!(self.logger, "Queuing event {:?}", event; "seat" => seat);
+        self.events.push_back((seat, event));
+    }
+
+    /// Parse a script and queue the events it describes
+    ///
+    /// A script contains one command per line. Empty lines and everything following a `#`
+    /// are ignored, any other line must be a valid command with exactly the arguments it
+    /// expects. Positions are expressed as fractions of the output, as for
+    /// [`MockPointerMotionAbsoluteEvent`], and touch slots can be replaced by `-` for
+    /// single-touch devices. The following commands are understood:
+    ///
+    /// - `seat <id> create <name> [pointer] [keyboard] [touch]`
+    /// - `seat <id> change [pointer] [keyboard] [touch]`
+    /// - `seat <id> destroy`
+    /// - `use <id>`: following events are sent to the seat of given id (`0` initially)
+    /// - `key <time> <code> pressed|released`
+    /// - `motion <time> <dx> <dy>`
+    /// - `absolute <time> <x> <y>`
+    /// - `button <time> left|right|middle|<index> pressed|released`
+    /// - `axis <time> wheel|tilt|finger|continuous horizontal|vertical <amount> [<discrete>]`
+    /// - `touch-down <time> <slot> <x> <y>`
+    /// - `touch-motion <time> <slot> <x> <y>`
+    /// - `touch-up <time> <slot>`
+    /// - `touch-cancel <time> <slot>`
+    /// - `touch-frame <time>`
+    /// - `swipe-begin <time> <fingers>`, `pinch-begin <time> <fingers>`
+    /// - `swipe-update <time> <dx> <dy>`
+    /// - `pinch-update <time> <dx> <dy> <scale> <rotation>`
+    /// - `swipe-end <time> [cancelled]`, `pinch-end <time> [cancelled]`
+    /// - `tool-proximity <time> <tool> <serial> in|out <x> <y>`
+    /// - `tool-axis <time> <tool> <serial> <x> <y> <pressure>`
+    /// - `tool-tip <time> <tool> <serial> down|up`
+    /// - `tool-button <time> <tool> <serial> <code> pressed|released`
+    /// - `pad-button <time> <index> pressed|released`
+    /// - `pad-ring <time> <index> <degrees>`, `pad-strip <time> <index> <position>`
+    /// - `switch <time> lid|tablet-mode on|off`
+    ///
+    /// Tablet tools are `pen`, `eraser`, `brush`, `pencil`, `airbrush`, `mouse` or `lens`,
+    /// identified by their hardware serial. Tip and button events are reported at the last
+    /// position of the tool.
+    ///
+    /// The script is entirely parsed before any event is queued: if an error occurs, no
+    /// event is queued.
+    ///
+    /// ```
+    /// # extern crate smithay;
+    /// use smithay::backend::mock::MockInputBackend;
+    ///
+    /// let mut backend = MockInputBackend::new(None);
+    /// backend.push_script("
+    ///     seat 1 create tablet touch
+    ///     use 1
+    ///     touch-down 10 0 0.5 0.5   # finger in the middle of the screen
+    ///     touch-frame 10
+    ///     touch-up 20 0
+    ///     touch-frame 20
+    ///     seat 1 destroy
+    /// ").unwrap();
+    /// assert_eq!(backend.pending_events(), 6);
+    /// ```
+    pub fn push_script(&mut self, script: &str) -> Result<(), ScriptError> {
+        let events = parse_script(script)?;
+        debug!(self.logger, "Queuing {} events from script", events.len());
+        for (seat, event) in events {
+            self.push_seat_event(seat, event);
+        }
+        Ok(())
+    }
+
+    /// Number of events queued and not yet dispatched
+    pub fn pending_events(&self) -> usize {
+        self.events.len()
+    }
+}
+
+impl InputBackend for MockInputBackend {
+    type InputConfig = ();
+    type EventError = MockInputError;
+
+    type KeyboardKeyEvent = MockKeyboardKeyEvent;
+    type PointerAxisEvent = MockPointerAxisEvent;
+    type PointerButtonEvent = MockPointerButtonEvent;
+    type PointerMotionEvent = MockPointerMotionEvent;
+    type PointerMotionAbsoluteEvent = MockPointerMotionAbsoluteEvent;
+    type TouchDownEvent = MockTouchDownEvent;
+    type TouchUpEvent = MockTouchUpEvent;
+    type TouchMotionEvent = MockTouchMotionEvent;
+    type TouchCancelEvent = MockTouchCancelEvent;
+    type TouchFrameEvent = MockTouchFrameEvent;
+    type GestureSwipeBeginEvent = MockGestureBeginEvent;
+    type GestureSwipeUpdateEvent = MockGestureSwipeUpdateEvent;
+    type GestureSwipeEndEvent = MockGestureEndEvent;
+    type GesturePinchBeginEvent = MockGestureBeginEvent;
+    type GesturePinchUpdateEvent = MockGesturePinchUpdateEvent;
+    type GesturePinchEndEvent = MockGestureEndEvent;
+    type TabletToolAxisEvent = MockTabletToolEvent;
+    type TabletToolProximityEvent = MockTabletToolProximityEvent;
+    type TabletToolTipEvent = MockTabletToolTipEvent;
+    type TabletToolButtonEvent = MockTabletToolButtonEvent;
+    type TabletPadButtonEvent = MockTabletPadButtonEvent;
+    type TabletPadRingEvent = MockTabletPadAxisEvent;
+    type TabletPadStripEvent = MockTabletPadAxisEvent;
+    type SwitchToggleEvent = MockSwitchToggleEvent;
+
+    fn set_handler<H: InputHandler<Self> + 'static>(&mut self, mut handler: H) {
+        if self.handler.is_some() {
+            self.clear_handler();
+        }
+        info!(self.logger, "New input handler set.");
+        for seat in &self.seats {
+            trace!(self.logger, "Calling on_seat_created with {:?}", seat);
+            handler.on_seat_created(seat);
+        }
+        self.handler = Some(Box::new(handler));
+    }
+
+    fn get_handler(&mut self) -> Option<&mut dyn InputHandler<Self>> {
+        self.handler
+            .as_mut()
+            .map(|handler| handler as &mut dyn InputHandler<Self>)
+    }
+
+    fn clear_handler(&mut self) {
+        if let Some(mut handler) = self.handler.take() {
+            for seat in &self.seats {
+                trace!(self.logger, "Calling on_seat_destroyed with {:?}", seat);
+                handler.on_seat_destroyed(seat);
+            }
+        }
+        info!(self.logger, "Removing input handler");
+    }
+
+    fn input_config(&mut self) -> &mut Self::InputConfig {
+        &mut self.input_config
+    }
+
+    /// Delivers all queued events to the set [`InputHandler`].
+    ///
+    /// Seat creation, modification and destruction events are always processed, even if
+    /// no handler is set. Input events targeting a non-existent seat are discarded.
+    fn dispatch_new_events(&mut self) -> Result<(), MockInputError> {
+        while let Some((id, event)) = self.events.pop_front() {
+            let index = self.seats.iter().position(|seat| seat.id() == id);
+            match (event, index) {
+                (MockEvent::SeatCreated { name, capabilities }, None) => {
+                    let seat = Seat::new(id, name, capabilities);
+                    if let Some(ref mut handler) = self.handler {
+                        trace!(self.logger, "Calling on_seat_created with {:?}", seat);
+                        handler.on_seat_created(&seat);
+                    }
+                    self.seats.push(seat);
+                }
+                (MockEvent::SeatCreated { .. }, Some(_)) => {
+                    warn!(self.logger, "Seat {} already exists, ignoring creation", id);
+                }
+                (MockEvent::SeatChanged(capabilities), Some(index)) => {
+                    let seat = &mut self.seats[index];
+                    *seat.capabilities_mut() = capabilities;
+                    if let Some(ref mut handler) = self.handler {
+                        trace!(self.logger, "Calling on_seat_changed with {:?}", seat);
+                        handler.on_seat_changed(seat);
+                    }
+                }
+                (MockEvent::SeatDestroyed, Some(index)) => {
+                    let seat = self.seats.remove(index);
+                    if let Some(ref mut handler) = self.handler {
+                        trace!(self.logger, "Calling on_seat_destroyed with {:?}", seat);
+                        handler.on_seat_destroyed(&seat);
+                    }
+                }
+                (event, None) => {
+                    warn!(
+                        self.logger,
+                        "Discarding event {:?} for unknown seat {}", event, id
+                    );
+                }
+                (event, Some(index)) => {
+                    let seat = &self.seats[index];
+                    let handler = match self.handler {
+                        Some(ref mut handler) => handler,
+                        None => {
+                            trace!(self.logger, "Discarding event {:?}, no handler is set", event);
+                            continue;
+                        }
+                    };
+                    match event {
+                        MockEvent::KeyboardKey(event) => handler.on_keyboard_key(seat, event),
+                        MockEvent::PointerMotion(event) => handler.on_pointer_move(seat, event),
+                        MockEvent::PointerMotionAbsolute(event) => {
+                            handler.on_pointer_move_absolute(seat, event)
+                        }
+                        MockEvent::PointerButton(event) => handler.on_pointer_button(seat, event),
+                        MockEvent::PointerAxis(event) => handler.on_pointer_axis(seat, event),
+                        MockEvent::TouchDown(event) => handler.on_touch_down(seat, event),
+                        MockEvent::TouchMotion(event) => handler.on_touch_motion(seat, event),
+                        MockEvent::TouchUp(event) => handler.on_touch_up(seat, event),
+                        MockEvent::TouchCancel(event) => handler.on_touch_cancel(seat, event),
+                        MockEvent::TouchFrame(event) => handler.on_touch_frame(seat, event),
+                        MockEvent::GestureSwipeBegin(event) => handler.on_gesture_swipe_begin(seat, event),
+                        MockEvent::GestureSwipeUpdate(event) => handler.on_gesture_swipe_update(seat, event),
+                        MockEvent::GestureSwipeEnd(event) => handler.on_gesture_swipe_end(seat, event),
+                        MockEvent::GesturePinchBegin(event) => handler.on_gesture_pinch_begin(seat, event),
+                        MockEvent::GesturePinchUpdate(event) => handler.on_gesture_pinch_update(seat, event),
+                        MockEvent::GesturePinchEnd(event) => handler.on_gesture_pinch_end(seat, event),
+                        MockEvent::TabletToolAxis(event) => handler.on_tablet_tool_axis(seat, event),
+                        MockEvent::TabletToolProximity(event) => {
+                            handler.on_tablet_tool_proximity(seat, event)
+                        }
+                        MockEvent::TabletToolTip(event) => handler.on_tablet_tool_tip(seat, event),
+                        MockEvent::TabletToolButton(event) => handler.on_tablet_tool_button(seat, event),
+                        MockEvent::TabletPadButton(event) => handler.on_tablet_pad_button(seat, event),
+                        MockEvent::TabletPadRing(event) => handler.on_tablet_pad_ring(seat, event),
+                        MockEvent::TabletPadStrip(event) => handler.on_tablet_pad_strip(seat, event),
+                        MockEvent::SwitchToggle(event) => handler.on_switch_toggle(seat, event),
+                        MockEvent::SeatCreated { .. }
+                        | MockEvent::SeatChanged(_)
+                        | MockEvent::SeatDestroyed => {
+                            unreachable!()
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_script(script: &str) -> Result<Vec<(u64, MockEvent)>, ScriptError> {
+    let mut events = Vec::new();
+    let mut current_seat = 0;
+    // number of keys pressed on each seat, to fill `KeyboardKeyEvent::count`
+    let mut pressed_keys = HashMap::<u64, u32>::new();
+    // number of tool buttons pressed on each seat, to fill `TabletToolButtonEvent::seat_button_count`
+    let mut pressed_tool_buttons = HashMap::<u64, u32>::new();
+    // last position of each tool of each seat, to fill `TabletToolEvent::delta`
+    let mut tool_positions = HashMap::<(u64, u64), (f64, f64)>::new();
+
+    for (index, line) in script.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.split('#').next().unwrap_or("");
+        let mut args = line.split_whitespace();
+        let command = match args.next() {
+            Some(command) => command,
+            None => continue,
+        };
+        let mut parser = ArgParser {
+            args,
+            line: line_number,
+        };
+
+        let event = match command {
+            "seat" => {
+                let id = parser.parse::<u64>()?;
+                let event = match parser.next()? {
+                    "create" => {
+                        let name = parser.next()?.to_owned();
+                        MockEvent::SeatCreated {
+                            name,
+                            capabilities: parser.capabilities()?,
+                        }
+                    }
+                    "change" => MockEvent::SeatChanged(parser.capabilities()?),
+                    "destroy" => {
+                        pressed_keys.remove(&id);
+                        pressed_tool_buttons.remove(&id);
+                        tool_positions.retain(|&(seat, _), _| seat != id);
+                        MockEvent::SeatDestroyed
+                    }
+                    argument => return Err(parser.invalid(argument)),
+                };
+                parser.finish()?;
+                events.push((id, event));
+                continue;
+            }
+            "use" => {
+                current_seat = parser.parse::<u64>()?;
+                parser.finish()?;
+                continue;
+            }
+            "key" => {
+                let time = parser.parse()?;
+                let key_code = parser.parse()?;
+                let state = match parser.next()? {
+                    "pressed" => KeyState::Pressed,
+                    "released" => KeyState::Released,
+                    argument => return Err(parser.invalid(argument)),
+                };
+                let count = pressed_keys.entry(current_seat).or_insert(0);
+                *count = match state {
+                    KeyState::Pressed => *count + 1,
+                    KeyState::Released => count.saturating_sub(1),
+                };
+                MockEvent::KeyboardKey(MockKeyboardKeyEvent {
+                    time,
+                    key_code,
+                    state,
+                    count: *count,
+                })
+            }
+            "motion" => MockEvent::PointerMotion(MockPointerMotionEvent {
+                time: parser.parse()?,
+                delta: (parser.parse()?, parser.parse()?),
+            }),
+            "absolute" => MockEvent::PointerMotionAbsolute(MockPointerMotionAbsoluteEvent {
+                time: parser.parse()?,
+                position: (parser.parse()?, parser.parse()?),
+            }),
+            "button" => {
+                let time = parser.parse()?;
+                let button = match parser.next()? {
+                    "left" => MouseButton::Left,
+                    "right" => MouseButton::Right,
+                    "middle" => MouseButton::Middle,
+                    argument => MouseButton::Other(argument.parse().map_err(|_| parser.invalid(argument))?),
+                };
+                let state = parser.button_state()?;
+                MockEvent::PointerButton(MockPointerButtonEvent { time, button, state })
+            }
+            "axis" => {
+                let time = parser.parse()?;
+                let source = match parser.next()? {
+                    "wheel" => AxisSource::Wheel,
+                    "tilt" => AxisSource::WheelTilt,
+                    "finger" => AxisSource::Finger,
+                    "continuous" => AxisSource::Continuous,
+                    argument => return Err(parser.invalid(argument)),
+                };
+                let axis = match parser.next()? {
+                    "horizontal" => Axis::Horizontal,
+                    "vertical" => Axis::Vertical,
+                    argument => return Err(parser.invalid(argument)),
+                };
+                let amount = Some(parser.parse::<f64>()?);
+                let discrete = match parser.args.next() {
+                    Some(argument) => Some(argument.parse::<f64>().map_err(|_| parser.invalid(argument))?),
+                    None => None,
+                };
+                let (amount, amount_discrete) = match axis {
+                    Axis::Horizontal => ((amount, None), (discrete, None)),
+                    Axis::Vertical => ((None, amount), (None, discrete)),
+                };
+                MockEvent::PointerAxis(MockPointerAxisEvent {
+                    time,
+                    source,
+                    amount,
+                    amount_discrete,
+                })
+            }
+            "touch-down" => MockEvent::TouchDown(MockTouchDownEvent {
+                time: parser.parse()?,
+                slot: parser.slot()?,
+                position: (parser.parse()?, parser.parse()?),
+            }),
+            "touch-motion" => MockEvent::TouchMotion(MockTouchMotionEvent {
+                time: parser.parse()?,
+                slot: parser.slot()?,
+                position: (parser.parse()?, parser.parse()?),
+            }),
+            "touch-up" => MockEvent::TouchUp(MockTouchUpEvent {
+                time: parser.parse()?,
+                slot: parser.slot()?,
+            }),
+            "touch-cancel" => MockEvent::TouchCancel(MockTouchCancelEvent {
+                time: parser.parse()?,
+                slot: parser.slot()?,
+            }),
+            "touch-frame" => MockEvent::TouchFrame(MockTouchFrameEvent {
+                time: parser.parse()?,
+            }),
+            "swipe-begin" => MockEvent::GestureSwipeBegin(MockGestureBeginEvent {
+                time: parser.parse()?,
+                fingers: parser.parse()?,
+            }),
+            "swipe-update" => MockEvent::GestureSwipeUpdate(MockGestureSwipeUpdateEvent {
+                time: parser.parse()?,
+                delta: (parser.parse()?, parser.parse()?),
+            }),
+            "swipe-end" => MockEvent::GestureSwipeEnd(MockGestureEndEvent {
+                time: parser.parse()?,
+                cancelled: parser.cancelled()?,
+            }),
+            "pinch-begin" => MockEvent::GesturePinchBegin(MockGestureBeginEvent {
+                time: parser.parse()?,
+                fingers: parser.parse()?,
+            }),
+            "pinch-update" => MockEvent::GesturePinchUpdate(MockGesturePinchUpdateEvent {
+                time: parser.parse()?,
+                delta: (parser.parse()?, parser.parse()?),
+                scale: parser.parse()?,
+                rotation: parser.parse()?,
+            }),
+            "pinch-end" => MockEvent::GesturePinchEnd(MockGestureEndEvent {
+                time: parser.parse()?,
+                cancelled: parser.cancelled()?,
+            }),
+            "tool-proximity" | "tool-tip" | "tool-axis" | "tool-button" => {
+                let time = parser.parse()?;
+                let tool_type = parser.tool_type()?;
+                let hardware_serial = parser.parse()?;
+                let mut tool_event = MockTabletToolEvent {
+                    time,
+                    tool: TabletToolDescriptor {
+                        tool_type,
+                        hardware_serial,
+                        hardware_id_wacom: 0,
+                        capabilities: TabletToolCapabilities {
+                            pressure: true,
+                            distance: false,
+                            tilt: false,
+                            rotation: false,
+                            slider: false,
+                            wheel: false,
+                        },
+                    },
+                    position: (0.0, 0.0),
+                    delta: (0.0, 0.0),
+                    pressure: 0.0,
+                    distance: 0.0,
+                    tilt: (0.0, 0.0),
+                    rotation: 0.0,
+                    slider_position: 0.0,
+                    wheel_delta: 0.0,
+                    wheel_delta_discrete: 0,
+                };
+                let position = tool_positions
+                    .entry((current_seat, hardware_serial))
+                    .or_insert((0.0, 0.0));
+                let event = match command {
+                    "tool-proximity" => {
+                        let state = match parser.next()? {
+                            "in" => ProximityState::In,
+                            "out" => ProximityState::Out,
+                            argument => return Err(parser.invalid(argument)),
+                        };
+                        tool_event.position = (parser.parse()?, parser.parse()?);
+                        MockEvent::TabletToolProximity(MockTabletToolProximityEvent { tool_event, state })
+                    }
+                    "tool-tip" => {
+                        let tip_state = match parser.next()? {
+                            "down" => TipState::Down,
+                            "up" => TipState::Up,
+                            argument => return Err(parser.invalid(argument)),
+                        };
+                        tool_event.position = *position;
+                        tool_event.pressure = match tip_state {
+                            TipState::Down => 1.0,
+                            TipState::Up => 0.0,
+                        };
+                        MockEvent::TabletToolTip(MockTabletToolTipEvent {
+                            tool_event,
+                            tip_state,
+                        })
+                    }
+                    "tool-axis" => {
+                        tool_event.position = (parser.parse()?, parser.parse()?);
+                        tool_event.delta = (
+                            tool_event.position.0 - position.0,
+                            tool_event.position.1 - position.1,
+                        );
+                        tool_event.pressure = parser.parse()?;
+                        MockEvent::TabletToolAxis(tool_event)
+                    }
+                    _ => {
+                        let button = parser.parse()?;
+                        let state = parser.button_state()?;
+                        let count = pressed_tool_buttons.entry(current_seat).or_insert(0);
+                        *count = match state {
+                            MouseButtonState::Pressed => *count + 1,
+                            MouseButtonState::Released => count.saturating_sub(1),
+                        };
+                        tool_event.position = *position;
+                        MockEvent::TabletToolButton(MockTabletToolButtonEvent {
+                            tool_event,
+                            button,
+                            state,
+                            seat_button_count: *count,
+                        })
+                    }
+                };
+                *position = tool_event.position;
+                event
+            }
+            "pad-button" => MockEvent::TabletPadButton(MockTabletPadButtonEvent {
+                time: parser.parse()?,
+                button: parser.parse()?,
+                state: parser.button_state()?,
+                mode: 0,
+            }),
+            "pad-ring" => MockEvent::TabletPadRing(MockTabletPadAxisEvent {
+                time: parser.parse()?,
+                number: parser.parse()?,
+                position: parser.parse()?,
+                source: TabletPadAxisSource::Finger,
+                mode: 0,
+            }),
+            "pad-strip" => MockEvent::TabletPadStrip(MockTabletPadAxisEvent {
+                time: parser.parse()?,
+                number: parser.parse()?,
+                position: parser.parse()?,
+                source: TabletPadAxisSource::Finger,
+                mode: 0,
+            }),
+            "switch" => {
+                let time = parser.parse()?;
+                let switch = match parser.next()? {
+                    "lid" => Switch::Lid,
+                    "tablet-mode" => Switch::TabletMode,
+                    argument => return Err(parser.invalid(argument)),
+                };
+                let state = match parser.next()? {
+                    "on" => SwitchState::On,
+                    "off" => SwitchState::Off,
+                    argument => return Err(parser.invalid(argument)),
+                };
+                MockEvent::SwitchToggle(MockSwitchToggleEvent {
+                    time,
+                    switch: Some(switch),
+                    state,
+                })
+            }
+            command => {
+                return Err(ScriptError::UnknownCommand {
+                    line: line_number,
+                    command: command.to_owned(),
+                })
+            }
+        };
+        parser.finish()?;
+        events.push((current_seat, event));
+    }
+
+    Ok(events)
+}
+
+struct ArgParser<'a> {
+    args: ::std::str::SplitWhitespace<'a>,
+    line: usize,
+}
+
+impl<'a> ArgParser<'a> {
+    fn invalid(&self, argument: &str) -> ScriptError {
+        ScriptError::InvalidArgument {
+            line: self.line,
+            argument: argument.to_owned(),
+        }
+    }
+
+    fn next(&mut self) -> Result<&'a str, ScriptError> {
+        let line = self.line;
+        self.args.next().ok_or(ScriptError::MissingArgument { line })
+    }
+
+    // Ensure all the arguments of the line have been consumed
+    fn finish(&mut self) -> Result<(), ScriptError> {
+        match self.args.next() {
+            Some(argument) => Err(ScriptError::UnexpectedArgument {
+                line: self.line,
+                argument: argument.to_owned(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn parse<T: ::std::str::FromStr>(&mut self) -> Result<T, ScriptError> {
+        let argument = self.next()?;
+        argument.parse().map_err(|_| self.invalid(argument))
+    }
+
+    fn slot(&mut self) -> Result<Option<u64>, ScriptError> {
+        match self.next()? {
+            "-" => Ok(None),
+            argument => argument.parse().map(Some).map_err(|_| self.invalid(argument)),
+        }
+    }
+
+    fn button_state(&mut self) -> Result<MouseButtonState, ScriptError> {
+        match self.next()? {
+            "pressed" => Ok(MouseButtonState::Pressed),
+            "released" => Ok(MouseButtonState::Released),
+            argument => Err(self.invalid(argument)),
+        }
+    }
+
+    fn cancelled(&mut self) -> Result<bool, ScriptError> {
+        match self.args.next() {
+            Some("cancelled") => Ok(true),
+            Some(argument) => Err(self.invalid(argument)),
+            None => Ok(false),
+        }
+    }
+
+    fn tool_type(&mut self) -> Result<TabletToolType, ScriptError> {
+        match self.next()? {
+            "pen" => Ok(TabletToolType::Pen),
+            "eraser" => Ok(TabletToolType::Eraser),
+            "brush" => Ok(TabletToolType::Brush),
+            "pencil" => Ok(TabletToolType::Pencil),
+            "airbrush" => Ok(TabletToolType::Airbrush),
+            "mouse" => Ok(TabletToolType::Mouse),
+            "lens" => Ok(TabletToolType::Lens),
+            argument => Err(self.invalid(argument)),
+        }
+    }
+
+    fn capabilities(&mut self) -> Result<SeatCapabilities, ScriptError> {
+        let mut capabilities = SeatCapabilities {
+            pointer: false,
+            keyboard: false,
+            touch: false,
+        };
+        for argument in &mut self.args {
+            match argument {
+                "pointer" => capabilities.pointer = true,
+                "keyboard" => capabilities.keyboard = true,
+                "touch" => capabilities.touch = true,
+                argument => {
+                    return Err(ScriptError::InvalidArgument {
+                        line: self.line,
+                        argument: argument.to_owned(),
+                    })
+                }
+            }
+        }
+        Ok(capabilities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    // Records the calls it receives as `<seat>: <call>` lines
+    struct RecordingHandler(Rc<RefCell<Vec<String>>>);
+
+    impl RecordingHandler {
+        fn record(&mut self, seat: &Seat, call: String) {
+            self.0.borrow_mut().push(format!("{}: {}", seat.name(), call));
+        }
+    }
+
+    impl InputHandler<MockInputBackend> for RecordingHandler {
+        fn on_seat_created(&mut self, seat: &Seat) {
+            self.record(seat, format!("created {:?}", seat.capabilities()));
+        }
+
+        fn on_seat_destroyed(&mut self, seat: &Seat) {
+            self.record(seat, "destroyed".into());
+        }
+
+        fn on_seat_changed(&mut self, seat: &Seat) {
+            self.record(seat, format!("changed {:?}", seat.capabilities()));
+        }
+
+        fn on_keyboard_key(&mut self, seat: &Seat, event: MockKeyboardKeyEvent) {
+            self.record(
+                seat,
+                format!(
+                    "key {} {} {:?} {}",
+                    event.time(),
+                    event.key_code(),
+                    KeyboardKeyEvent::state(&event),
+                    event.count()
+                ),
+            );
+        }
+
+        fn on_pointer_move(&mut self, seat: &Seat, event: MockPointerMotionEvent) {
+            self.record(seat, format!("motion {} {:?}", event.time(), event.delta()));
+        }
+
+        fn on_pointer_move_absolute(&mut self, seat: &Seat, event: MockPointerMotionAbsoluteEvent) {
+            self.record(
+                seat,
+                format!(
+                    "absolute {} {:?}",
+                    event.time(),
+                    event.position_transformed((100, 100))
+                ),
+            );
+        }
+
+        fn on_pointer_button(&mut self, seat: &Seat, event: MockPointerButtonEvent) {
+            self.record(
+                seat,
+                format!(
+                    "button {} {:?} {:?}",
+                    event.time(),
+                    event.button(),
+                    PointerButtonEvent::state(&event)
+                ),
+            );
+        }
+
+        fn on_pointer_axis(&mut self, seat: &Seat, event: MockPointerAxisEvent) {
+            self.record(
+                seat,
+                format!(
+                    "axis {} {:?} {:?}",
+                    event.time(),
+                    event.amount(&Axis::Vertical),
+                    event.amount_discrete(&Axis::Vertical)
+                ),
+            );
+        }
+
+        fn on_touch_down(&mut self, seat: &Seat, event: MockTouchDownEvent) {
+            self.record(
+                seat,
+                format!(
+                    "touch-down {} {:?} {:?}",
+                    event.time(),
+                    TouchDownEvent::slot(&event),
+                    event.position_transformed((100, 100))
+                ),
+            );
+        }
+
+        fn on_touch_motion(&mut self, seat: &Seat, event: MockTouchMotionEvent) {
+            self.record(
+                seat,
+                format!(
+                    "touch-motion {} {:?} {:?}",
+                    event.time(),
+                    TouchMotionEvent::slot(&event),
+                    event.position_transformed((100, 100))
+                ),
+            );
+        }
+
+        fn on_touch_up(&mut self, seat: &Seat, event: MockTouchUpEvent) {
+            self.record(
+                seat,
+                format!("touch-up {} {:?}", event.time(), TouchUpEvent::slot(&event)),
+            );
+        }
+
+        fn on_touch_cancel(&mut self, seat: &Seat, event: MockTouchCancelEvent) {
+            self.record(
+                seat,
+                format!(
+                    "touch-cancel {} {:?}",
+                    event.time(),
+                    TouchCancelEvent::slot(&event)
+                ),
+            );
+        }
+
+        fn on_touch_frame(&mut self, seat: &Seat, event: MockTouchFrameEvent) {
+            self.record(seat, format!("touch-frame {}", event.time()));
+        }
+
+        fn on_gesture_swipe_begin(&mut self, seat: &Seat, event: MockGestureBeginEvent) {
+            self.record(seat, format!("swipe-begin {} {}", event.time(), event.fingers()));
+        }
+
+        fn on_gesture_swipe_end(&mut self, seat: &Seat, event: MockGestureEndEvent) {
+            self.record(seat, format!("swipe-end {} {}", event.time(), event.cancelled()));
+        }
+
+        fn on_tablet_tool_proximity(&mut self, seat: &Seat, event: MockTabletToolProximityEvent) {
+            self.record(
+                seat,
+                format!(
+                    "tool-proximity {} {:?} {:?} {:?}",
+                    event.time(),
+                    event.tool().tool_type,
+                    TabletToolProximityEvent::state(&event),
+                    TabletToolEvent::position(&event)
+                ),
+            );
+        }
+
+        fn on_tablet_tool_axis(&mut self, seat: &Seat, event: MockTabletToolEvent) {
+            self.record(
+                seat,
+                format!(
+                    "tool-axis {} {:?} {:?} {}",
+                    event.time(),
+                    TabletToolEvent::position(&event),
+                    TabletToolEvent::delta(&event),
+                    event.pressure()
+                ),
+            );
+        }
+
+        fn on_tablet_tool_button(&mut self, seat: &Seat, event: MockTabletToolButtonEvent) {
+            self.record(
+                seat,
+                format!(
+                    "tool-button {} {} {:?} {} {:?}",
+                    event.time(),
+                    TabletToolButtonEvent::button(&event),
+                    event.button_state(),
+                    event.seat_button_count(),
+                    TabletToolEvent::position(&event)
+                ),
+            );
+        }
+
+        fn on_switch_toggle(&mut self, seat: &Seat, event: MockSwitchToggleEvent) {
+            self.record(
+                seat,
+                format!(
+                    "switch {} {:?} {:?}",
+                    event.time(),
+                    event.switch(),
+                    SwitchToggleEvent::state(&event)
+                ),
+            );
+        }
+
+        fn on_input_config_changed(&mut self, _config: &mut ()) {}
+    }
+
+    fn recording_backend() -> (MockInputBackend, Rc<RefCell<Vec<String>>>) {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let mut backend = MockInputBackend::new(None);
+        backend.set_handler(RecordingHandler(calls.clone()));
+        (backend, calls)
+    }
+
+    #[test]
+    fn script_reaches_handler() {
+        let (mut backend, calls) = recording_backend();
+        backend
+            .push_script(
+                "
+                seat 1 create tablet keyboard touch
+                key 1 30 pressed
+                use 1
+                key 2 30 pressed
+                touch-down 3 - 0.5 0.25
+                touch-frame 3
+                use 0
+                key 4 30 released
+                axis 5 wheel vertical 15 1
+                swipe-begin 6 3
+                swipe-end 7 cancelled
+                use 1
+                tool-proximity 8 pen 42 in 0.5 0.5
+                tool-axis 9 pen 42 0.75 0.5 0.25
+                tool-button 10 pen 42 331 pressed
+                seat 1 change keyboard
+                switch 11 lid on
+                seat 1 destroy
+                key 12 30 released   # the seat no longer exists
+                ",
+            )
+            .unwrap();
+        backend.dispatch_new_events().unwrap();
+        assert_eq!(backend.pending_events(), 0);
+
+        let all_caps = "SeatCapabilities { pointer: true, keyboard: true, touch: true }";
+        let keyboard_touch = "SeatCapabilities { pointer: false, keyboard: true, touch: true }";
+        let keyboard = "SeatCapabilities { pointer: false, keyboard: true, touch: false }";
+        assert_eq!(
+            *calls.borrow(),
+            vec![
+                format!("mock: created {}", all_caps),
+                format!("tablet: created {}", keyboard_touch),
+                "mock: key 1 30 Pressed 1".to_owned(),
+                "tablet: key 2 30 Pressed 1".to_owned(),
+                "tablet: touch-down 3 None (50, 25)".to_owned(),
+                "tablet: touch-frame 3".to_owned(),
+                "mock: key 4 30 Released 0".to_owned(),
+                "mock: axis 5 Some(15.0) Some(1.0)".to_owned(),
+                "mock: swipe-begin 6 3".to_owned(),
+                "mock: swipe-end 7 true".to_owned(),
+                "tablet: tool-proximity 8 Pen In (0.5, 0.5)".to_owned(),
+                "tablet: tool-axis 9 (0.75, 0.5) (0.25, 0.0) 0.25".to_owned(),
+                "tablet: tool-button 10 331 Pressed 1 (0.75, 0.5)".to_owned(),
+                format!("tablet: changed {}", keyboard),
+                "tablet: switch 11 Some(Lid) On".to_owned(),
+                "tablet: destroyed".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn seats_are_tracked_without_handler() {
+        let mut backend = MockInputBackend::new(None);
+        backend
+            .push_script("seat 1 create second pointer\nuse 1\nmotion 1 2 3")
+            .unwrap();
+        backend.dispatch_new_events().unwrap();
+        assert_eq!(backend.pending_events(), 0);
+
+        // the seat created before the handler was set is announced to it, the motion is lost
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        backend.set_handler(RecordingHandler(calls.clone()));
+        backend.push_script("use 1\nmotion 4 5 6").unwrap();
+        backend.dispatch_new_events().unwrap();
+        assert_eq!(
+            *calls.borrow(),
+            vec![
+                "mock: created SeatCapabilities { pointer: true, keyboard: true, touch: true }".to_owned(),
+                "second: created SeatCapabilities { pointer: true, keyboard: false, touch: false }"
+                    .to_owned(),
+                "second: motion 4 (5, 6)".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_scripts_are_rejected() {
+        let cases = vec![
+            (
+                "key 1 30 pressed extra",
+                ScriptError::UnexpectedArgument {
+                    line: 1,
+                    argument: "extra".into(),
+                },
+            ),
+            (
+                "use 1 2",
+                ScriptError::UnexpectedArgument {
+                    line: 1,
+                    argument: "2".into(),
+                },
+            ),
+            (
+                "seat 1 destroy now",
+                ScriptError::UnexpectedArgument {
+                    line: 1,
+                    argument: "now".into(),
+                },
+            ),
+            ("touch-down 1 0 0.5", ScriptError::MissingArgument { line: 1 }),
+            (
+                "key 1 30 pressed\n\n  # comment\nfly 2",
+                ScriptError::UnknownCommand {
+                    line: 4,
+                    command: "fly".into(),
+                },
+            ),
+            (
+                "swipe-end 1 done",
+                ScriptError::InvalidArgument {
+                    line: 1,
+                    argument: "done".into(),
+                },
+            ),
+            (
+                "tool-tip 1 quill 0 down",
+                ScriptError::InvalidArgument {
+                    line: 1,
+                    argument: "quill".into(),
+                },
+            ),
+        ];
+        for (script, error) in cases {
+            let mut backend = MockInputBackend::new(None);
+            assert_eq!(backend.push_script(script), Err(error), "script: {:?}", script);
+            assert_eq!(backend.pending_events(), 0);
+        }
+    }
+}
@@ -8,11 +8,13 @@
 //!
 //! - winit
 //! - drm
+//! - headless
 //!
 //! Supported input backends:
 //!
 //! - winit
 //! - libinput
+//! - mock
 
 pub mod graphics;
 pub mod input;
@@ -21,8 +23,12 @@ pub mod input;
 pub mod drm;
 #[cfg(feature = "backend_egl")]
 pub mod egl;
+#[cfg(feature = "backend_headless")]
+pub mod headless;
 #[cfg(feature = "backend_libinput")]
 pub mod libinput;
+#[cfg(feature = "backend_mock")]
+pub mod mock;
 #[cfg(feature = "backend_session")]
 pub mod session;
 #[cfg(feature = "backend_udev")]
@@ -1,5 +1,7 @@
 //! Various utilities functions and types
 
 mod rectangle;
+mod region;
 
 pub use self::rectangle::Rectangle;
+pub use self::region::Region;
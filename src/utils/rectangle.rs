@@ -17,4 +17,27 @@ impl Rectangle {
         let (x, y) = point;
         (x >= self.x) && (x < self.x + self.width) && (y >= self.y) && (y < self.y + self.height)
     }
+
+    /// Checks whether this rectangle has no area
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+
+    /// Computes the intersection of two rectangles, if they overlap
+    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+        let x1 = self.x.max(other.x);
+        let y1 = self.y.max(other.y);
+        let x2 = (self.x + self.width).min(other.x + other.width);
+        let y2 = (self.y + self.height).min(other.y + other.height);
+        if x2 > x1 && y2 > y1 {
+            Some(Rectangle {
+                x: x1,
+                y: y1,
+                width: x2 - x1,
+                height: y2 - y1,
+            })
+        } else {
+            None
+        }
+    }
 }
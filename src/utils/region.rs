@@ -0,0 +1,450 @@
+use super::Rectangle;
+
+/// A region of the plane, made of a set of rectangles
+///
+/// The rectangles of a region never overlap. A region supports the usual set
+/// operations, which makes it suitable for input hit-testing, opaque region
+/// culling or damage tracking:
+///
+/// ```
+/// use smithay::utils::{Rectangle, Region};
+///
+/// let a = Region::from(Rectangle { x: 0, y: 0, width: 10, height: 10 });
+/// let b = Region::from(Rectangle { x: 5, y: 5, width: 10, height: 10 });
+///
+/// let union = a.union(&b);
+/// let intersection = a.intersection(&b);
+/// let difference = a.subtract(&b);
+///
+/// for x in -2..17 {
+///     for y in -2..17 {
+///         let (in_a, in_b) = (a.contains((x, y)), b.contains((x, y)));
+///         assert_eq!(union.contains((x, y)), in_a || in_b);
+///         assert_eq!(intersection.contains((x, y)), in_a && in_b);
+///         assert_eq!(difference.contains((x, y)), in_a && !in_b);
+///     }
+/// }
+///
+/// // the area of the union accounts for the overlap only once
+/// assert_eq!(union.area(), 100 + 100 - 25);
+/// assert!(difference.subtract(&a).is_empty());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Region {
+    rects: Vec<Rectangle>,
+}
+
+impl Region {
+    /// Create a new empty region
+    pub fn new() -> Region {
+        Region { rects: Vec::new() }
+    }
+
+    /// The rectangles making this region
+    ///
+    /// They do not overlap each other.
+    pub fn rects(&self) -> &[Rectangle] {
+        &self.rects
+    }
+
+    /// Checks whether this region is empty
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// The area of this region
+    pub fn area(&self) -> i64 {
+        self.rects
+            .iter()
+            .map(|r| i64::from(r.width) * i64::from(r.height))
+            .sum()
+    }
+
+    /// The smallest rectangle containing this region, if it is not empty
+    pub fn bounding_box(&self) -> Option<Rectangle> {
+        let first = *self.rects.first()?;
+        Some(self.rects.iter().skip(1).fold(first, |bbox, r| {
+            let x1 = bbox.x.min(r.x);
+            let y1 = bbox.y.min(r.y);
+            let x2 = (bbox.x + bbox.width).max(r.x + r.width);
+            let y2 = (bbox.y + bbox.height).max(r.y + r.height);
+            Rectangle {
+                x: x1,
+                y: y1,
+                width: x2 - x1,
+                height: y2 - y1,
+            }
+        }))
+    }
+
+    /// Checks whether given point is inside this region
+    pub fn contains(&self, point: (i32, i32)) -> bool {
+        self.rects.iter().any(|r| r.contains(point))
+    }
+
+    /// Add a rectangle to this region
+    pub fn add_rect(&mut self, rect: Rectangle) {
+        if rect.is_empty() {
+            return;
+        }
+        // remove the overlapping part from the existing rectangles to keep them disjoint
+        self.subtract_rect(rect);
+        self.rects.push(rect);
+    }
+
+    /// Remove a rectangle from this region
+    pub fn subtract_rect(&mut self, rect: Rectangle) {
+        if rect.is_empty() {
+            return;
+        }
+        let rects = ::std::mem::replace(&mut self.rects, Vec::new());
+        for r in rects {
+            subtract(r, rect, &mut self.rects);
+        }
+    }
+
+    /// Restrict this region to the parts inside of a rectangle
+    pub fn intersect_rect(&mut self, rect: Rectangle) {
+        let rects = ::std::mem::replace(&mut self.rects, Vec::new());
+        self.rects = rects.iter().filter_map(|r| r.intersection(&rect)).collect();
+    }
+
+    /// Compute the union of two regions
+    pub fn union(&self, other: &Region) -> Region {
+        let mut region = self.clone();
+        for &rect in &other.rects {
+            region.add_rect(rect);
+        }
+        region
+    }
+
+    /// Compute the intersection of two regions
+    pub fn intersection(&self, other: &Region) -> Region {
+        // the rectangles of each region being disjoint, so are their intersections
+        let rects = self
+            .rects
+            .iter()
+            .flat_map(|r| other.rects.iter().filter_map(move |o| r.intersection(o)))
+            .collect();
+        Region { rects }
+    }
+
+    /// Compute the parts of this region that are not in another one
+    pub fn subtract(&self, other: &Region) -> Region {
+        let mut region = self.clone();
+        for &rect in &other.rects {
+            region.subtract_rect(rect);
+        }
+        region
+    }
+
+    /// Translate this region by given offset
+    pub fn translate(&self, (dx, dy): (i32, i32)) -> Region {
+        let rects = self
+            .rects
+            .iter()
+            .map(|r| Rectangle {
+                x: r.x + dx,
+                y: r.y + dy,
+                ..*r
+            })
+            .collect();
+        Region { rects }
+    }
+
+    /// Scale this region by given factor
+    ///
+    /// The edges of the rectangles are rounded to the nearest integer, so that
+    /// adjacent rectangles remain adjacent:
+    ///
+    /// ```
+    /// use smithay::utils::{Rectangle, Region};
+    ///
+    /// let mut region = Region::new();
+    /// region.add_rect(Rectangle { x: 0, y: 0, width: 3, height: 3 });
+    /// region.add_rect(Rectangle { x: 3, y: 0, width: 3, height: 3 });
+    /// let scaled = region.scale(1.5);
+    /// assert_eq!(scaled.area(), 9 * 5);
+    /// assert!(scaled.contains((4, 4)) && scaled.contains((5, 4)));
+    /// ```
+    pub fn scale(&self, factor: f64) -> Region {
+        let scale = |v: i32| (f64::from(v) * factor).round() as i32;
+        let rects = self
+            .rects
+            .iter()
+            .map(|r| {
+                let (x1, y1) = (scale(r.x), scale(r.y));
+                let (x2, y2) = (scale(r.x + r.width), scale(r.y + r.height));
+                Rectangle {
+                    x: x1,
+                    y: y1,
+                    width: x2 - x1,
+                    height: y2 - y1,
+                }
+            })
+            .filter(|r| !r.is_empty())
+            .collect();
+        Region { rects }
+    }
+}
+
+impl From<Rectangle> for Region {
+    fn from(rect: Rectangle) -> Region {
+        let mut region = Region::new();
+        region.add_rect(rect);
+        region
+    }
+}
+
+// Push the parts of `rect` that are not in `hole` into `out`
+fn subtract(rect: Rectangle, hole: Rectangle, out: &mut Vec<Rectangle>) {
+    let inter = match rect.intersection(&hole) {
+        Some(inter) => inter,
+        None => {
+            out.push(rect);
+            return;
+        }
+    };
+    let pieces = [
+        // above the hole
+        Rectangle {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: inter.y - rect.y,
+        },
+        // below the hole
+        Rectangle {
+            x: rect.x,
+            y: inter.y + inter.height,
+            width: rect.width,
+            height: rect.y + rect.height - (inter.y + inter.height),
+        },
+        // left of the hole
+        Rectangle {
+            x: rect.x,
+            y: inter.y,
+            width: inter.x - rect.x,
+            height: inter.height,
+        },
+        // right of the hole
+        Rectangle {
+            x: inter.x + inter.width,
+            y: inter.y,
+            width: rect.x + rect.width - (inter.x + inter.width),
+            height: inter.height,
+        },
+    ];
+    out.extend(pieces.iter().filter(|r| !r.is_empty()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rectangle, Region};
+
+    // The region tests check the set operations against point membership on random
+    // inputs. A small deterministic generator keeps failures reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            // xorshift64*
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn range(&mut self, min: i32, max: i32) -> i32 {
+            min + (self.next() % (max - min) as u64) as i32
+        }
+
+        fn rect(&mut self) -> Rectangle {
+            Rectangle {
+                x: self.range(-RANGE, RANGE),
+                y: self.range(-RANGE, RANGE),
+                width: self.range(0, RANGE),
+                height: self.range(0, RANGE),
+            }
+        }
+    }
+
+    const RANGE: i32 = 16;
+    const ITERATIONS: usize = 200;
+
+    // A random region, along with the list of operations that built it
+    fn region(rng: &mut Rng) -> (Region, Vec<(bool, Rectangle)>) {
+        let mut region = Region::new();
+        let mut ops = Vec::new();
+        for _ in 0..rng.range(0, 8) {
+            let rect = rng.rect();
+            let add = rng.next() % 3 != 0;
+            if add {
+                region.add_rect(rect);
+            } else {
+                region.subtract_rect(rect);
+            }
+            ops.push((add, rect));
+        }
+        (region, ops)
+    }
+
+    // Membership of a point, replaying the operations that built a region
+    fn replay(ops: &[(bool, Rectangle)], point: (i32, i32)) -> bool {
+        ops.iter()
+            .rev()
+            .find(|&&(_, rect)| rect.contains(point))
+            .map(|&(add, _)| add)
+            .unwrap_or(false)
+    }
+
+    fn points(margin: i32) -> impl Iterator<Item = (i32, i32)> {
+        let (min, max) = (-RANGE - margin, 2 * RANGE + margin);
+        (min..max).flat_map(move |x| (min..max).map(move |y| (x, y)))
+    }
+
+    fn check_invariants(region: &Region) {
+        for (i, a) in region.rects().iter().enumerate() {
+            assert!(!a.is_empty(), "empty rectangle {:?}", a);
+            for b in &region.rects()[i + 1..] {
+                assert!(
+                    a.intersection(b).is_none(),
+                    "overlapping rectangles {:?} {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+        let area = region.rects().iter().map(|r| r.width * r.height).sum::<i32>();
+        assert_eq!(region.area(), i64::from(area));
+        if let Some(bbox) = region.bounding_box() {
+            assert!(region.rects().iter().all(|r| r.x >= bbox.x
+                && r.y >= bbox.y
+                && r.x + r.width <= bbox.x + bbox.width
+                && r.y + r.height <= bbox.y + bbox.height));
+        }
+    }
+
+    #[test]
+    fn construction() {
+        let mut rng = Rng(0x5eed_0001);
+        for _ in 0..ITERATIONS {
+            let (region, ops) = region(&mut rng);
+            check_invariants(&region);
+            let mut count = 0;
+            for point in points(1) {
+                let inside = replay(&ops, point);
+                assert_eq!(region.contains(point), inside, "{:?} in {:?}", point, ops);
+                if inside {
+                    count += 1;
+                }
+            }
+            // the area is the amount of points of the region
+            assert_eq!(region.area(), count);
+            assert_eq!(region.is_empty(), count == 0);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "wayland_frontend")]
+    fn region_attributes() {
+        use crate::wayland::compositor::{RectangleKind, RegionAttributes};
+        let mut rng = Rng(0x5eed_0005);
+        for _ in 0..ITERATIONS {
+            let (_, ops) = region(&mut rng);
+            let attributes = RegionAttributes {
+                rects: ops
+                    .iter()
+                    .map(|&(add, rect)| {
+                        let kind = if add {
+                            RectangleKind::Add
+                        } else {
+                            RectangleKind::Subtract
+                        };
+                        (kind, rect)
+                    })
+                    .collect(),
+            };
+            let region = Region::from(&attributes);
+            check_invariants(&region);
+            for point in points(1) {
+                assert_eq!(attributes.contains(point), replay(&ops, point));
+                assert_eq!(region.contains(point), replay(&ops, point));
+            }
+        }
+    }
+
+    #[test]
+    fn set_operations() {
+        let mut rng = Rng(0x5eed_0002);
+        for _ in 0..ITERATIONS {
+            let (a, _) = region(&mut rng);
+            let (b, _) = region(&mut rng);
+            let union = a.union(&b);
+            let intersection = a.intersection(&b);
+            let difference = a.subtract(&b);
+            check_invariants(&union);
+            check_invariants(&intersection);
+            check_invariants(&difference);
+            for point in points(1) {
+                let (in_a, in_b) = (a.contains(point), b.contains(point));
+                assert_eq!(union.contains(point), in_a || in_b);
+                assert_eq!(intersection.contains(point), in_a && in_b);
+                assert_eq!(difference.contains(point), in_a && !in_b);
+            }
+            assert_eq!(union.area(), a.area() + b.area() - intersection.area());
+            assert_eq!(difference.area(), a.area() - intersection.area());
+        }
+    }
+
+    #[test]
+    fn translate() {
+        let mut rng = Rng(0x5eed_0003);
+        for _ in 0..ITERATIONS {
+            let (region, _) = region(&mut rng);
+            let offset = (rng.range(-RANGE, RANGE), rng.range(-RANGE, RANGE));
+            let translated = region.translate(offset);
+            check_invariants(&translated);
+            assert_eq!(translated.area(), region.area());
+            for (x, y) in points(1) {
+                assert_eq!(
+                    translated.contains((x + offset.0, y + offset.1)),
+                    region.contains((x, y))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn scale() {
+        let mut rng = Rng(0x5eed_0004);
+        for _ in 0..ITERATIONS {
+            let (region, _) = region(&mut rng);
+
+            // integer factors map each point to a square of points
+            let factor = rng.range(1, 4);
+            let scaled = region.scale(f64::from(factor));
+            check_invariants(&scaled);
+            assert_eq!(scaled.area(), region.area() * i64::from(factor * factor));
+            for (x, y) in points(2 * factor * RANGE) {
+                let origin = (
+                    (f64::from(x) / f64::from(factor)).floor() as i32,
+                    (f64::from(y) / f64::from(factor)).floor() as i32,
+                );
+                assert_eq!(scaled.contains((x, y)), region.contains(origin));
+            }
+
+            // fractional factors round the edges, but keep the rectangles disjoint
+            let factor = f64::from(rng.range(1, 40)) / 10.0;
+            let scaled = region.scale(factor);
+            check_invariants(&scaled);
+            // scaling back down gives back the original region
+            if factor >= 1.0 {
+                let restored = scaled.scale(1.0 / factor);
+                for point in points(1) {
+                    assert_eq!(restored.contains(point), region.contains(point));
+                }
+            }
+        }
+    }
+}
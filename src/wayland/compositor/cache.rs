@@ -0,0 +1,144 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+/// Trait representing a double-buffered state associated with surfaces
+///
+/// Types implementing it can be stored in the per-surface state container of the
+/// compositor, which handles the pending and current versions of the state, as well
+/// as the caching needed by synchronized subsurfaces.
+///
+/// On each commit of the surface, [`commit`](Cacheable::commit) is called on the pending
+/// state to produce the state to apply, which is then merged into the current state with
+/// [`merge_into`](Cacheable::merge_into). If the surface is a synchronized subsurface, the
+/// states of several commits can be merged together before being applied.
+///
+/// ```
+/// use smithay::wayland::compositor::Cacheable;
+///
+/// #[derive(Default)]
+/// struct MyState {
+///     // state that is reset after each commit
+///     value: Option<u32>,
+///     // state that persists across commits
+///     enabled: bool,
+/// }
+///
+/// impl Cacheable for MyState {
+///     fn commit(&mut self) -> Self {
+///         MyState {
+///             value: self.value.take(),
+///             enabled: self.enabled,
+///         }
+///     }
+///
+///     fn merge_into(self, into: &mut Self) {
+///         if self.value.is_some() {
+///             into.value = self.value;
+///         }
+///         into.enabled = self.enabled;
+///     }
+/// }
+/// ```
+pub trait Cacheable: Default {
+    /// Produce the state to commit from the pending state
+    ///
+    /// Values that are only valid for a single commit should be taken out of the
+    /// pending state, while values that persist across commits should be copied.
+    fn commit(&mut self) -> Self;
+    /// Merge this committed state into an older one
+    fn merge_into(self, into: &mut Self);
+}
+
+struct CachedState<T> {
+    pending: T,
+    cached: Option<T>,
+    current: T,
+}
+
+trait Cache {
+    fn commit(&mut self);
+    fn apply(&mut self);
+    fn is_cached(&self) -> bool;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Cacheable + 'static> Cache for CachedState<T> {
+    fn commit(&mut self) {
+        let state = self.pending.commit();
+        self.cached = Some(match self.cached.take() {
+            Some(mut cached) => {
+                state.merge_into(&mut cached);
+                cached
+            }
+            None => state,
+        });
+    }
+
+    fn apply(&mut self) {
+        if let Some(cached) = self.cached.take() {
+            cached.merge_into(&mut self.current);
+        }
+    }
+
+    fn is_cached(&self) -> bool {
+        self.cached.is_some()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Storage of all the double-buffered states of a surface
+#[derive(Default)]
+pub struct MultiCache {
+    caches: HashMap<TypeId, Box<dyn Cache>>,
+}
+
+impl MultiCache {
+    fn get<T: Cacheable + 'static>(&mut self) -> &mut CachedState<T> {
+        self.caches
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| {
+                Box::new(CachedState::<T> {
+                    pending: T::default(),
+                    cached: None,
+                    current: T::default(),
+                }) as Box<dyn Cache>
+            })
+            .as_any_mut()
+            .downcast_mut::<CachedState<T>>()
+            .unwrap()
+    }
+
+    /// Access the pending state of given type
+    pub fn pending<T: Cacheable + 'static>(&mut self) -> &mut T {
+        &mut self.get::<T>().pending
+    }
+
+    /// Access the current state of given type
+    pub fn current<T: Cacheable + 'static>(&mut self) -> &mut T {
+        &mut self.get::<T>().current
+    }
+
+    /// Commit the pending states, merging them into the cached ones
+    pub fn commit(&mut self) {
+        for cache in self.caches.values_mut() {
+            cache.commit();
+        }
+    }
+
+    /// Check whether some committed states are waiting to be applied
+    pub fn has_cached(&self) -> bool {
+        self.caches.values().any(|cache| cache.is_cached())
+    }
+
+    /// Apply the cached states, merging them into the current ones
+    pub fn apply(&mut self) {
+        for cache in self.caches.values_mut() {
+            cache.apply();
+        }
+    }
+}
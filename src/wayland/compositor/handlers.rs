@@ -8,7 +8,7 @@ use wayland_server::{
 use super::{
     tree::{Location, SurfaceData},
     CompositorToken, Damage, Rectangle, RectangleKind, RegionAttributes, Role, RoleType, SubsurfaceRole,
-    SurfaceEvent,
+    SurfaceEvent, SurfaceState,
 };
 
 /*
@@ -22,7 +22,7 @@ pub(crate) fn implement_compositor<U, R, Impl>(
 ) -> wl_compositor::WlCompositor
 where
     U: Default + 'static,
-    R: Default + 'static,
+    R: Default + RoleType + Role<SubsurfaceRole> + 'static,
     Impl: FnMut(SurfaceEvent, wl_surface::WlSurface, CompositorToken<U, R>) + 'static,
 {
     compositor.implement_closure(
@@ -46,6 +46,16 @@ where
  * wl_surface
  */
 
+// Access the pending state of a surface, applied at its next commit
+fn with_pending_surface_state<U, R, F, T>(surface: &wl_surface::WlSurface, f: F) -> T
+where
+    F: FnOnce(&mut SurfaceState) -> T,
+    U: 'static,
+    R: 'static,
+{
+    SurfaceData::<U, R>::with_states(surface, |states| f(states.pending::<SurfaceState>()))
+}
+
 // Internal implementation data of surfaces
 pub(crate) struct SurfaceImplem<U, R> {
     log: ::slog::Logger,
@@ -64,18 +74,18 @@ impl<U, R> SurfaceImplem<U, R> {
 impl<U, R> SurfaceImplem<U, R>
 where
     U: 'static,
-    R: 'static,
+    R: RoleType + Role<SubsurfaceRole> + 'static,
 {
     fn receive_surface_request(&mut self, req: wl_surface::Request, surface: wl_surface::WlSurface) {
         match req {
             wl_surface::Request::Attach { buffer, x, y } => {
-                SurfaceData::<U, R>::with_data(&surface, |d| {
+                with_pending_surface_state::<U, R, _, _>(&surface, |d| {
                     d.buffer = Some(buffer.map(|b| (b.clone(), (x, y))))
                 });
             }
             wl_surface::Request::Damage { x, y, width, height } => {
-                SurfaceData::<U, R>::with_data(&surface, |d| {
-                    d.damage = Damage::Surface(Rectangle { x, y, width, height })
+                with_pending_surface_state::<U, R, _, _>(&surface, |d| {
+                    d.damage.push(Damage::Surface(Rectangle { x, y, width, height }))
                 });
             }
             wl_surface::Request::Frame { callback } => {
@@ -88,29 +98,46 @@ where
                     let attributes_mutex = r.as_ref().user_data::<Mutex<RegionAttributes>>().unwrap();
                     attributes_mutex.lock().unwrap().clone()
                 });
-                SurfaceData::<U, R>::with_data(&surface, |d| d.opaque_region = attributes);
+                with_pending_surface_state::<U, R, _, _>(&surface, |d| d.opaque_region = attributes);
             }
             wl_surface::Request::SetInputRegion { region } => {
                 let attributes = region.map(|r| {
                     let attributes_mutex = r.as_ref().user_data::<Mutex<RegionAttributes>>().unwrap();
                     attributes_mutex.lock().unwrap().clone()
                 });
-                SurfaceData::<U, R>::with_data(&surface, |d| d.input_region = attributes);
+                with_pending_surface_state::<U, R, _, _>(&surface, |d| d.input_region = attributes);
             }
             wl_surface::Request::Commit => {
-                let mut user_impl = self.implem.borrow_mut();
-                trace!(self.log, "Calling user implementation for wl_surface.commit");
-                (&mut *user_impl)(SurfaceEvent::Commit, surface, CompositorToken::make());
+                SurfaceData::<U, R>::run_pre_commit_hooks(&surface);
+                if !with_pending_surface_state::<U, R, _, _>(&surface, |d| d.viewport.ensure_valid_size()) {
+                    return;
+                }
+                // if the subsurface was synchronized, its leftover cached state
+                // is merged with the new one
+                SurfaceData::<U, R>::commit_pending_states(&surface);
+                if SurfaceData::<U, R>::is_effectively_sync(&surface) {
+                    // the state will be applied when the parent is committed
+                    trace!(self.log, "Caching the state of a synchronized subsurface");
+                } else {
+                    self.commit_cached_state(&surface);
+                }
             }
             wl_surface::Request::SetBufferTransform { transform } => {
-                SurfaceData::<U, R>::with_data(&surface, |d| d.buffer_transform = transform);
+                with_pending_surface_state::<U, R, _, _>(&surface, |d| d.buffer_transform = transform);
             }
             wl_surface::Request::SetBufferScale { scale } => {
-                SurfaceData::<U, R>::with_data(&surface, |d| d.buffer_scale = scale);
+                if scale < 1 {
+                    surface.as_ref().post_error(
+                        wl_surface::Error::InvalidScale as u32,
+                        format!("Invalid buffer scale: {}.", scale),
+                    );
+                    return;
+                }
+                with_pending_surface_state::<U, R, _, _>(&surface, |d| d.buffer_scale = scale);
             }
             wl_surface::Request::DamageBuffer { x, y, width, height } => {
-                SurfaceData::<U, R>::with_data(&surface, |d| {
-                    d.damage = Damage::Buffer(Rectangle { x, y, width, height })
+                with_pending_surface_state::<U, R, _, _>(&surface, |d| {
+                    d.damage.push(Damage::Buffer(Rectangle { x, y, width, height }))
                 });
             }
             wl_surface::Request::Destroy => {
@@ -119,6 +146,31 @@ where
             _ => unreachable!(),
         }
     }
+
+    // Apply the committed state of a surface, if any
+    fn commit_cached_state(&self, surface: &wl_surface::WlSurface) {
+        if !SurfaceData::<U, R>::apply_cached_states(surface) {
+            return;
+        }
+        {
+            let mut user_impl = self.implem.borrow_mut();
+            trace!(self.log, "Calling user implementation for wl_surface.commit");
+            (&mut *user_impl)(SurfaceEvent::Commit, surface.clone(), CompositorToken::make());
+        }
+        SurfaceData::<U, R>::run_post_commit_hooks(surface);
+        self.commit_children(surface);
+    }
+
+    // The state of a surface has been applied, apply the cached state of its
+    // synchronized children as well
+    fn commit_children(&self, surface: &wl_surface::WlSurface) {
+        for child in SurfaceData::<U, R>::get_children(surface) {
+            if child.as_ref().equals(surface.as_ref()) {
+                continue;
+            }
+            self.commit_cached_state(&child);
+        }
+    }
 }
 
 fn implement_surface<U, R, Impl>(
@@ -128,7 +180,7 @@ fn implement_surface<U, R, Impl>(
 ) -> wl_surface::WlSurface
 where
     U: Default + 'static,
-    R: Default + 'static,
+    R: Default + RoleType + Role<SubsurfaceRole> + 'static,
     Impl: FnMut(SurfaceEvent, wl_surface::WlSurface, CompositorToken<U, R>) + 'static,
 {
     let surface = surface.implement_closure(
@@ -175,12 +227,15 @@ fn implement_region(region: NewResource<wl_region::WlRegion>) -> wl_region::WlRe
  * wl_subcompositor
  */
 
-pub(crate) fn implement_subcompositor<U, R>(
+pub(crate) fn implement_subcompositor<U, R, Impl>(
     subcompositor: NewResource<wl_subcompositor::WlSubcompositor>,
+    log: ::slog::Logger,
+    implem: Rc<RefCell<Impl>>,
 ) -> wl_subcompositor::WlSubcompositor
 where
     R: RoleType + Role<SubsurfaceRole> + 'static,
     U: 'static,
+    Impl: FnMut(SurfaceEvent, wl_surface::WlSurface, CompositorToken<U, R>) + 'static,
 {
     subcompositor.implement_closure(
         move |request, subcompositor| match request {
@@ -192,7 +247,11 @@ where
                     );
                     return;
                 }
-                implement_subsurface::<U, R>(id, surface.clone());
+                implement_subsurface::<U, R>(
+                    id,
+                    surface.clone(),
+                    SurfaceImplem::make(log.clone(), implem.clone()),
+                );
             }
             wl_subcompositor::Request::Destroy => {}
             _ => unreachable!(),
@@ -220,13 +279,14 @@ where
 fn implement_subsurface<U, R>(
     subsurface: NewResource<wl_subsurface::WlSubsurface>,
     surface: wl_surface::WlSurface,
+    implem: SurfaceImplem<U, R>,
 ) -> wl_subsurface::WlSubsurface
 where
     U: 'static,
     R: RoleType + Role<SubsurfaceRole> + 'static,
 {
     subsurface.implement_closure(
-        |request, subsurface| {
+        move |request, subsurface| {
             match request {
                 wl_subsurface::Request::SetPosition { x, y } => {
                     with_subsurface_attributes::<U, R, _>(&subsurface, |attrs| {
@@ -259,7 +319,12 @@ where
                 wl_subsurface::Request::SetDesync => {
                     with_subsurface_attributes::<U, R, _>(&subsurface, |attrs| {
                         attrs.sync = false;
-                    })
+                    });
+                    // the cached state is applied as soon as the subsurface is no longer synchronized
+                    let surface = subsurface.as_ref().user_data::<wl_surface::WlSurface>().unwrap();
+                    if !SurfaceData::<U, R>::is_effectively_sync(surface) {
+                        implem.commit_cached_state(surface);
+                    }
                 }
                 wl_subsurface::Request::Destroy => {
                     // Our destructor already handles it
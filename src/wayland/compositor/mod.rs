@@ -71,26 +71,43 @@
 //! for the details of what it enables you.
 //!
 //! The surface metadata is held in the [`SurfaceAttributes`](::wayland::compositor::SurfaceAttributes)
-//! struct. In contains double-buffered state pending from the client as defined by the protocol for
-//! [`wl_surface`](wayland_server::protocol::wl_surface), as well as your user-defined type holding
-//! any data you need to have associated with a struct. See its documentation for details.
+//! struct. It contains the current double-buffered state of the surface as defined by the protocol for
+//! [`wl_surface`](wayland_server::protocol::wl_surface), updated each time the surface is committed,
+//! as well as your user-defined type holding any data you need to have associated with a struct.
+//! See its documentation for details.
 //!
 //! This [`CompositorToken`](::wayland::compositor::CompositorToken) also provides access to the metadata associated with the role of the
 //! surfaces. See the documentation of the [`roles`](::wayland::compositor::roles) submodule
 //! for a detailed explanation.
+//!
+//! ### Double-buffered state of protocol extensions
+//!
+//! Protocol extensions adding state to surfaces can store it in a per-surface container
+//! handling the double-buffering for them: any type implementing [`Cacheable`] has a
+//! pending version, modified by the requests of the client with
+//! [`CompositorToken::with_pending_state`], and a current version, accessed with
+//! [`CompositorToken::with_current_state`]. The pending state is applied atomically when
+//! the surface is committed, or when its parent is if it is a synchronized subsurface.
+//!
+//! Hooks can also be registered on a surface with [`CompositorToken::add_pre_commit_hook`],
+//! for example to validate its pending state, and [`CompositorToken::add_post_commit_hook`],
+//! to be notified once its new state has been applied.
 
-use std::{cell::RefCell, rc::Rc, sync::Mutex};
+use std::{cell::RefCell, mem, rc::Rc, sync::Mutex};
 
+mod cache;
 mod handlers;
 pub mod roles;
 mod tree;
 
+pub use self::cache::Cacheable;
 pub use self::tree::TraversalAction;
 use self::{
     roles::{Role, RoleType, WrongRole},
     tree::SurfaceData,
 };
-use crate::utils::Rectangle;
+use crate::utils::{Rectangle, Region};
+use wayland_protocols::viewporter::server::wp_viewport;
 use wayland_server::{
     protocol::{
         wl_buffer, wl_callback, wl_compositor, wl_output, wl_region, wl_subcompositor, wl_surface::WlSurface,
@@ -100,17 +117,84 @@ use wayland_server::{
 
 /// Description of which part of a surface
 /// should be considered damaged and needs to be redrawn
+#[derive(Copy, Clone, Debug)]
 pub enum Damage {
-    /// The whole surface must be considered damaged (this is the default)
+    /// The whole surface must be considered damaged
+    ///
+    /// Clients never send it, but the compositor can push it to the damage of a
+    /// surface to have all of it redrawn.
     Full,
     /// A rectangle containing the damaged zone, in surface coordinates
     Surface(Rectangle),
     /// A rectangle containing the damaged zone, in buffer coordinates
     ///
-    /// Note: Buffer scaling must be taken into consideration
+    /// It can be converted to surface coordinates with [`buffer_to_surface_rect`],
+    /// or directly with [`SurfaceAttributes::surface_damage`].
     Buffer(Rectangle),
 }
 
+/// Convert a rectangle from buffer coordinates to surface coordinates
+///
+/// The `buffer_size` is the size in pixels of the buffer, and `buffer_scale` and
+/// `buffer_transform` are the scale and transform set by the client for this buffer.
+/// The resulting rectangle is rounded outwards if the scale does not divide it evenly.
+///
+/// This does not take into account the viewport of the surface, see
+/// [`SurfaceAttributes::surface_damage`] for that.
+///
+/// ```
+/// # extern crate wayland_server;
+/// # extern crate smithay;
+/// use smithay::utils::Rectangle;
+/// use smithay::wayland::compositor::buffer_to_surface_rect;
+/// use wayland_server::protocol::wl_output::Transform;
+///
+/// # fn main() {
+/// // the top-left corner of a buffer rotated by 90 degrees...
+/// let rect = Rectangle { x: 0, y: 0, width: 20, height: 10 };
+/// let rect = buffer_to_surface_rect(rect, (200, 100), 2, Transform::_90);
+/// // ...is the bottom-left corner of the surface
+/// assert_eq!((rect.x, rect.y, rect.width, rect.height), (0, 90, 5, 10));
+/// # }
+/// ```
+pub fn buffer_to_surface_rect(
+    rect: Rectangle,
+    buffer_size: (i32, i32),
+    buffer_scale: i32,
+    buffer_transform: wl_output::Transform,
+) -> Rectangle {
+    use wayland_server::protocol::wl_output::Transform;
+
+    let scale = buffer_scale.max(1);
+    let (surface_width, surface_height) = transformed_buffer_size(buffer_size, scale, buffer_transform);
+    let map = |x: i32, y: i32| match buffer_transform {
+        Transform::Normal => (x, y),
+        Transform::_90 => (y, surface_height - x),
+        Transform::_180 => (surface_width - x, surface_height - y),
+        Transform::_270 => (surface_width - y, x),
+        Transform::Flipped => (surface_width - x, y),
+        Transform::Flipped90 => (surface_width - y, surface_height - x),
+        Transform::Flipped180 => (x, surface_height - y),
+        Transform::Flipped270 => (y, x),
+        _ => unreachable!(),
+    };
+    // scale the corners outwards
+    let (x1, y1) = map(
+        (f64::from(rect.x) / f64::from(scale)).floor() as i32,
+        (f64::from(rect.y) / f64::from(scale)).floor() as i32,
+    );
+    let (x2, y2) = map(
+        (f64::from(rect.x + rect.width) / f64::from(scale)).ceil() as i32,
+        (f64::from(rect.y + rect.height) / f64::from(scale)).ceil() as i32,
+    );
+    Rectangle {
+        x: x1.min(x2),
+        y: y1.min(y2),
+        width: (x2 - x1).abs(),
+        height: (y2 - y1).abs(),
+    }
+}
+
 #[derive(Copy, Clone, Default)]
 struct Marker<U, R> {
     _u: ::std::marker::PhantomData<U>,
@@ -119,24 +203,24 @@ struct Marker<U, R> {
 
 /// Data associated with a surface, aggregated by the handlers
 ///
-/// Most of the fields of this struct represent a double-buffered state, which
-/// should only be applied once a [`commit`](::wayland::compositor::SurfaceEvent::Commit)
-/// request is received from the surface.
+/// Most of the fields of this struct represent the current double-buffered state
+/// of the surface. The requests of the client are accumulated in a pending state,
+/// which replaces these fields when the surface is committed, right before the
+/// [`Commit`](::wayland::compositor::SurfaceEvent::Commit) event is sent to you.
+/// For synchronized subsurfaces, this only happens when their parent is committed.
 ///
-/// You are responsible for setting those values as you see fit to avoid
-/// processing them two times.
+/// State added by protocol extensions is stored the same way, by implementing [`Cacheable`].
 pub struct SurfaceAttributes<U> {
-    /// Buffer defining the contents of the surface
+    /// Buffer attached to the surface by its last commit
     ///
     /// The tuple represent the coordinates of this buffer
-    /// relative to the location of the current buffer.
+    /// relative to the location of the current buffer. When the states of several
+    /// commits of a synchronized subsurface are applied at once, it is the sum of
+    /// the offsets of all the buffers they attached.
     ///
     /// If set to `Some(None)`, it means the user specifically asked for the
-    /// surface to be unmapped.
-    ///
-    /// You are free to set this field to `None` to avoid processing it several
-    /// times. It'll be set to `Some(...)` if the user attaches a buffer (or `NULL`) to
-    /// the surface.
+    /// surface to be unmapped. It is `None` if the last commit did not attach
+    /// a new buffer (or `NULL`) to the surface.
     pub buffer: Option<Option<(wl_buffer::WlBuffer, (i32, i32))>>,
     /// Scale of the contents of the buffer, for higher-resolution contents.
     ///
@@ -156,11 +240,19 @@ pub struct SurfaceAttributes<U> {
     ///
     /// By default the whole surface should be sensitive
     pub input_region: Option<RegionAttributes>,
-    /// Damage rectangle
+    /// Crop and scale state of the surface
+    ///
+    /// It is set by clients using the `wp_viewporter` protocol, see the
+    /// [`viewporter`](::wayland::viewporter) module.
+    pub viewport: SurfaceViewport,
+    /// Damage accumulated by the last commit
     ///
-    /// Hint provided by the client to suggest that only this part
-    /// of the surface was changed and needs to be redrawn
-    pub damage: Damage,
+    /// Hint provided by the client to suggest that only these parts
+    /// of the surface were changed and need to be redrawn.
+    ///
+    /// This list is replaced at each commit of the surface, so you need to
+    /// process it while handling the [`Commit`](SurfaceEvent::Commit) event.
+    pub damage: Vec<Damage>,
     /// User-controlled data
     ///
     /// This is your field to host whatever you need.
@@ -175,12 +267,262 @@ impl<U: Default> Default for SurfaceAttributes<U> {
             buffer_transform: wl_output::Transform::Normal,
             opaque_region: None,
             input_region: None,
-            damage: Damage::Full,
+            viewport: Default::default(),
+            damage: Vec::new(),
             user_data: Default::default(),
         }
     }
 }
 
+// The double-buffered state of a `wl_surface`
+//
+// Its pending version is modified by the requests of the client, and it replaces
+// the corresponding fields of the `SurfaceAttributes` when it is applied.
+pub(crate) struct SurfaceState {
+    pub(crate) buffer: Option<Option<(wl_buffer::WlBuffer, (i32, i32))>>,
+    pub(crate) buffer_scale: i32,
+    pub(crate) buffer_transform: wl_output::Transform,
+    pub(crate) opaque_region: Option<RegionAttributes>,
+    pub(crate) input_region: Option<RegionAttributes>,
+    pub(crate) viewport: SurfaceViewport,
+    pub(crate) damage: Vec<Damage>,
+}
+
+impl Default for SurfaceState {
+    fn default() -> SurfaceState {
+        SurfaceState {
+            buffer: None,
+            buffer_scale: 1,
+            buffer_transform: wl_output::Transform::Normal,
+            opaque_region: None,
+            input_region: None,
+            viewport: Default::default(),
+            damage: Vec::new(),
+        }
+    }
+}
+
+impl Cacheable for SurfaceState {
+    fn commit(&mut self) -> SurfaceState {
+        SurfaceState {
+            buffer: self.buffer.take(),
+            buffer_scale: self.buffer_scale,
+            buffer_transform: self.buffer_transform,
+            opaque_region: self.opaque_region.clone(),
+            input_region: self.input_region.clone(),
+            viewport: self.viewport.clone(),
+            damage: mem::replace(&mut self.damage, Vec::new()),
+        }
+    }
+
+    fn merge_into(self, into: &mut SurfaceState) {
+        merge_attach(self.buffer, &mut into.buffer);
+        into.buffer_scale = self.buffer_scale;
+        into.buffer_transform = self.buffer_transform;
+        into.opaque_region = self.opaque_region;
+        into.input_region = self.input_region;
+        into.viewport = self.viewport;
+        into.damage.extend(self.damage);
+    }
+}
+
+// Merge an attach request into an older one
+//
+// The offset of an attach is relative to the current position of the surface, so when
+// two attached buffers are merged, the offset of the later one is relative to the position
+// resulting from the earlier one.
+fn merge_attach<B>(attach: Option<Option<(B, (i32, i32))>>, into: &mut Option<Option<(B, (i32, i32))>>) {
+    match attach {
+        Some(Some((buffer, (x, y)))) => {
+            let (dx, dy) = match *into {
+                Some(Some((_, offset))) => offset,
+                _ => (0, 0),
+            };
+            *into = Some(Some((buffer, (x + dx, y + dy))));
+        }
+        Some(None) => *into = Some(None),
+        None => {}
+    }
+}
+
+impl SurfaceState {
+    pub(crate) fn apply_to<U>(self, attributes: &mut SurfaceAttributes<U>) {
+        attributes.buffer = self.buffer;
+        attributes.buffer_scale = self.buffer_scale;
+        attributes.buffer_transform = self.buffer_transform;
+        attributes.opaque_region = self.opaque_region;
+        attributes.input_region = self.input_region;
+        attributes.viewport = self.viewport;
+        attributes.damage = self.damage;
+    }
+}
+
+impl<U> SurfaceAttributes<U> {
+    /// Compute the size of the surface in surface-local coordinates
+    ///
+    /// The provided `buffer_size` is the size in pixels of the buffer attached to this
+    /// surface. The buffer transform and scale are applied to it, and then the crop and
+    /// scale of the viewport, if any.
+    pub fn surface_size(&self, buffer_size: (i32, i32)) -> (i32, i32) {
+        if let Some(destination) = self.viewport.destination {
+            return destination;
+        }
+        if let Some(source) = self.viewport.source {
+            return (source.width as i32, source.height as i32);
+        }
+        self.transformed_buffer_size(buffer_size)
+    }
+
+    /// Compute the damage of the surface in surface-local coordinates
+    ///
+    /// The provided `buffer_size` is the size in pixels of the buffer attached to this
+    /// surface. The buffer damage is converted according to the buffer transform and
+    /// scale, as well as the crop and scale of the viewport, if any.
+    pub fn surface_damage(&self, buffer_size: (i32, i32)) -> Vec<Rectangle> {
+        self.damage
+            .iter()
+            .map(|damage| match *damage {
+                Damage::Full => {
+                    let (width, height) = self.surface_size(buffer_size);
+                    Rectangle {
+                        x: 0,
+                        y: 0,
+                        width,
+                        height,
+                    }
+                }
+                Damage::Surface(rect) => rect,
+                Damage::Buffer(rect) => {
+                    let rect =
+                        buffer_to_surface_rect(rect, buffer_size, self.buffer_scale, self.buffer_transform);
+                    self.apply_viewport(rect, buffer_size)
+                }
+            })
+            .collect()
+    }
+
+    // convert a rectangle from the coordinates of the transformed and scaled buffer
+    // to surface-local coordinates
+    fn apply_viewport(&self, rect: Rectangle, buffer_size: (i32, i32)) -> Rectangle {
+        let (src_x, src_y, src_width, src_height) = match self.viewport.source {
+            Some(source) => (source.x, source.y, source.width, source.height),
+            None => {
+                let (width, height) = self.transformed_buffer_size(buffer_size);
+                (0.0, 0.0, f64::from(width), f64::from(height))
+            }
+        };
+        if src_width <= 0.0 || src_height <= 0.0 {
+            return rect;
+        }
+        let (dst_width, dst_height) = self.surface_size(buffer_size);
+        let scale_x = f64::from(dst_width) / src_width;
+        let scale_y = f64::from(dst_height) / src_height;
+        let x1 = ((f64::from(rect.x) - src_x) * scale_x).floor() as i32;
+        let y1 = ((f64::from(rect.y) - src_y) * scale_y).floor() as i32;
+        let x2 = ((f64::from(rect.x + rect.width) - src_x) * scale_x).ceil() as i32;
+        let y2 = ((f64::from(rect.y + rect.height) - src_y) * scale_y).ceil() as i32;
+        Rectangle {
+            x: x1,
+            y: y1,
+            width: x2 - x1,
+            height: y2 - y1,
+        }
+    }
+
+    fn transformed_buffer_size(&self, buffer_size: (i32, i32)) -> (i32, i32) {
+        transformed_buffer_size(buffer_size, self.buffer_scale, self.buffer_transform)
+    }
+}
+
+// size of a buffer once its scale and transform are applied
+fn transformed_buffer_size(
+    (width, height): (i32, i32),
+    buffer_scale: i32,
+    buffer_transform: wl_output::Transform,
+) -> (i32, i32) {
+    let scale = buffer_scale.max(1);
+    let (width, height) = (width / scale, height / scale);
+    match buffer_transform {
+        wl_output::Transform::_90
+        | wl_output::Transform::_270
+        | wl_output::Transform::Flipped90
+        | wl_output::Transform::Flipped270 => (height, width),
+        _ => (width, height),
+    }
+}
+
+/// Source rectangle of a surface viewport
+///
+/// It is expressed in the coordinates of the buffer after its transform
+/// and scale have been applied.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ViewportSource {
+    /// Horizontal position of the top-left corner
+    pub x: f64,
+    /// Vertical position of the top-left corner
+    pub y: f64,
+    /// Width of the rectangle
+    pub width: f64,
+    /// Height of the rectangle
+    pub height: f64,
+}
+
+/// Crop and scale state of a surface
+///
+/// If both are unset, the whole buffer is displayed unscaled.
+#[derive(Clone, Default)]
+pub struct SurfaceViewport {
+    /// Part of the buffer to display, if cropped
+    pub source: Option<ViewportSource>,
+    /// Size in surface-local coordinates to which the source is scaled, if any
+    pub destination: Option<(i32, i32)>,
+    pub(crate) resource: Option<wp_viewport::WpViewport>,
+}
+
+impl SurfaceViewport {
+    // Check that the size of the surface is defined by the viewport state,
+    // sending the `bad_size` protocol error otherwise
+    pub(crate) fn ensure_valid_size(&self) -> bool {
+        match (self.source, self.destination, &self.resource) {
+            (Some(source), None, Some(viewport))
+                if source.width.fract() != 0.0 || source.height.fract() != 0.0 =>
+            {
+                viewport.as_ref().post_error(
+                    wp_viewport::Error::BadSize as u32,
+                    "Source size is not integer while destination size is not set.".into(),
+                );
+                false
+            }
+            _ => true,
+        }
+    }
+
+    // Check that the source rectangle is contained in the buffer of given size, once its
+    // scale and transform are applied, sending the `out_of_buffer` protocol error otherwise
+    pub(crate) fn ensure_in_buffer(
+        &self,
+        buffer_size: (i32, i32),
+        buffer_scale: i32,
+        buffer_transform: wl_output::Transform,
+    ) -> bool {
+        let source = match self.source {
+            Some(source) => source,
+            None => return true,
+        };
+        let (width, height) = transformed_buffer_size(buffer_size, buffer_scale, buffer_transform);
+        if source.x + source.width > f64::from(width) || source.y + source.height > f64::from(height) {
+            if let Some(ref viewport) = self.resource {
+                viewport.as_ref().post_error(
+                    wp_viewport::Error::OutOfBuffer as u32,
+                    "Source rectangle extends outside of the buffer.".into(),
+                );
+            }
+            return false;
+        }
+        true
+    }
+}
+
 /// Attributes defining the behaviour of a sub-surface relative to its parent
 #[derive(Copy, Clone, Debug)]
 pub struct SubsurfaceRole {
@@ -192,6 +534,9 @@ pub struct SubsurfaceRole {
     /// If `true`, this surface should be repainted synchronously with its parent
     /// if `false`, it should be considered independent of its parent regarding
     /// repaint timings.
+    ///
+    /// This is handled for you: the commits of a synchronized subsurface are cached,
+    /// and only forwarded to your implementation once its parent is committed.
     pub sync: bool,
 }
 
@@ -233,6 +578,58 @@ impl Default for RegionAttributes {
     }
 }
 
+impl RegionAttributes {
+    /// Checks whether given point is inside the region
+    pub fn contains(&self, point: (i32, i32)) -> bool {
+        // the last rectangle containing the point decides whether it was added or subtracted
+        match self
+            .rects
+            .iter()
+            .rev()
+            .find(|&&(_, ref rect)| rect.contains(point))
+        {
+            Some(&(RectangleKind::Add, _)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> From<&'a RegionAttributes> for Region {
+    /// Compute the actual contents of the region, by applying its rectangles in order
+    ///
+    /// ```
+    /// use smithay::utils::{Rectangle, Region};
+    /// use smithay::wayland::compositor::{RectangleKind, RegionAttributes};
+    ///
+    /// let attributes = RegionAttributes {
+    ///     rects: vec![
+    ///         (RectangleKind::Add, Rectangle { x: 0, y: 0, width: 10, height: 10 }),
+    ///         (RectangleKind::Subtract, Rectangle { x: 0, y: 0, width: 5, height: 10 }),
+    ///         (RectangleKind::Add, Rectangle { x: 0, y: 0, width: 2, height: 2 }),
+    ///     ],
+    /// };
+    /// let region = Region::from(&attributes);
+    /// assert_eq!(region.area(), 5 * 10 + 2 * 2);
+    /// assert!(region.contains((1, 1)) && !region.contains((3, 3)) && region.contains((7, 7)));
+    /// ```
+    fn from(attributes: &'a RegionAttributes) -> Region {
+        let mut region = Region::new();
+        for &(kind, rect) in &attributes.rects {
+            match kind {
+                RectangleKind::Add => region.add_rect(rect),
+                RectangleKind::Subtract => region.subtract_rect(rect),
+            }
+        }
+        region
+    }
+}
+
+impl From<RegionAttributes> for Region {
+    fn from(attributes: RegionAttributes) -> Region {
+        Region::from(&attributes)
+    }
+}
+
 /// A Compositor global token
 ///
 /// This token can be cloned at will, and is the entry-point to
@@ -278,6 +675,69 @@ impl<U: 'static, R: 'static> CompositorToken<U, R> {
     {
         SurfaceData::<U, R>::with_data(surface, f)
     }
+
+    /// Access the pending double-buffered state of given type of a surface
+    ///
+    /// It will be applied at the next commit of the surface. If no state of
+    /// this type was associated with this surface yet, it is created with its
+    /// default value.
+    ///
+    /// If the surface is not managed by the `CompositorGlobal` that provided this token, this
+    /// will panic (having more than one compositor is not supported).
+    pub fn with_pending_state<S, F, T>(&self, surface: &WlSurface, f: F) -> T
+    where
+        S: Cacheable + 'static,
+        F: FnOnce(&mut S) -> T,
+    {
+        SurfaceData::<U, R>::with_states(surface, |states| f(states.pending::<S>()))
+    }
+
+    /// Access the current double-buffered state of given type of a surface
+    ///
+    /// If no state of this type was associated with this surface yet, it is created
+    /// with its default value.
+    ///
+    /// If the surface is not managed by the `CompositorGlobal` that provided this token, this
+    /// will panic (having more than one compositor is not supported).
+    pub fn with_current_state<S, F, T>(&self, surface: &WlSurface, f: F) -> T
+    where
+        S: Cacheable + 'static,
+        F: FnOnce(&mut S) -> T,
+    {
+        SurfaceData::<U, R>::with_states(surface, |states| f(states.current::<S>()))
+    }
+
+    /// Register a hook invoked at each commit of a surface, before its state is applied
+    ///
+    /// It is typically used to validate the pending state of the surface and post
+    /// protocol errors. The hook is invoked even if the state of the surface is
+    /// cached because it is a synchronized subsurface.
+    ///
+    /// The hook can capture the state it needs, it is kept until the surface is destroyed.
+    ///
+    /// If the surface is not managed by the `CompositorGlobal` that provided this token, this
+    /// will panic (having more than one compositor is not supported).
+    pub fn add_pre_commit_hook<F>(&self, surface: &WlSurface, hook: F)
+    where
+        F: Fn(&WlSurface) + 'static,
+    {
+        SurfaceData::<U, R>::add_pre_commit_hook(surface, hook)
+    }
+
+    /// Register a hook invoked each time the state of a surface has been applied
+    ///
+    /// The hook is invoked after your implementation received the
+    /// [`Commit`](SurfaceEvent::Commit) event. Like the pre-commit hooks, it is kept
+    /// until the surface is destroyed.
+    ///
+    /// If the surface is not managed by the `CompositorGlobal` that provided this token, this
+    /// will panic (having more than one compositor is not supported).
+    pub fn add_post_commit_hook<F>(&self, surface: &WlSurface, hook: F)
+    where
+        F: Fn(&WlSurface) + 'static,
+    {
+        SurfaceData::<U, R>::add_post_commit_hook(surface, hook)
+    }
 }
 
 impl<U, R> CompositorToken<U, R>
@@ -456,12 +916,18 @@ where
     let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "compositor_handler"));
     let implem = Rc::new(RefCell::new(implem));
 
+    let compositor_log = log.clone();
+    let compositor_implem = implem.clone();
     let compositor = display.create_global(4, move |new_compositor, _version| {
-        self::handlers::implement_compositor::<U, R, Impl>(new_compositor, log.clone(), implem.clone());
+        self::handlers::implement_compositor::<U, R, Impl>(
+            new_compositor,
+            compositor_log.clone(),
+            compositor_implem.clone(),
+        );
     });
 
     let subcompositor = display.create_global(1, move |new_subcompositor, _version| {
-        self::handlers::implement_subcompositor::<U, R>(new_subcompositor);
+        self::handlers::implement_subcompositor::<U, R, Impl>(new_subcompositor, log.clone(), implem.clone());
     });
 
     (CompositorToken::make(), compositor, subcompositor)
@@ -493,3 +959,43 @@ pub enum SurfaceEvent {
         callback: NewResource<wl_callback::WlCallback>,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_attach, Damage, SurfaceAttributes};
+    use crate::utils::Rectangle;
+    use wayland_server::protocol::wl_output::Transform;
+
+    #[test]
+    fn attach_offsets_are_accumulated() {
+        let mut cached = None;
+        merge_attach(Some(Some(("first", (5, -3)))), &mut cached);
+        assert_eq!(cached, Some(Some(("first", (5, -3)))));
+        merge_attach(Some(Some(("second", (2, 10)))), &mut cached);
+        assert_eq!(cached, Some(Some(("second", (7, 7)))));
+        // commits without attach do not change it
+        merge_attach(None, &mut cached);
+        assert_eq!(cached, Some(Some(("second", (7, 7)))));
+    }
+
+    #[test]
+    fn attach_after_detach_keeps_its_offset() {
+        let mut cached = Some(Some(("first", (5, 5))));
+        merge_attach(Some(None), &mut cached);
+        assert_eq!(cached, Some(None));
+        merge_attach(Some(Some(("second", (1, 2)))), &mut cached);
+        assert_eq!(cached, Some(Some(("second", (1, 2)))));
+    }
+
+    #[test]
+    fn full_damage_covers_the_surface() {
+        let mut attributes = SurfaceAttributes::<()>::default();
+        attributes.buffer_scale = 2;
+        attributes.buffer_transform = Transform::_90;
+        attributes.damage = vec![Damage::Full];
+        let damage = attributes.surface_damage((200, 100));
+        assert_eq!(damage.len(), 1);
+        let Rectangle { x, y, width, height } = damage[0];
+        assert_eq!((x, y, width, height), (0, 0, 50, 100));
+    }
+}
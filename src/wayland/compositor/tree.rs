@@ -1,5 +1,5 @@
-use super::{roles::*, SubsurfaceRole, SurfaceAttributes};
-use std::sync::Mutex;
+use super::{cache::MultiCache, roles::*, SubsurfaceRole, SurfaceAttributes, SurfaceState};
+use std::{mem, rc::Rc, sync::Mutex};
 use wayland_server::protocol::wl_surface::WlSurface;
 
 /// Node of a subsurface tree, holding some user specified data type U
@@ -23,8 +23,13 @@ pub struct SurfaceData<U, R> {
     children: Vec<WlSurface>,
     role: R,
     attributes: SurfaceAttributes<U>,
+    states: MultiCache,
+    pre_commit_hooks: Vec<CommitHook>,
+    post_commit_hooks: Vec<CommitHook>,
 }
 
+type CommitHook = Rc<dyn Fn(&WlSurface)>;
+
 pub enum Location {
     Before,
     After,
@@ -42,11 +47,18 @@ pub enum TraversalAction<T> {
 
 impl<U: Default, R: Default> SurfaceData<U, R> {
     pub fn new() -> Mutex<SurfaceData<U, R>> {
+        let mut states = MultiCache::default();
+        // the state of the wl_surface itself is always tracked, so that each commit
+        // has some state to apply
+        states.pending::<SurfaceState>();
         Mutex::new(SurfaceData {
             parent: None,
             children: Vec::new(),
             role: Default::default(),
             attributes: Default::default(),
+            states,
+            pre_commit_hooks: Vec::new(),
+            post_commit_hooks: Vec::new(),
         })
     }
 }
@@ -229,6 +241,34 @@ impl<U: 'static, R: RoleType + Role<SubsurfaceRole> + 'static> SurfaceData<U, R>
         child_guard.children.to_vec()
     }
 
+    /// Check whether the commits of this surface must be synchronized with its parent
+    ///
+    /// This is the case if it is a subsurface in synchronized mode, or if one of
+    /// its ancestors is.
+    pub fn is_effectively_sync(surface: &WlSurface) -> bool {
+        let mut visited = vec![surface.clone()];
+        let mut current = surface.clone();
+        loop {
+            let is_sync = match Self::with_role_data::<SubsurfaceRole, _, _>(&current, |d| d.sync) {
+                Ok(is_sync) => is_sync,
+                // not a subsurface, so the root of the tree
+                Err(_) => return false,
+            };
+            if is_sync {
+                return true;
+            }
+            current = match Self::get_parent(&current) {
+                Some(parent) => parent,
+                None => return false,
+            };
+            // protect ourselves against loops in the tree
+            if visited.iter().any(|s| s.as_ref().equals(current.as_ref())) {
+                return false;
+            }
+            visited.push(current.clone());
+        }
+    }
+
     /// Reorders a surface relative to one of its sibling
     ///
     /// Fails if `relative_to` is not a sibling or parent of `surface`.
@@ -289,6 +329,92 @@ impl<U: 'static, R: 'static> SurfaceData<U, R> {
         f(&mut data_guard.attributes)
     }
 
+    /// Commit the pending states of this surface, merging them into its cache
+    ///
+    /// They are applied by [`apply_cached_states`](SurfaceData::apply_cached_states), which
+    /// is delayed until the commit of the parent for synchronized subsurfaces.
+    pub fn commit_pending_states(surface: &WlSurface) {
+        let data_mutex = surface.as_ref().user_data::<Mutex<SurfaceData<U, R>>>().unwrap();
+        data_mutex.lock().unwrap().states.commit();
+    }
+
+    /// Apply the cached states of this surface, updating its attributes
+    ///
+    /// Returns `false` and does nothing if there is no cached state.
+    pub fn apply_cached_states(surface: &WlSurface) -> bool {
+        let data_mutex = surface.as_ref().user_data::<Mutex<SurfaceData<U, R>>>().unwrap();
+        let mut data_guard = data_mutex.lock().unwrap();
+        let data_guard = &mut *data_guard;
+        if !data_guard.states.has_cached() {
+            return false;
+        }
+        data_guard.states.apply();
+        // the state of the wl_surface is exposed through the attributes
+        let state = mem::replace(
+            data_guard.states.current::<SurfaceState>(),
+            SurfaceState::default(),
+        );
+        state.apply_to(&mut data_guard.attributes);
+        true
+    }
+
+    /// Access the double-buffered states associated with a surface
+    pub fn with_states<T, F>(surface: &WlSurface, f: F) -> T
+    where
+        F: FnOnce(&mut MultiCache) -> T,
+    {
+        let data_mutex = surface
+            .as_ref()
+            .user_data::<Mutex<SurfaceData<U, R>>>()
+            .expect("Accessing the data of foreign surfaces is not supported.");
+        let mut data_guard = data_mutex.lock().unwrap();
+        f(&mut data_guard.states)
+    }
+
+    /// Register a hook to be invoked at each commit of this surface, before its state is applied
+    pub fn add_pre_commit_hook<F>(surface: &WlSurface, hook: F)
+    where
+        F: Fn(&WlSurface) + 'static,
+    {
+        let data_mutex = surface.as_ref().user_data::<Mutex<SurfaceData<U, R>>>().unwrap();
+        data_mutex.lock().unwrap().pre_commit_hooks.push(Rc::new(hook));
+    }
+
+    /// Register a hook to be invoked each time the state of this surface is applied
+    pub fn add_post_commit_hook<F>(surface: &WlSurface, hook: F)
+    where
+        F: Fn(&WlSurface) + 'static,
+    {
+        let data_mutex = surface.as_ref().user_data::<Mutex<SurfaceData<U, R>>>().unwrap();
+        data_mutex.lock().unwrap().post_commit_hooks.push(Rc::new(hook));
+    }
+
+    /// Invoke the pre-commit hooks of this surface
+    ///
+    /// The hooks are called without holding the lock, so they can access the surface data.
+    pub fn run_pre_commit_hooks(surface: &WlSurface) {
+        let hooks = {
+            let data_mutex = surface.as_ref().user_data::<Mutex<SurfaceData<U, R>>>().unwrap();
+            data_mutex.lock().unwrap().pre_commit_hooks.clone()
+        };
+        for hook in hooks {
+            hook(surface);
+        }
+    }
+
+    /// Invoke the post-commit hooks of this surface
+    ///
+    /// The hooks are called without holding the lock, so they can access the surface data.
+    pub fn run_post_commit_hooks(surface: &WlSurface) {
+        let hooks = {
+            let data_mutex = surface.as_ref().user_data::<Mutex<SurfaceData<U, R>>>().unwrap();
+            data_mutex.lock().unwrap().post_commit_hooks.clone()
+        };
+        for hook in hooks {
+            hook(surface);
+        }
+    }
+
     /// Access sequentially the attributes associated with a surface tree,
     /// in a depth-first order.
     ///
@@ -6,9 +6,15 @@ use wayland_server::{
     NewResource,
 };
 
-use crate::wayland::{
-    compositor::{roles::Role, CompositorToken},
-    seat::{AxisFrame, PointerGrab, PointerInnerHandle, Seat},
+use crate::{
+    backend::input::TouchSlot,
+    wayland::{
+        compositor::{roles::Role, CompositorToken},
+        seat::{
+            slot_or_default, AxisFrame, PointerGrab, PointerInnerHandle, Seat, TouchGrab, TouchInnerHandle,
+        },
+        SERIAL_COUNTER,
+    },
 };
 
 use super::{with_source_metadata, DataDeviceData, DnDIconRole, SeatData};
@@ -23,6 +29,7 @@ pub(crate) struct DnDGrab<U, R> {
     callback: Rc<RefCell<dyn FnMut(super::DataDeviceEvent)>>,
     token: CompositorToken<U, R>,
     seat: Seat,
+    touch_slot: Option<TouchSlot>,
 }
 
 impl<U: 'static, R: Role<DnDIconRole> + 'static> DnDGrab<U, R> {
@@ -33,6 +40,7 @@ impl<U: 'static, R: Role<DnDIconRole> + 'static> DnDGrab<U, R> {
         icon: Option<wl_surface::WlSurface>,
         token: CompositorToken<U, R>,
         callback: Rc<RefCell<dyn FnMut(super::DataDeviceEvent)>>,
+        touch_slot: Option<TouchSlot>,
     ) -> DnDGrab<U, R> {
         DnDGrab {
             data_source: source,
@@ -44,17 +52,15 @@ impl<U: 'static, R: Role<DnDIconRole> + 'static> DnDGrab<U, R> {
             callback,
             token,
             seat,
+            touch_slot,
         }
     }
-}
 
-impl<U: 'static, R: Role<DnDIconRole> + 'static> PointerGrab for DnDGrab<U, R> {
-    fn motion(
+    fn update_focus(
         &mut self,
-        _handle: &mut PointerInnerHandle<'_>,
         location: (f64, f64),
         focus: Option<(wl_surface::WlSurface, (f64, f64))>,
-        serial: u32,
+        serial: Option<u32>,
         time: u32,
     ) {
         let (x, y) = location;
@@ -89,6 +95,8 @@ impl<U: 'static, R: Role<DnDIconRole> + 'static> PointerGrab for DnDGrab<U, R> {
                 None => return,
             };
             if self.current_focus.is_none() {
+                // events without a serial of their own only get one when an enter is sent
+                let serial = serial.unwrap_or_else(|| SERIAL_COUNTER.next_serial());
                 // We entered a new surface, send the data offer if appropriate
                 if let Some(ref source) = self.data_source {
                     let offer_data = Rc::new(RefCell::new(OfferData {
@@ -157,6 +165,99 @@ impl<U: 'static, R: Role<DnDIconRole> + 'static> PointerGrab for DnDGrab<U, R> {
         }
     }
 
+    fn finish_drop(&mut self) {
+        let seat_data = self
+            .seat
+            .user_data()
+            .get::<RefCell<SeatData>>()
+            .unwrap()
+            .borrow_mut();
+        let validated = if let Some(ref data) = self.offer_data {
+            let data = data.borrow();
+            data.accepted && (!data.chosen_action.is_empty())
+        } else {
+            false
+        };
+        if let Some(ref surface) = self.current_focus {
+            if self.data_source.is_some() || self.origin.as_ref().same_client_as(&surface.as_ref()) {
+                for device in &seat_data.known_devices {
+                    if device.as_ref().same_client_as(surface.as_ref()) {
+                        if validated {
+                            device.drop();
+                        } else {
+                            device.leave();
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(ref offer_data) = self.offer_data {
+            let mut data = offer_data.borrow_mut();
+            if validated {
+                data.dropped = true;
+            } else {
+                data.active = false;
+            }
+        }
+        if let Some(ref source) = self.data_source {
+            source.dnd_drop_performed();
+            if !validated {
+                source.cancelled();
+            }
+        }
+        drop(seat_data);
+        self.end_drag();
+    }
+
+    fn cancel_drag(&mut self) {
+        let seat_data = self
+            .seat
+            .user_data()
+            .get::<RefCell<SeatData>>()
+            .unwrap()
+            .borrow_mut();
+        if let Some(ref surface) = self.current_focus {
+            if self.data_source.is_some() || self.origin.as_ref().same_client_as(&surface.as_ref()) {
+                for device in &seat_data.known_devices {
+                    if device.as_ref().same_client_as(surface.as_ref()) {
+                        device.leave();
+                    }
+                }
+            }
+        }
+        if let Some(ref offer_data) = self.offer_data {
+            offer_data.borrow_mut().active = false;
+        }
+        // nothing was dropped, the source is only told that the drag is over
+        if let Some(ref source) = self.data_source {
+            source.cancelled();
+        }
+        drop(seat_data);
+        self.end_drag();
+    }
+
+    fn end_drag(&mut self) {
+        (&mut *self.callback.borrow_mut())(super::DataDeviceEvent::DnDDropped);
+        if let Some(icon) = self.icon.take() {
+            if icon.as_ref().is_alive() {
+                self.token.remove_role::<super::DnDIconRole>(&icon).unwrap();
+            }
+        }
+    }
+}
+
+impl<U: 'static, R: Role<DnDIconRole> + 'static> PointerGrab for DnDGrab<U, R> {
+    fn motion(
+        &mut self,
+        _handle: &mut PointerInnerHandle<'_>,
+        location: (f64, f64),
+        focus: Option<(wl_surface::WlSurface, (f64, f64))>,
+        serial: u32,
+        time: u32,
+    ) {
+        self.update_focus(location, focus, Some(serial), time);
+    }
+
     fn button(
         &mut self,
         handle: &mut PointerInnerHandle<'_>,
@@ -167,51 +268,7 @@ impl<U: 'static, R: Role<DnDIconRole> + 'static> PointerGrab for DnDGrab<U, R> {
     ) {
         if handle.current_pressed().len() == 0 {
             // the user dropped, proceed to the drop
-            let seat_data = self
-                .seat
-                .user_data()
-                .get::<RefCell<SeatData>>()
-                .unwrap()
-                .borrow_mut();
-            let validated = if let Some(ref data) = self.offer_data {
-                let data = data.borrow();
-                data.accepted && (!data.chosen_action.is_empty())
-            } else {
-                false
-            };
-            if let Some(ref surface) = self.current_focus {
-                if self.data_source.is_some() || self.origin.as_ref().same_client_as(&surface.as_ref()) {
-                    for device in &seat_data.known_devices {
-                        if device.as_ref().same_client_as(surface.as_ref()) {
-                            if validated {
-                                device.drop();
-                            } else {
-                                device.leave();
-                            }
-                        }
-                    }
-                }
-            }
-            if let Some(ref offer_data) = self.offer_data {
-                let mut data = offer_data.borrow_mut();
-                if validated {
-                    data.dropped = true;
-                } else {
-                    data.active = false;
-                }
-            }
-            if let Some(ref source) = self.data_source {
-                source.dnd_drop_performed();
-                if !validated {
-                    source.cancelled();
-                }
-            }
-            (&mut *self.callback.borrow_mut())(super::DataDeviceEvent::DnDDropped);
-            if let Some(icon) = self.icon.take() {
-                if icon.as_ref().is_alive() {
-                    self.token.remove_role::<super::DnDIconRole>(&icon).unwrap();
-                }
-            }
+            self.finish_drop();
             // in all cases abandon the drop
             // no more buttons are pressed, release the grab
             handle.unset_grab(serial, time);
@@ -224,6 +281,50 @@ impl<U: 'static, R: Role<DnDIconRole> + 'static> PointerGrab for DnDGrab<U, R> {
     }
 }
 
+impl<U: 'static, R: Role<DnDIconRole> + 'static> TouchGrab for DnDGrab<U, R> {
+    fn down(
+        &mut self,
+        _handle: &mut TouchInnerHandle<'_>,
+        _slot: Option<TouchSlot>,
+        _location: (f64, f64),
+        _focus: Option<(wl_surface::WlSurface, (f64, f64))>,
+        _serial: u32,
+        _time: u32,
+    ) {
+        // other touch points are ignored during the drag
+    }
+
+    fn up(&mut self, handle: &mut TouchInnerHandle<'_>, slot: Option<TouchSlot>, _serial: u32, _time: u32) {
+        if self.touch_slot == Some(slot_or_default(slot)) {
+            // the user dropped, proceed to the drop
+            self.finish_drop();
+            handle.unset_grab();
+        }
+    }
+
+    fn motion(
+        &mut self,
+        _handle: &mut TouchInnerHandle<'_>,
+        slot: Option<TouchSlot>,
+        location: (f64, f64),
+        focus: Option<(wl_surface::WlSurface, (f64, f64))>,
+        time: u32,
+    ) {
+        // the drag only follows the touch point that started it
+        if self.touch_slot == Some(slot_or_default(slot)) {
+            self.update_focus(location, focus, None, time);
+        }
+    }
+
+    fn frame(&mut self, _handle: &mut TouchInnerHandle<'_>) {}
+
+    fn cancel(&mut self, handle: &mut TouchInnerHandle<'_>) {
+        // a cancelled sequence aborts the drag, nothing is dropped
+        self.cancel_drag();
+        handle.unset_grab();
+    }
+}
+
 struct OfferData {
     active: bool,
     dropped: bool,
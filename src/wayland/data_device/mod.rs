@@ -481,6 +481,40 @@ where
                                 icon.clone(),
                                 token.clone(),
                                 callback.clone(),
+                                None,
+                            ),
+                            serial,
+                        );
+                        return;
+                    }
+                }
+                if let Some(touch) = seat.get_touch() {
+                    // the drag follows the touch point whose down event started the implicit grab
+                    let slot = touch.slot_of_serial(serial);
+                    if touch.has_grab(serial) && slot.is_some() {
+                        if let Some(ref icon) = icon {
+                            if token.give_role::<DnDIconRole>(icon).is_err() {
+                                dd.as_ref().post_error(
+                                    wl_data_device::Error::Role as u32,
+                                    "Given surface already has an other role".into(),
+                                );
+                                return;
+                            }
+                        }
+                        // The StartDrag is in response to a touch implicit grab, all is good
+                        (&mut *callback.borrow_mut())(DataDeviceEvent::DnDStarted {
+                            source: source.clone(),
+                            icon: icon.clone(),
+                        });
+                        touch.set_grab(
+                            dnd_grab::DnDGrab::new(
+                                source,
+                                origin,
+                                seat.clone(),
+                                icon.clone(),
+                                token.clone(),
+                                callback.clone(),
+                                slot,
                             ),
                             serial,
                         );
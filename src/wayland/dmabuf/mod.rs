@@ -0,0 +1,644 @@
+//! Linux DMABUF protocol
+//!
+//! This module provides helpers to handle the `zwp_linux_dmabuf_v1` protocol, which allows clients
+//! to submit their contents as dmabuf file descriptors. These handlers automate the aggregation of
+//! the metadata associated with a dma buffer, and do some basic checking of the sanity of what the
+//! client sends.
+//!
+//! ## How to use
+//!
+//! To setup the dmabuf global, you will need to provide 2 things:
+//!
+//! - a list of the dmabuf formats you wish to support
+//! - a closure to test if a dmabuf buffer can be imported by your renderer
+//!
+//! The list of supported format is just a `Vec<Format>`, where you will enter all the (format, modifier)
+//! couples you support.
+//!
+//! The closure is called for every new dmabuf buffer sent by a client. It should return `true` if the
+//! buffer can be used by your renderer, for example by trying to import it with
+//! [`EGLDisplay::import_dmabuf`](::backend::egl::EGLDisplay::import_dmabuf). Its results
+//! is forwarded to the client: if it returns `false`, the client is notified that the buffer
+//! could not be created.
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # extern crate smithay;
+//! use smithay::wayland::dmabuf::{init_dmabuf_global, Dmabuf, Format};
+//!
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! // define your supported formats
+//! let formats = vec![
+//!     // ARGB8888, with a linear layout
+//!     Format { format: 0x3432_5241, modifier: 0 },
+//!     /* ... */
+//! ];
+//! let dmabuf_global = init_dmabuf_global(
+//!     &mut display,
+//!     formats,
+//!     |dmabuf: &Dmabuf| {
+//!         // test the dmabuf and return if it can be used
+//!         true
+//!     },
+//!     None // we don't provide a logger in this example
+//! );
+//! # }
+//! ```
+//!
+//! Once a dmabuf buffer is attached to a surface by a client, you can retrieve its description
+//! with [`get_dmabuf`]. Buffers created by this module are also understood by
+//! [`EGLDisplay::egl_buffer_contents`](::backend::egl::EGLDisplay::egl_buffer_contents).
+
+use std::{cell::RefCell, os::unix::io::RawFd, rc::Rc};
+
+use nix::unistd;
+use wayland_protocols::unstable::linux_dmabuf::v1::server::{
+    zwp_linux_buffer_params_v1::{Error as ParamError, Request as ParamsRequest, ZwpLinuxBufferParamsV1},
+    zwp_linux_dmabuf_v1,
+};
+use wayland_server::{
+    protocol::wl_buffer::{self, WlBuffer},
+    Display, Global, NewResource,
+};
+
+pub use wayland_protocols::unstable::linux_dmabuf::v1::server::zwp_linux_buffer_params_v1::Flags as BufferFlags;
+
+/// The special `DRM_FORMAT_MOD_INVALID` modifier
+///
+/// It means that the modifier of the buffer is implicit, and negotiated by other means.
+pub const DRM_FORMAT_MOD_INVALID: u64 = 0x00ff_ffff_ffff_ffff;
+
+// a dmabuf buffer has at most 4 planes
+const MAX_PLANES: u32 = 4;
+
+// DRM fourcc codes of the formats we know the layout of, as defined in `drm_fourcc.h`
+mod fourcc {
+    const fn code(a: u8, b: u8, c: u8, d: u8) -> u32 {
+        (a as u32) | (b as u32) << 8 | (c as u32) << 16 | (d as u32) << 24
+    }
+
+    pub const RGB565: u32 = code(b'R', b'G', b'1', b'6');
+    pub const BGR565: u32 = code(b'B', b'G', b'1', b'6');
+    pub const RGB888: u32 = code(b'R', b'G', b'2', b'4');
+    pub const BGR888: u32 = code(b'B', b'G', b'2', b'4');
+    pub const XRGB8888: u32 = code(b'X', b'R', b'2', b'4');
+    pub const XBGR8888: u32 = code(b'X', b'B', b'2', b'4');
+    pub const RGBX8888: u32 = code(b'R', b'X', b'2', b'4');
+    pub const BGRX8888: u32 = code(b'B', b'X', b'2', b'4');
+    pub const ARGB8888: u32 = code(b'A', b'R', b'2', b'4');
+    pub const ABGR8888: u32 = code(b'A', b'B', b'2', b'4');
+    pub const RGBA8888: u32 = code(b'R', b'A', b'2', b'4');
+    pub const BGRA8888: u32 = code(b'B', b'A', b'2', b'4');
+    pub const XRGB2101010: u32 = code(b'X', b'R', b'3', b'0');
+    pub const XBGR2101010: u32 = code(b'X', b'B', b'3', b'0');
+    pub const ARGB2101010: u32 = code(b'A', b'R', b'3', b'0');
+    pub const ABGR2101010: u32 = code(b'A', b'B', b'3', b'0');
+    pub const YUYV: u32 = code(b'Y', b'U', b'Y', b'V');
+    pub const UYVY: u32 = code(b'U', b'Y', b'V', b'Y');
+    pub const NV12: u32 = code(b'N', b'V', b'1', b'2');
+    pub const NV21: u32 = code(b'N', b'V', b'2', b'1');
+    pub const YUV420: u32 = code(b'Y', b'U', b'1', b'2');
+    pub const YVU420: u32 = code(b'Y', b'V', b'1', b'2');
+}
+
+// Amount of bytes used by a pixel in the first plane of given format, if it is known
+fn bytes_per_pixel(format: u32) -> Option<u32> {
+    use self::fourcc::*;
+    match format {
+        NV12 | NV21 | YUV420 | YVU420 => Some(1),
+        RGB565 | BGR565 | YUYV | UYVY => Some(2),
+        RGB888 | BGR888 => Some(3),
+        XRGB8888 | XBGR8888 | RGBX8888 | BGRX8888 | ARGB8888 | ABGR8888 | RGBA8888 | BGRA8888
+        | XRGB2101010 | XBGR2101010 | ARGB2101010 | ABGR2101010 => Some(4),
+        _ => None,
+    }
+}
+
+// Height of the given plane of a buffer of given format, if it is known
+//
+// The first plane always covers the whole height of the buffer, but the chroma planes
+// of YUV formats can be subsampled.
+fn plane_height(format: u32, plane_idx: u32, height: u32) -> Option<u32> {
+    use self::fourcc::*;
+    match (format, plane_idx) {
+        (_, 0) => Some(height),
+        (NV12, 1) | (NV21, 1) | (YUV420, 1) | (YUV420, 2) | (YVU420, 1) | (YVU420, 2) => {
+            Some(height / 2 + height % 2)
+        }
+        _ => None,
+    }
+}
+
+// Whether given format is a single-plane RGB format with an alpha channel
+//
+// `None` if this is not a known RGB format.
+#[cfg(all(feature = "backend_egl", feature = "native_lib"))]
+pub(crate) fn rgb_has_alpha(format: u32) -> Option<bool> {
+    use self::fourcc::*;
+    match format {
+        RGB565 | BGR565 | RGB888 | BGR888 | XRGB8888 | XBGR8888 | RGBX8888 | BGRX8888 | XRGB2101010
+        | XBGR2101010 => Some(false),
+        ARGB8888 | ABGR8888 | RGBA8888 | BGRA8888 | ARGB2101010 | ABGR2101010 => Some(true),
+        _ => None,
+    }
+}
+
+/// Representation of a Dmabuf format, as advertized to the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Format {
+    /// The format
+    ///
+    /// The possible values are defined in the `drm_fourcc.h` header of the linux kernel.
+    pub format: u32,
+    /// The supported dmabuf layout modifier
+    ///
+    /// This is an opaque token. Drivers use this token to express tiling, compression, etc. driver-specific
+    /// modifications to the base format defined by the DRM fourcc code.
+    pub modifier: u64,
+}
+
+/// A plane send by the client
+#[derive(Debug)]
+pub struct Plane {
+    /// The file descriptor
+    pub fd: RawFd,
+    /// The plane index
+    pub plane_idx: u32,
+    /// Offset from the start of the Fd
+    pub offset: u32,
+    /// Stride for this plane
+    pub stride: u32,
+    /// Modifier for this plane
+    pub modifier: u64,
+}
+
+/// A complete dmabuf buffer, as sent by a client
+///
+/// The file descriptors of the planes are owned by this struct, and closed when it
+/// is dropped, which happens when the associated `wl_buffer` is destroyed.
+#[derive(Debug)]
+pub struct Dmabuf {
+    /// The width of this buffer
+    pub width: i32,
+    /// The height of this buffer
+    pub height: i32,
+    /// The format in use
+    pub format: u32,
+    /// The flags applied to it
+    ///
+    /// This is a bitfield of [`BufferFlags`], test them with `flags & BufferFlags::YInvert as u32 != 0`.
+    pub flags: u32,
+    /// The list of planes used by this buffer, ordered by plane index
+    pub planes: Vec<Plane>,
+}
+
+impl Drop for Dmabuf {
+    fn drop(&mut self) {
+        for plane in self.planes.drain(..) {
+            let _ = unistd::close(plane.fd);
+        }
+    }
+}
+
+/// Retrieve the dmabuf description of a buffer
+///
+/// Returns `None` if this buffer was not created by the dmabuf global.
+pub fn get_dmabuf(buffer: &WlBuffer) -> Option<&Dmabuf> {
+    buffer.as_ref().user_data::<Dmabuf>()
+}
+
+/// Initialize a dmabuf global.
+///
+/// You need to provide a vector of the supported formats, as well as a closure,
+/// that will validate the parameters provided by the client and tests the import as a dmabuf.
+///
+/// The global is directly created on the provided [`Display`](wayland_server::Display),
+/// and this function returns the global handle, in case you wish to remove this global in
+/// the future.
+pub fn init_dmabuf_global<F, L>(
+    display: &mut Display,
+    formats: Vec<Format>,
+    validate: F,
+    logger: L,
+) -> Global<zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1>
+where
+    F: FnMut(&Dmabuf) -> bool + 'static,
+    L: Into<Option<::slog::Logger>>,
+{
+    let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "dmabuf_handler"));
+
+    let formats = Rc::new(formats);
+    let validate = Rc::new(RefCell::new(validate));
+
+    trace!(
+        log,
+        "Initializing DMABUF handler with {} supported formats",
+        formats.len()
+    );
+
+    display.create_global(3, move |new_dmabuf, version| {
+        let dma_formats = formats.clone();
+        let dma_validate = validate.clone();
+        let dma_log = log.clone();
+        let dmabuf: zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1 = new_dmabuf.implement_closure(
+            move |req, _| {
+                if let zwp_linux_dmabuf_v1::Request::CreateParams { params_id } = req {
+                    let mut handler = ParamsHandler {
+                        pending_planes: Vec::new(),
+                        used: false,
+                        formats: dma_formats.clone(),
+                        validate: dma_validate.clone(),
+                        log: dma_log.clone(),
+                    };
+                    params_id.implement_closure(
+                        move |req, params| match req {
+                            ParamsRequest::Add {
+                                fd,
+                                plane_idx,
+                                offset,
+                                stride,
+                                modifier_hi,
+                                modifier_lo,
+                            } => {
+                                handler.add(&params, fd, plane_idx, offset, stride, modifier_hi, modifier_lo)
+                            }
+                            ParamsRequest::Create {
+                                width,
+                                height,
+                                format,
+                                flags,
+                            } => handler.create(&params, width, height, format, flags),
+                            ParamsRequest::CreateImmed {
+                                buffer_id,
+                                width,
+                                height,
+                                format,
+                                flags,
+                            } => handler.create_immed(&params, buffer_id, width, height, format, flags),
+                            ParamsRequest::Destroy => {}
+                            _ => unreachable!(),
+                        },
+                        None::<fn(_)>,
+                        (),
+                    );
+                }
+            },
+            None::<fn(_)>,
+            (),
+        );
+
+        // send the supported formats
+        if version >= 3 {
+            for f in &*formats {
+                dmabuf.modifier(f.format, (f.modifier >> 32) as u32, f.modifier as u32);
+            }
+        } else {
+            let mut sent: Vec<u32> = Vec::new();
+            for f in &*formats {
+                if !sent.contains(&f.format) {
+                    dmabuf.format(f.format);
+                    sent.push(f.format);
+                }
+            }
+        }
+    })
+}
+
+struct ParamsHandler<F: FnMut(&Dmabuf) -> bool> {
+    pending_planes: Vec<Plane>,
+    used: bool,
+    formats: Rc<Vec<Format>>,
+    validate: Rc<RefCell<F>>,
+    log: ::slog::Logger,
+}
+
+impl<F: FnMut(&Dmabuf) -> bool> ParamsHandler<F> {
+    #[allow(clippy::too_many_arguments)]
+    fn add(
+        &mut self,
+        params: &ZwpLinuxBufferParamsV1,
+        fd: RawFd,
+        plane_idx: u32,
+        offset: u32,
+        stride: u32,
+        modifier_hi: u32,
+        modifier_lo: u32,
+    ) {
+        // protocol checks:
+        // Cannot reuse a params:
+        if self.used {
+            params.as_ref().post_error(
+                ParamError::AlreadyUsed as u32,
+                "This buffer_params has already been used to create a buffer.".into(),
+            );
+            let _ = unistd::close(fd);
+            return;
+        }
+        // plane_idx is not too large
+        if plane_idx >= MAX_PLANES {
+            // plane_idx starts at 0
+            params.as_ref().post_error(
+                ParamError::PlaneIdx as u32,
+                format!("Plane index {} is out of bounds.", plane_idx),
+            );
+            let _ = unistd::close(fd);
+            return;
+        }
+        // plane_idx has already been set
+        if self.pending_planes.iter().any(|d| d.plane_idx == plane_idx) {
+            params.as_ref().post_error(
+                ParamError::PlaneSet as u32,
+                format!("Plane index {} is already set.", plane_idx),
+            );
+            let _ = unistd::close(fd);
+            return;
+        }
+        // all checks passed, store the plane
+        self.pending_planes.push(Plane {
+            fd,
+            plane_idx,
+            offset,
+            stride,
+            modifier: ((modifier_hi as u64) << 32) + (modifier_lo as u64),
+        });
+    }
+
+    fn create(&mut self, params: &ZwpLinuxBufferParamsV1, width: i32, height: i32, format: u32, flags: u32) {
+        // Cannot reuse a params:
+        if self.used {
+            params.as_ref().post_error(
+                ParamError::AlreadyUsed as u32,
+                "This buffer_params has already been used to create a buffer.".into(),
+            );
+            return;
+        }
+        self.used = true;
+        let dmabuf = match self.build_dmabuf(params, width, height, format, flags) {
+            Some(dmabuf) => dmabuf,
+            None => return,
+        };
+        if !(&mut *self.validate.borrow_mut())(&dmabuf) {
+            trace!(self.log, "Dmabuf rejected by the compositor");
+            params.failed();
+            return;
+        }
+        let client = match params.as_ref().client() {
+            Some(client) => client,
+            None => return,
+        };
+        let buffer = match client.create_resource::<WlBuffer>(1) {
+            Some(new_buffer) => implement_buffer(new_buffer, dmabuf),
+            None => return,
+        };
+        trace!(self.log, "Creating a new validated dma wl_buffer.");
+        params.created(&buffer);
+    }
+
+    fn create_immed(
+        &mut self,
+        params: &ZwpLinuxBufferParamsV1,
+        buffer_id: NewResource<WlBuffer>,
+        width: i32,
+        height: i32,
+        format: u32,
+        flags: u32,
+    ) {
+        // Cannot reuse a params:
+        if self.used {
+            params.as_ref().post_error(
+                ParamError::AlreadyUsed as u32,
+                "This buffer_params has already been used to create a buffer.".into(),
+            );
+            return;
+        }
+        self.used = true;
+        let dmabuf = match self.build_dmabuf(params, width, height, format, flags) {
+            Some(dmabuf) => dmabuf,
+            None => return,
+        };
+        if !(&mut *self.validate.borrow_mut())(&dmabuf) {
+            trace!(self.log, "Dmabuf rejected by the compositor");
+            params.as_ref().post_error(
+                ParamError::InvalidWlBuffer as u32,
+                "Failed to import the dmabuf.".into(),
+            );
+            return;
+        }
+        trace!(self.log, "Creating a new validated immediate dma wl_buffer.");
+        implement_buffer(buffer_id, dmabuf);
+    }
+
+    // checks the parameters of a creation request, posts a protocol error and returns
+    // `None` if they are invalid
+    fn build_dmabuf(
+        &mut self,
+        params: &ZwpLinuxBufferParamsV1,
+        width: i32,
+        height: i32,
+        format: u32,
+        flags: u32,
+    ) -> Option<Dmabuf> {
+        let mut planes = ::std::mem::replace(&mut self.pending_planes, Vec::new());
+        planes.sort_by_key(|plane| plane.plane_idx);
+        let dmabuf = Dmabuf {
+            width,
+            height,
+            format,
+            flags,
+            planes,
+        };
+
+        // the format must be supported
+        let format = match self
+            .formats
+            .iter()
+            .find(|f| f.format == format && dmabuf.planes.iter().all(|p| p.modifier == f.modifier))
+        {
+            Some(format) => *format,
+            None => {
+                params.as_ref().post_error(
+                    ParamError::InvalidFormat as u32,
+                    format!("Format {:x} and modifier are not supported", format),
+                );
+                return None;
+            }
+        };
+
+        // the planes must be set without gaps, starting from the first one
+        if dmabuf.planes.is_empty()
+            || dmabuf
+                .planes
+                .iter()
+                .enumerate()
+                .any(|(i, p)| p.plane_idx != i as u32)
+        {
+            params.as_ref().post_error(
+                ParamError::Incomplete as u32,
+                format!("Missing planes for format {:x}.", format.format),
+            );
+            return None;
+        }
+
+        if width < 1 || height < 1 {
+            params.as_ref().post_error(
+                ParamError::InvalidDimensions as u32,
+                format!("Invalid dimensions ({}, {}).", width, height),
+            );
+            return None;
+        }
+
+        if let Err((error, message)) = check_planes(&dmabuf) {
+            params.as_ref().post_error(error as u32, message);
+            return None;
+        }
+
+        Some(dmabuf)
+    }
+}
+
+// checks that the planes of a buffer with valid dimensions fit in their files, returns
+// the protocol error to post otherwise
+fn check_planes(dmabuf: &Dmabuf) -> Result<(), (ParamError, String)> {
+    for plane in &dmabuf.planes {
+        if let Some(bpp) = bytes_per_pixel(dmabuf.format) {
+            let row_size = (dmabuf.width as u64) * (bpp as u64);
+            if plane.plane_idx == 0 && (plane.stride as u64) < row_size {
+                return Err((
+                    ParamError::OutOfBounds,
+                    format!("Stride of plane 0 is smaller than a row ({} bytes).", row_size),
+                ));
+            }
+        }
+        // the extent of the planes of unknown layout cannot be checked
+        let height = match plane_height(dmabuf.format, plane.plane_idx, dmabuf.height as u32) {
+            Some(height) => height,
+            None => continue,
+        };
+        // check that the plane does not overflow
+        let end = (plane.offset as u64)
+            .checked_add((plane.stride as u64) * (height as u64))
+            .filter(|&end| end <= ::std::u32::MAX as u64);
+        let end = match end {
+            Some(end) => end,
+            None => {
+                return Err((
+                    ParamError::OutOfBounds,
+                    format!("Size overflow for plane {}.", plane.plane_idx),
+                ))
+            }
+        };
+        // check that the plane fits into the file, if its size can be determined
+        if let Ok(size) = unistd::lseek(plane.fd, 0, unistd::Whence::SeekEnd) {
+            if size >= 0 && end > size as u64 {
+                return Err((
+                    ParamError::OutOfBounds,
+                    format!("Plane {} is out of the bounds of its file.", plane.plane_idx),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<F: FnMut(&Dmabuf) -> bool> Drop for ParamsHandler<F> {
+    fn drop(&mut self) {
+        // close the file descriptors that were never used in a buffer
+        for plane in self.pending_planes.drain(..) {
+            let _ = unistd::close(plane.fd);
+        }
+    }
+}
+
+fn implement_buffer(buffer: NewResource<WlBuffer>, dmabuf: Dmabuf) -> WlBuffer {
+    buffer.implement_closure(
+        |req, _| match req {
+            wl_buffer::Request::Destroy => {}
+            _ => unreachable!(),
+        },
+        None::<fn(_)>,
+        dmabuf,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::io::AsRawFd;
+
+    // A buffer whose planes all live in one file of given size
+    fn buffer(file_size: u64, format: u32, height: i32, planes: &[(u32, u32)]) -> (::std::fs::File, Dmabuf) {
+        let file = ::tempfile::tempfile().unwrap();
+        file.set_len(file_size).unwrap();
+        let planes = planes
+            .iter()
+            .enumerate()
+            .map(|(plane_idx, &(offset, stride))| Plane {
+                // the planes do not own the fd, it is closed with the file
+                fd: file.as_raw_fd(),
+                plane_idx: plane_idx as u32,
+                offset,
+                stride,
+                modifier: 0,
+            })
+            .collect();
+        let dmabuf = Dmabuf {
+            width: 64,
+            height,
+            format,
+            flags: 0,
+            planes,
+        };
+        (file, dmabuf)
+    }
+
+    fn check(file_size: u64, format: u32, height: i32, planes: &[(u32, u32)]) -> Result<(), ParamError> {
+        let (_file, mut dmabuf) = buffer(file_size, format, height, planes);
+        let result = check_planes(&dmabuf).map_err(|(error, _)| error);
+        // do not let the drop of the dmabuf close the fd of the file
+        dmabuf.planes.clear();
+        result
+    }
+
+    #[test]
+    fn two_planes_in_one_fd() {
+        // NV12: a 64x48 luma plane followed by a 64x24 interleaved chroma plane
+        let size = 64 * 48 + 64 * 24;
+        assert!(check(size, fourcc::NV12, 48, &[(0, 64), (64 * 48, 64)]).is_ok());
+        // odd heights round the chroma plane up
+        let size = 64 * 47 + 64 * 24;
+        assert!(check(size, fourcc::NV12, 47, &[(0, 64), (64 * 47, 64)]).is_ok());
+        assert_eq!(
+            check(size - 1, fourcc::NV12, 47, &[(0, 64), (64 * 47, 64)]),
+            Err(ParamError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn three_planes_in_one_fd() {
+        // YUV420: a 64x48 luma plane followed by two 32x24 chroma planes
+        let size = 64 * 48 + 2 * 32 * 24;
+        let planes = [(0, 64), (64 * 48, 32), (64 * 48 + 32 * 24, 32)];
+        assert!(check(size, fourcc::YUV420, 48, &planes).is_ok());
+        assert_eq!(
+            check(size - 1, fourcc::YUV420, 48, &planes),
+            Err(ParamError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn single_plane_bounds() {
+        assert!(check(256 * 48, fourcc::ARGB8888, 48, &[(0, 256)]).is_ok());
+        // the plane does not fit in the file
+        assert_eq!(
+            check(256 * 48, fourcc::ARGB8888, 48, &[(16, 256)]),
+            Err(ParamError::OutOfBounds)
+        );
+        // the stride is smaller than a row of pixels
+        assert_eq!(
+            check(256 * 48, fourcc::ARGB8888, 48, &[(0, 128)]),
+            Err(ParamError::OutOfBounds)
+        );
+    }
+}
@@ -0,0 +1,493 @@
+//! Utilities for tracking the idle state of the user
+//!
+//! This module provides an [`IdleTracker`], which keeps track of the last user activity
+//! and notifies you when the user has been idle for some time, as well as when they
+//! resume their activity. This is typically what you need to blank the screens or lock
+//! the session after some time of inactivity.
+//!
+//! It also provides an implementation of the `zwp_idle_inhibit_manager_v1` protocol,
+//! allowing clients like video players to prevent the session from going idle while
+//! one of their surfaces is visible.
+//!
+//! ## How to use it
+//!
+//! Create the tracker with [`IdleTracker::new`], inserting its timer in your event loop,
+//! and register the timeouts you are interested in with
+//! [`IdleTracker::add_idle_timeout`]. You then need to call
+//! [`IdleTracker::notify_activity`] from your input handling, every time the user
+//! interacts with the compositor.
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # extern crate smithay;
+//! use std::time::Duration;
+//! use smithay::wayland::idle::{IdleEvent, IdleTracker};
+//!
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! let tracker = IdleTracker::new(
+//!     &event_loop.handle(),
+//!     None // put a logger here
+//! ).expect("Failed to insert the idle timer");
+//!
+//! tracker.add_idle_timeout(Duration::from_secs(300), |event| match event {
+//!     IdleEvent::Idle => { /* turn the screens off */ },
+//!     IdleEvent::Resumed => { /* turn the screens back on */ },
+//! });
+//!
+//! // in your input handling:
+//! tracker.notify_activity();
+//! # }
+//! ```
+//!
+//! To support idle inhibitors, initialize the global with
+//! [`init_idle_inhibit_manager_global`], providing a closure telling whether a surface is
+//! currently visible. An inhibitor only prevents the session from going idle while its
+//! surface is visible.
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # extern crate smithay;
+//! # use smithay::wayland::idle::IdleTracker;
+//! use smithay::wayland::idle::init_idle_inhibit_manager_global;
+//!
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! # let tracker = IdleTracker::new(&event_loop.handle(), None).unwrap();
+//! let idle_inhibit_global = init_idle_inhibit_manager_global(
+//!     &mut display,
+//!     &tracker,
+//!     |_surface| { /* check if this surface is mapped on an output */ true },
+//!     None // put a logger here
+//! );
+//! # }
+//! ```
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+use calloop::{
+    timer::{Timeout, Timer, TimerHandle},
+    InsertError, LoopHandle,
+};
+use wayland_protocols::unstable::idle_inhibit::v1::server::{
+    zwp_idle_inhibit_manager_v1::{self, ZwpIdleInhibitManagerV1},
+    zwp_idle_inhibitor_v1::{self, ZwpIdleInhibitorV1},
+};
+use wayland_server::{protocol::wl_surface::WlSurface, Display, Global};
+
+/// An event generated by the [`IdleTracker`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IdleEvent {
+    /// The user has been idle for the duration of the timeout
+    Idle,
+    /// The user resumed their activity after the timeout elapsed
+    Resumed,
+}
+
+/// An identifier for a timeout registered on an [`IdleTracker`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct IdleTimeoutId(usize);
+
+type IdleCallback = Rc<RefCell<dyn FnMut(IdleEvent)>>;
+
+struct IdleTimeout {
+    id: usize,
+    duration: Duration,
+    idle: bool,
+    timeout: Option<Timeout>,
+    callback: IdleCallback,
+}
+
+struct Inner {
+    timer: TimerHandle<usize>,
+    timeouts: Vec<IdleTimeout>,
+    inhibitors: Vec<(ZwpIdleInhibitorV1, WlSurface)>,
+    is_visible: Option<Box<dyn FnMut(&WlSurface) -> bool>>,
+    next_id: usize,
+    log: ::slog::Logger,
+}
+
+impl Inner {
+    fn is_inhibited(&mut self) -> bool {
+        self.inhibitors.retain(|(_, surface)| surface.as_ref().is_alive());
+        let Inner {
+            ref inhibitors,
+            ref mut is_visible,
+            ..
+        } = *self;
+        match is_visible {
+            Some(is_visible) => inhibitors.iter().any(|(_, surface)| is_visible(surface)),
+            None => !inhibitors.is_empty(),
+        }
+    }
+
+    // restart the timeouts that elapsed while the session was inhibited
+    fn rearm_elapsed(&mut self) {
+        let Inner {
+            ref timer,
+            ref mut timeouts,
+            ..
+        } = *self;
+        for timeout in timeouts.iter_mut().filter(|t| t.timeout.is_none() && !t.idle) {
+            timeout.timeout = Some(timer.add_timeout(timeout.duration, timeout.id));
+        }
+    }
+}
+
+/// A tracker of the idle state of the user
+///
+/// This handle can be cloned, all clones refer to the same tracker.
+#[derive(Clone)]
+pub struct IdleTracker {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl IdleTracker {
+    /// Create a new idle tracker
+    ///
+    /// Its timer is inserted in the event loop associated with the provided
+    /// [`LoopHandle`], the callbacks of the timeouts are invoked from there.
+    pub fn new<Data, L>(
+        handle: &LoopHandle<Data>,
+        logger: L,
+    ) -> Result<IdleTracker, InsertError<Timer<usize>>>
+    where
+        Data: 'static,
+        L: Into<Option<::slog::Logger>>,
+    {
+        let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "idle_tracker"));
+        let timer = Timer::new();
+        let inner = Rc::new(RefCell::new(Inner {
+            timer: timer.handle(),
+            timeouts: Vec::new(),
+            inhibitors: Vec::new(),
+            is_visible: None,
+            next_id: 0,
+            log,
+        }));
+        let weak_inner = Rc::downgrade(&inner);
+        handle.insert_source(timer, move |(id, _), _| {
+            if let Some(inner) = weak_inner.upgrade() {
+                IdleTracker { inner }.timeout_expired(id);
+            }
+        })?;
+        Ok(IdleTracker { inner })
+    }
+
+    /// Register a new idle timeout
+    ///
+    /// The callback will receive [`IdleEvent::Idle`] once the user has been inactive for
+    /// `duration`, and [`IdleEvent::Resumed`] at their next activity.
+    pub fn add_idle_timeout<F>(&self, duration: Duration, callback: F) -> IdleTimeoutId
+    where
+        F: FnMut(IdleEvent) + 'static,
+    {
+        let mut inner = self.inner.borrow_mut();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        let timeout = inner.timer.add_timeout(duration, id);
+        inner.timeouts.push(IdleTimeout {
+            id,
+            duration,
+            idle: false,
+            timeout: Some(timeout),
+            callback: Rc::new(RefCell::new(callback)),
+        });
+        IdleTimeoutId(id)
+    }
+
+    /// Unregister an idle timeout
+    ///
+    /// Its callback will not be invoked anymore.
+    pub fn remove_idle_timeout(&self, id: IdleTimeoutId) {
+        let mut inner = self.inner.borrow_mut();
+        if let Some(idx) = inner.timeouts.iter().position(|t| t.id == id.0) {
+            let timeout = inner.timeouts.remove(idx);
+            if let Some(timeout) = timeout.timeout {
+                inner.timer.cancel_timeout(&timeout);
+            }
+        }
+    }
+
+    /// Notify the tracker of some user activity
+    ///
+    /// This resets all the timeouts, and sends [`IdleEvent::Resumed`] to the ones
+    /// that had already elapsed. You should call it for every input event.
+    pub fn notify_activity(&self) {
+        let mut resumed = Vec::new();
+        {
+            let mut inner = self.inner.borrow_mut();
+            let Inner {
+                ref timer,
+                ref mut timeouts,
+                ..
+            } = *inner;
+            for timeout in timeouts.iter_mut() {
+                if let Some(old) = timeout.timeout.take() {
+                    timer.cancel_timeout(&old);
+                }
+                timeout.timeout = Some(timer.add_timeout(timeout.duration, timeout.id));
+                if timeout.idle {
+                    timeout.idle = false;
+                    resumed.push(timeout.callback.clone());
+                }
+            }
+            if !resumed.is_empty() {
+                trace!(inner.log, "User activity resumed");
+            }
+        }
+        // invoke the callbacks without holding the borrow, so that they can access the tracker
+        for callback in resumed {
+            (&mut *callback.borrow_mut())(IdleEvent::Resumed);
+        }
+    }
+
+    /// Check whether the session is currently prevented from going idle
+    ///
+    /// This is the case if a client created an inhibitor on a visible surface.
+    pub fn is_inhibited(&self) -> bool {
+        self.inner.borrow_mut().is_inhibited()
+    }
+
+    fn timeout_expired(&self, id: usize) {
+        let callback = {
+            let mut inner = self.inner.borrow_mut();
+            let inhibited = inner.is_inhibited();
+            let Inner {
+                ref mut timeouts,
+                ref log,
+                ..
+            } = *inner;
+            let timeout = match timeouts.iter_mut().find(|t| t.id == id) {
+                Some(timeout) => timeout,
+                // the timeout was removed in the meantime
+                None => return,
+            };
+            timeout.timeout = None;
+            if inhibited {
+                // it is restarted once the last inhibitor goes away
+                trace!(log, "Idle timeout elapsed while inhibited"; "timeout" => ?timeout.duration);
+                return;
+            }
+            trace!(log, "User became idle"; "timeout" => ?timeout.duration);
+            timeout.idle = true;
+            timeout.callback.clone()
+        };
+        (&mut *callback.borrow_mut())(IdleEvent::Idle);
+    }
+}
+
+/// Create a new `zwp_idle_inhibit_manager_v1` global
+///
+/// The inhibitors created by the clients will prevent the provided [`IdleTracker`] from
+/// going idle, as long as `is_visible` returns `true` for their surface. The timeouts
+/// elapsing while the session is inhibited are restarted once the last inhibitor is
+/// destroyed, so the session goes idle if the user stays inactive for their full
+/// duration again.
+///
+/// The global is directly created on the provided [`Display`](wayland_server::Display),
+/// and this function returns the global handle, in case you wish to remove this global in
+/// the future.
+pub fn init_idle_inhibit_manager_global<F, L>(
+    display: &mut Display,
+    tracker: &IdleTracker,
+    is_visible: F,
+    logger: L,
+) -> Global<ZwpIdleInhibitManagerV1>
+where
+    F: FnMut(&WlSurface) -> bool + 'static,
+    L: Into<Option<::slog::Logger>>,
+{
+    let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "idle_inhibit_handler"));
+    tracker.inner.borrow_mut().is_visible = Some(Box::new(is_visible));
+    let weak_inner = Rc::downgrade(&tracker.inner);
+
+    display.create_global::<ZwpIdleInhibitManagerV1, _>(1, move |new_manager, _version| {
+        let log = log.clone();
+        let weak_inner = weak_inner.clone();
+        new_manager.implement_closure(
+            move |request, _manager| match request {
+                zwp_idle_inhibit_manager_v1::Request::CreateInhibitor { id, surface } => {
+                    let destructor_inner = weak_inner.clone();
+                    let inhibitor = id.implement_closure(
+                        |request, _inhibitor| match request {
+                            zwp_idle_inhibitor_v1::Request::Destroy => {
+                                // our destructor already handles it
+                            }
+                            _ => unreachable!(),
+                        },
+                        Some(move |_inhibitor: ZwpIdleInhibitorV1| remove_inhibitor(&destructor_inner)),
+                        (),
+                    );
+                    if let Some(inner) = weak_inner.upgrade() {
+                        trace!(log, "New idle inhibitor");
+                        inner.borrow_mut().inhibitors.push((inhibitor, surface));
+                    }
+                }
+                zwp_idle_inhibit_manager_v1::Request::Destroy => {
+                    // nothing to do
+                }
+                _ => unreachable!(),
+            },
+            None::<fn(_)>,
+            (),
+        );
+    })
+}
+
+fn remove_inhibitor(inner: &Weak<RefCell<Inner>>) {
+    if let Some(inner) = inner.upgrade() {
+        let mut inner = inner.borrow_mut();
+        // the destroyed inhibitor is already dead, as well as any leftover one
+        inner
+            .inhibitors
+            .retain(|(inhibitor, _)| inhibitor.as_ref().is_alive());
+        if !inner.is_inhibited() {
+            trace!(inner.log, "Session no longer inhibited");
+            inner.rearm_elapsed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{define_roles, wayland::compositor::compositor_init};
+    use std::{
+        os::unix::{io::IntoRawFd, net::UnixStream},
+        sync::mpsc,
+        thread,
+        time::Instant,
+    };
+    use wayland_client::{protocol::wl_compositor::WlCompositor, GlobalManager};
+    use wayland_protocols::unstable::idle_inhibit::v1::client::zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1 as ClientManager;
+    use wayland_server::calloop::EventLoop;
+
+    const TIMEOUT: Duration = Duration::from_millis(300);
+    // the timer of the event loop has a resolution of 100ms
+    const MARGIN: Duration = Duration::from_millis(200);
+
+    define_roles!(Roles);
+
+    fn record(tracker: &IdleTracker) -> Rc<RefCell<Vec<IdleEvent>>> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = events.clone();
+        tracker.add_idle_timeout(TIMEOUT, move |event| recorded.borrow_mut().push(event));
+        events
+    }
+
+    // dispatch the event loop for the given duration
+    fn dispatch_for(event_loop: &mut EventLoop<()>, display: Option<&Display>, duration: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            event_loop
+                .dispatch(Some(Duration::from_millis(5)), &mut ())
+                .unwrap();
+            if let Some(display) = display {
+                display.flush_clients();
+            }
+        }
+    }
+
+    #[test]
+    fn idle_and_resumed() {
+        let mut event_loop = EventLoop::<()>::new().unwrap();
+        let tracker = IdleTracker::new(&event_loop.handle(), None).unwrap();
+        let events = record(&tracker);
+
+        dispatch_for(&mut event_loop, None, TIMEOUT / 2);
+        assert!(events.borrow().is_empty());
+        tracker.notify_activity();
+        dispatch_for(&mut event_loop, None, TIMEOUT / 2);
+        // the activity restarted the timeout
+        assert!(events.borrow().is_empty());
+        dispatch_for(&mut event_loop, None, TIMEOUT + MARGIN);
+        assert_eq!(*events.borrow(), vec![IdleEvent::Idle]);
+
+        tracker.notify_activity();
+        assert_eq!(*events.borrow(), vec![IdleEvent::Idle, IdleEvent::Resumed]);
+        dispatch_for(&mut event_loop, None, TIMEOUT * 2);
+        assert_eq!(
+            *events.borrow(),
+            vec![IdleEvent::Idle, IdleEvent::Resumed, IdleEvent::Idle]
+        );
+    }
+
+    #[test]
+    fn removed_timeout_is_not_invoked() {
+        let mut event_loop = EventLoop::<()>::new().unwrap();
+        let tracker = IdleTracker::new(&event_loop.handle(), None).unwrap();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = events.clone();
+        let id = tracker.add_idle_timeout(TIMEOUT, move |event| recorded.borrow_mut().push(event));
+        tracker.remove_idle_timeout(id);
+        dispatch_for(&mut event_loop, None, TIMEOUT * 2);
+        assert!(events.borrow().is_empty());
+    }
+
+    #[test]
+    fn inhibitor_prevents_idle() {
+        let mut event_loop = EventLoop::<()>::new().unwrap();
+        let mut display = Display::new(event_loop.handle());
+        let tracker = IdleTracker::new(&event_loop.handle(), None).unwrap();
+        let _compositor = compositor_init::<(), Roles, _, _>(&mut display, |_, _, _| {}, None);
+        let _global = init_idle_inhibit_manager_global(&mut display, &tracker, |_| true, None);
+        let events = record(&tracker);
+
+        // the client creates an inhibitor, and destroys it when told to
+        let (server_end, client_end) = UnixStream::pair().unwrap();
+        let _client = unsafe { display.create_client(server_end.into_raw_fd()) };
+        let (inhibited_sender, inhibited) = mpsc::channel();
+        let (release, release_receiver) = mpsc::channel::<()>();
+        let client_thread = thread::spawn(move || {
+            let (client_display, mut queue) =
+                unsafe { wayland_client::Display::from_fd(client_end.into_raw_fd()) }.unwrap();
+            let globals = GlobalManager::new(&client_display);
+            queue.sync_roundtrip().unwrap();
+            let compositor = globals
+                .instantiate_exact::<WlCompositor, _>(1, |compositor| compositor.implement_dummy())
+                .unwrap();
+            let manager = globals
+                .instantiate_exact::<ClientManager, _>(1, |manager| manager.implement_dummy())
+                .unwrap();
+            let surface = compositor
+                .create_surface(|surface| surface.implement_dummy())
+                .unwrap();
+            let inhibitor = manager
+                .create_inhibitor(&surface, |inhibitor| inhibitor.implement_dummy())
+                .unwrap();
+            queue.sync_roundtrip().unwrap();
+            inhibited_sender.send(()).unwrap();
+            release_receiver.recv().unwrap();
+            inhibitor.destroy();
+            queue.sync_roundtrip().unwrap();
+        });
+
+        while inhibited.try_recv().is_err() {
+            dispatch_for(&mut event_loop, Some(&display), Duration::from_millis(5));
+        }
+        assert!(tracker.is_inhibited());
+        dispatch_for(&mut event_loop, Some(&display), TIMEOUT * 3);
+        // the timeout elapsed, but the session did not go idle and the timer is not re-armed
+        assert!(events.borrow().is_empty());
+        assert!(tracker.inner.borrow().timeouts[0].timeout.is_none());
+
+        release.send(()).unwrap();
+        while !tracker.inner.borrow().inhibitors.is_empty() {
+            dispatch_for(&mut event_loop, Some(&display), Duration::from_millis(5));
+        }
+        assert!(!tracker.is_inhibited());
+        // the timeout restarts for its full duration when the inhibitor goes away
+        dispatch_for(&mut event_loop, Some(&display), TIMEOUT / 2);
+        assert!(events.borrow().is_empty());
+        dispatch_for(&mut event_loop, Some(&display), TIMEOUT + MARGIN);
+        assert_eq!(*events.borrow(), vec![IdleEvent::Idle]);
+
+        client_thread.join().unwrap();
+    }
+}
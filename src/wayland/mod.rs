@@ -16,10 +16,18 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub mod compositor;
 pub mod data_device;
+pub mod dmabuf;
+pub mod idle;
 pub mod output;
+pub mod pointer_constraints;
+pub mod presentation;
+pub mod primary_selection;
+pub mod relative_pointer;
 pub mod seat;
 pub mod shell;
 pub mod shm;
+pub mod text_input;
+pub mod viewporter;
 
 /// A global [`SerialCounter`] for use in your compositor.
 ///
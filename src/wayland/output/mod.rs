@@ -41,7 +41,8 @@
 //! output.change_current_state(
 //!     Some(Mode { width: 1902, height: 1080, refresh: 60000 }), // the resolution mode,
 //!     Some(wl_output::Transform::Normal), // global screen transformation
-//!     Some(1), // global screen scaling factor
+//!     Some(1.0), // global screen scaling factor, which can be fractional
+//!     Some((0, 0)), // location of the output in the global compositor space
 //! );
 //! // set the preferred mode
 //! output.set_preferred(Mode { width: 1920, height: 1080, refresh: 60000 });
@@ -50,15 +51,28 @@
 //! output.add_mode(Mode { width: 1024, height: 768, refresh: 60000 });
 //! # }
 //! ```
+//!
+//! The logical position and size of the outputs can also be advertised to the clients using
+//! the [`xdg_output`](::wayland::output::xdg) protocol.
+//!
+//! If the scale of an output is fractional, clients are told to use the next integer
+//! scale through the `wl_output` protocol, and the logical size advertised through
+//! `xdg_output` accounts for the actual scale. The [`Output::to_physical`],
+//! [`Output::to_logical`] and [`Output::rect_to_physical`] methods allow you to convert
+//! coordinates between the logical and physical spaces of an output.
 
 use std::sync::{Arc, Mutex};
 
+use crate::utils::Rectangle;
+use wayland_protocols::unstable::xdg_output::v1::server::zxdg_output_v1::ZxdgOutputV1;
 use wayland_server::protocol::wl_output::{Subpixel, Transform};
 use wayland_server::{
     protocol::wl_output::{Mode as WMode, WlOutput},
-    Display, Global, NewResource,
+    Client, Display, Global, NewResource,
 };
 
+pub mod xdg;
+
 /// An output mode
 ///
 /// A possible combination of dimensions and refresh rate for an output.
@@ -95,10 +109,11 @@ struct Inner {
     name: String,
     log: ::slog::Logger,
     instances: Vec<WlOutput>,
+    xdg_instances: Vec<ZxdgOutputV1>,
     physical: PhysicalProperties,
     location: (i32, i32),
     transform: Transform,
-    scale: i32,
+    scale: f64,
     modes: Vec<Mode>,
     current_mode: Option<Mode>,
     preferred_mode: Option<Mode>,
@@ -130,7 +145,7 @@ impl Inner {
             output.mode(flags, mode.width, mode.height, mode.refresh);
         }
         if output.as_ref().version() >= 2 {
-            output.scale(self.scale);
+            output.scale(self.integer_scale());
             output.done();
         }
 
@@ -149,6 +164,30 @@ impl Inner {
             self.transform,
         );
     }
+
+    // the scale advertised through wl_output, which only supports integers
+    fn integer_scale(&self) -> i32 {
+        self.scale.ceil() as i32
+    }
+
+    // size of the output in the global compositor space
+    fn logical_size(&self) -> (i32, i32) {
+        let (width, height) = self
+            .current_mode
+            .map(|mode| {
+                (
+                    (f64::from(mode.width) / self.scale).round() as i32,
+                    (f64::from(mode.height) / self.scale).round() as i32,
+                )
+            })
+            .unwrap_or((0, 0));
+        match self.transform {
+            Transform::_90 | Transform::_270 | Transform::Flipped90 | Transform::Flipped270 => {
+                (height, width)
+            }
+            _ => (width, height),
+        }
+    }
 }
 
 /// An output as seen by the clients
@@ -182,10 +221,11 @@ impl Output {
             name,
             log,
             instances: Vec::new(),
+            xdg_instances: Vec::new(),
             physical,
             location: (0, 0),
             transform: Transform::Normal,
-            scale: 1,
+            scale: 1.0,
             modes: Vec::new(),
             current_mode: None,
             preferred_mode: None,
@@ -249,20 +289,32 @@ impl Output {
 
     /// Change the current state of this output
     ///
-    /// You can changed the current mode, transform status or scale of this output. Providing
-    /// `None` to any of these field means that the value does not change.
+    /// You can changed the current mode, transform status, scale or location of this output.
+    /// Providing `None` to any of these field means that the value does not change.
     ///
     /// If the provided mode was not previously known to this output, it is added to its
     /// internal list.
     ///
-    /// By default, transform status is `Normal`, and scale is `1`.
+    /// The scale can be fractional, in which case the clients are asked to render their
+    /// contents at the next integer scale. Scales that are not finite or not strictly
+    /// positive are ignored.
+    ///
+    /// By default, transform status is `Normal`, scale is `1.0` and location is `(0, 0)`.
     pub fn change_current_state(
         &self,
         new_mode: Option<Mode>,
         new_transform: Option<Transform>,
-        new_scale: Option<i32>,
+        new_scale: Option<f64>,
+        new_location: Option<(i32, i32)>,
     ) {
         let mut inner = self.inner.lock().unwrap();
+        let new_scale = match new_scale {
+            Some(scale) if !scale.is_finite() || scale <= 0.0 => {
+                warn!(inner.log, "Ignoring invalid output scale"; "scale" => scale);
+                None
+            }
+            scale => scale,
+        };
         if let Some(mode) = new_mode {
             if inner.modes.iter().find(|&m| *m == mode).is_none() {
                 inner.modes.push(mode);
@@ -275,6 +327,9 @@ impl Output {
         if let Some(scale) = new_scale {
             inner.scale = scale;
         }
+        if let Some(location) = new_location {
+            inner.location = location;
+        }
         let mut flags = WMode::Current;
         if inner.preferred_mode == new_mode {
             flags |= WMode::Preferred;
@@ -283,18 +338,108 @@ impl Output {
             if let Some(mode) = new_mode {
                 output.mode(flags, mode.width, mode.height, mode.refresh);
             }
-            if new_transform.is_some() {
+            if new_transform.is_some() || new_location.is_some() {
                 inner.send_geometry(output);
             }
-            if let Some(scale) = new_scale {
-                if output.as_ref().version() >= 2 {
-                    output.scale(scale);
-                }
+            if new_scale.is_some() && output.as_ref().version() >= 2 {
+                output.scale(inner.integer_scale());
             }
             if output.as_ref().version() >= 2 {
                 output.done();
             }
         }
+        for xdg_output in &inner.xdg_instances {
+            inner.send_xdg_state(xdg_output);
+        }
+    }
+
+    /// Access the current mode of this output, if any
+    pub fn current_mode(&self) -> Option<Mode> {
+        self.inner.lock().unwrap().current_mode
+    }
+
+    /// Access the current scale of this output
+    pub fn current_scale(&self) -> f64 {
+        self.inner.lock().unwrap().scale
+    }
+
+    /// Convert a point from logical to physical coordinates
+    ///
+    /// The coordinates are relative to the top-left corner of the output, and are
+    /// rounded to the nearest pixel.
+    pub fn to_physical(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let scale = self.current_scale();
+        (
+            (f64::from(x) * scale).round() as i32,
+            (f64::from(y) * scale).round() as i32,
+        )
+    }
+
+    /// Convert a point from physical to logical coordinates
+    ///
+    /// The coordinates are relative to the top-left corner of the output, and are
+    /// rounded to the nearest logical unit.
+    pub fn to_logical(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let scale = self.current_scale();
+        (
+            (f64::from(x) / scale).round() as i32,
+            (f64::from(y) / scale).round() as i32,
+        )
+    }
+
+    /// Convert a rectangle from logical to physical coordinates
+    ///
+    /// The edges of the rectangle are converted rather than its size, so that
+    /// rectangles adjacent in logical space remain adjacent in physical space:
+    ///
+    /// ```
+    /// # extern crate wayland_server;
+    /// # extern crate smithay;
+    /// # use smithay::utils::Rectangle;
+    /// # use smithay::wayland::output::{Output, PhysicalProperties};
+    /// # use wayland_server::protocol::wl_output;
+    /// # fn main() {
+    /// # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+    /// # let mut display = wayland_server::Display::new(event_loop.handle());
+    /// # let (output, _) = Output::new(&mut display, "output-0".into(), PhysicalProperties {
+    /// #     width: 0, height: 0, subpixel: wl_output::Subpixel::Unknown,
+    /// #     make: String::new(), model: String::new(),
+    /// # }, None);
+    /// output.change_current_state(None, None, Some(1.5), None);
+    /// let left = output.rect_to_physical(Rectangle { x: 0, y: 0, width: 3, height: 3 });
+    /// let right = output.rect_to_physical(Rectangle { x: 3, y: 0, width: 3, height: 3 });
+    /// assert_eq!(left.x + left.width, right.x);
+    /// assert_eq!(left.width + right.width, 9);
+    /// # }
+    /// ```
+    pub fn rect_to_physical(&self, rect: Rectangle) -> Rectangle {
+        let (x1, y1) = self.to_physical((rect.x, rect.y));
+        let (x2, y2) = self.to_physical((rect.x + rect.width, rect.y + rect.height));
+        Rectangle {
+            x: x1,
+            y: y1,
+            width: x2 - x1,
+            height: y2 - y1,
+        }
+    }
+
+    /// Call the closure with each [`wl_output`](WlOutput) instance of this [`Output`]
+    /// bound by the given client
+    pub fn with_client_outputs<F>(&self, client: &Client, mut f: F)
+    where
+        F: FnMut(&WlOutput),
+    {
+        let inner = self.inner.lock().unwrap();
+        for output in &inner.instances {
+            if output
+                .as_ref()
+                .client()
+                .map(|c| c.equals(client))
+                .unwrap_or(false)
+            {
+                f(output);
+            }
+        }
     }
 
     /// Check is given [`wl_output`](WlOutput) instance is managed by this [`Output`].
@@ -0,0 +1,105 @@
+//! Utilities for handling the `xdg_output` protocol
+//!
+//! This protocol extends the `wl_output` globals with the logical position and size of
+//! the outputs in the global compositor space, taking into account their scale and
+//! transform. It is notably used by Xwayland and screenshot tools.
+//!
+//! ## How to use it
+//!
+//! Initialize the global with [`init_xdg_output_manager`]. The state of the
+//! [`Output`](::wayland::output::Output)s is then automatically advertised through it,
+//! and updated whenever [`Output::change_current_state`](::wayland::output::Output::change_current_state)
+//! is called.
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # extern crate smithay;
+//! use smithay::wayland::output::xdg::init_xdg_output_manager;
+//!
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! let xdg_output_global = init_xdg_output_manager(
+//!     &mut display,
+//!     None // put a logger here
+//! );
+//! # }
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use wayland_protocols::unstable::xdg_output::v1::server::{
+    zxdg_output_manager_v1::{self, ZxdgOutputManagerV1},
+    zxdg_output_v1::{self, ZxdgOutputV1},
+};
+use wayland_server::{Display, Global};
+
+use super::Inner;
+
+impl Inner {
+    // the name and description never change, they are only sent once, before the first state
+    fn send_xdg_info(&self, xdg_output: &ZxdgOutputV1) {
+        if xdg_output.as_ref().version() >= 2 {
+            xdg_output.name(self.name.clone());
+            xdg_output.description(format!("{} - {}", self.physical.make, self.physical.model));
+        }
+    }
+
+    pub(super) fn send_xdg_state(&self, xdg_output: &ZxdgOutputV1) {
+        let (width, height) = self.logical_size();
+        xdg_output.logical_position(self.location.0, self.location.1);
+        xdg_output.logical_size(width, height);
+        xdg_output.done();
+    }
+}
+
+/// Create a new `zxdg_output_manager_v1` global
+///
+/// The global is directly created on the provided [`Display`](wayland_server::Display),
+/// and this function returns the global handle, in case you wish to remove this global in
+/// the future.
+pub fn init_xdg_output_manager<L>(display: &mut Display, logger: L) -> Global<ZxdgOutputManagerV1>
+where
+    L: Into<Option<::slog::Logger>>,
+{
+    let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "xdg_output_handler"));
+
+    display.create_global::<ZxdgOutputManagerV1, _>(2, move |new_manager, _version| {
+        let log = log.clone();
+        new_manager.implement_closure(
+            move |request, _manager| match request {
+                zxdg_output_manager_v1::Request::GetXdgOutput { id, output } => {
+                    let inner = output.as_ref().user_data::<Arc<Mutex<Inner>>>().unwrap().clone();
+                    let xdg_output = id.implement_closure(
+                        |request, _xdg_output| match request {
+                            zxdg_output_v1::Request::Destroy => {
+                                // our destructor already handles it
+                            }
+                            _ => unreachable!(),
+                        },
+                        Some(|xdg_output: ZxdgOutputV1| {
+                            let inner = xdg_output.as_ref().user_data::<Arc<Mutex<Inner>>>().unwrap();
+                            inner
+                                .lock()
+                                .unwrap()
+                                .xdg_instances
+                                .retain(|o| !o.as_ref().equals(&xdg_output.as_ref()));
+                        }),
+                        inner.clone(),
+                    );
+                    let mut guard = inner.lock().unwrap();
+                    trace!(log, "New xdg_output"; "name" => &guard.name);
+                    guard.send_xdg_info(&xdg_output);
+                    guard.send_xdg_state(&xdg_output);
+                    guard.xdg_instances.push(xdg_output);
+                }
+                zxdg_output_manager_v1::Request::Destroy => {
+                    // nothing to do
+                }
+                _ => unreachable!(),
+            },
+            None::<fn(_)>,
+            (),
+        );
+    })
+}
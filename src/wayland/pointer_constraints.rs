@@ -0,0 +1,573 @@
+//! Utilities for handling the `zwp_pointer_constraints_v1` protocol
+//!
+//! This protocol allows clients to request the pointer to be locked in place or confined
+//! to a region of one of their surfaces. Enforcing the constraint is the responsibility of
+//! the compositor: this module only tracks the constraints requested by the clients and
+//! handles the protocol exchanges when they are activated or deactivated.
+//!
+//! ## How to use it
+//!
+//! Initialize the global with [`init_pointer_constraints_global`]. Your implementation is
+//! called every time a client creates a new [`PointerConstraint`]. A constraint is initially
+//! inactive: it is up to you to [`activate`](PointerConstraint::activate) it, typically when
+//! the pointer enters the surface inside of the constraint region, and to
+//! [`deactivate`](PointerConstraint::deactivate) it when the surface loses the focus.
+//!
+//! While a lock is active, the pointer should not move: only
+//! [relative motion events](::wayland::relative_pointer) are sent to the client. While a
+//! confinement is active, the pointer must stay inside the [region](PointerConstraint::region)
+//! of the constraint.
+//!
+//! The region and cursor position hint of a constraint are double-buffered state of its
+//! surface, they are updated when the surface is committed.
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! # #[macro_use] extern crate smithay;
+//! use smithay::wayland::pointer_constraints::{init_pointer_constraints_global, PointerConstraint};
+//! # use smithay::wayland::compositor::compositor_init;
+//!
+//! # define_roles!(Roles);
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! # let (compositor_token, _, _) = compositor_init::<(), Roles, _, _>(&mut display, |_, _, _| {}, None);
+//! let (constraints_state, _global) = init_pointer_constraints_global(
+//!     &mut display,
+//!     compositor_token,
+//!     |constraint: PointerConstraint| {
+//!         // decide when to activate the new constraint
+//!     },
+//!     None // put a logger here
+//! );
+//! # }
+//! ```
+
+use std::{
+    cell::RefCell,
+    mem,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use wayland_protocols::unstable::pointer_constraints::v1::server::{
+    zwp_confined_pointer_v1::{self, ZwpConfinedPointerV1},
+    zwp_locked_pointer_v1::{self, ZwpLockedPointerV1},
+    zwp_pointer_constraints_v1::{self, Lifetime, ZwpPointerConstraintsV1},
+};
+use wayland_server::{
+    protocol::{wl_region::WlRegion, wl_surface::WlSurface},
+    Display, Global, NewResource,
+};
+
+use crate::wayland::{
+    compositor::{roles::RoleType, Cacheable, CompositorToken, RegionAttributes},
+    seat::PointerHandle,
+};
+
+/// Kind of a pointer constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintKind {
+    /// The pointer is locked in place
+    Lock,
+    /// The pointer is confined to a region
+    Confine,
+}
+
+/// Lifetime of a pointer constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintLifetime {
+    /// The constraint becomes defunct once deactivated
+    Oneshot,
+    /// The constraint can be activated again after being deactivated
+    Persistent,
+}
+
+struct ConstraintData {
+    surface: WlSurface,
+    pointer: PointerHandle,
+    lifetime: ConstraintLifetime,
+    region: Option<RegionAttributes>,
+    cursor_hint: Option<(f64, f64)>,
+    active: bool,
+    defunct: bool,
+}
+
+// Changes to the double-buffered state of a constraint
+#[derive(Default)]
+struct ConstraintChanges {
+    region: Option<Option<RegionAttributes>>,
+    cursor_hint: Option<(f64, f64)>,
+}
+
+// The double-buffered state of the constraints of a surface
+//
+// The changes are stored by pointer, as a surface can only have one constraint
+// per pointer. They are moved to the constraints once applied.
+#[derive(Default)]
+struct SurfaceConstraints {
+    changes: Vec<(PointerHandle, ConstraintChanges)>,
+    // whether the hook applying the state was registered on the surface
+    hooked: bool,
+}
+
+impl SurfaceConstraints {
+    fn changes_for(&mut self, pointer: &PointerHandle) -> &mut ConstraintChanges {
+        match self.changes.iter().position(|(other, _)| other == pointer) {
+            Some(idx) => &mut self.changes[idx].1,
+            None => {
+                self.changes.push((pointer.clone(), ConstraintChanges::default()));
+                &mut self.changes.last_mut().unwrap().1
+            }
+        }
+    }
+}
+
+impl Cacheable for SurfaceConstraints {
+    fn commit(&mut self) -> SurfaceConstraints {
+        SurfaceConstraints {
+            changes: mem::replace(&mut self.changes, Vec::new()),
+            hooked: false,
+        }
+    }
+
+    fn merge_into(self, into: &mut SurfaceConstraints) {
+        for (pointer, changes) in self.changes {
+            let into = into.changes_for(&pointer);
+            if changes.region.is_some() {
+                into.region = changes.region;
+            }
+            if changes.cursor_hint.is_some() {
+                into.cursor_hint = changes.cursor_hint;
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+enum ConstraintResource {
+    Lock(ZwpLockedPointerV1),
+    Confine(ZwpConfinedPointerV1),
+}
+
+/// A pointer constraint requested by a client
+///
+/// This is a handle to the constraint, it can be cloned.
+#[derive(Clone)]
+pub struct PointerConstraint {
+    resource: ConstraintResource,
+}
+
+impl PointerConstraint {
+    fn with_data<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&mut ConstraintData) -> T,
+    {
+        let data = match self.resource {
+            ConstraintResource::Lock(ref lock) => lock.as_ref().user_data::<RefCell<ConstraintData>>(),
+            ConstraintResource::Confine(ref confine) => {
+                confine.as_ref().user_data::<RefCell<ConstraintData>>()
+            }
+        };
+        f(&mut data.unwrap().borrow_mut())
+    }
+
+    fn resource_alive(&self) -> bool {
+        match self.resource {
+            ConstraintResource::Lock(ref lock) => lock.as_ref().is_alive(),
+            ConstraintResource::Confine(ref confine) => confine.as_ref().is_alive(),
+        }
+    }
+
+    /// Is the constraint still alive
+    ///
+    /// A constraint dies when the client destroys it or its surface, or when a oneshot
+    /// constraint is deactivated.
+    pub fn alive(&self) -> bool {
+        self.resource_alive() && self.with_data(|data| !data.defunct && data.surface.as_ref().is_alive())
+    }
+
+    /// Kind of this constraint
+    pub fn kind(&self) -> ConstraintKind {
+        match self.resource {
+            ConstraintResource::Lock(_) => ConstraintKind::Lock,
+            ConstraintResource::Confine(_) => ConstraintKind::Confine,
+        }
+    }
+
+    /// Lifetime of this constraint
+    pub fn lifetime(&self) -> ConstraintLifetime {
+        self.with_data(|data| data.lifetime)
+    }
+
+    /// The surface this constraint applies to
+    pub fn surface(&self) -> WlSurface {
+        self.with_data(|data| data.surface.clone())
+    }
+
+    /// The pointer this constraint applies to
+    pub fn pointer(&self) -> PointerHandle {
+        self.with_data(|data| data.pointer.clone())
+    }
+
+    /// The region of the constraint, in surface coordinates
+    ///
+    /// The effective region is the intersection of this region with the input region
+    /// of the surface. `None` means the input region of the surface is used as is.
+    pub fn region(&self) -> Option<RegionAttributes> {
+        self.with_data(|data| data.region.clone())
+    }
+
+    /// The position where the client expects the cursor to be shown when a lock ends,
+    /// in surface coordinates
+    ///
+    /// Always `None` for confinements.
+    pub fn cursor_position_hint(&self) -> Option<(f64, f64)> {
+        self.with_data(|data| data.cursor_hint)
+    }
+
+    /// Is the constraint currently active
+    pub fn is_active(&self) -> bool {
+        self.with_data(|data| data.active)
+    }
+
+    /// Activate the constraint
+    ///
+    /// Notifies the client that the pointer is now locked or confined. Does nothing if the
+    /// constraint is already active or is not alive.
+    pub fn activate(&self) {
+        if !self.alive() || self.is_active() {
+            return;
+        }
+        self.with_data(|data| data.active = true);
+        match self.resource {
+            ConstraintResource::Lock(ref lock) => lock.locked(),
+            ConstraintResource::Confine(ref confine) => confine.confined(),
+        }
+    }
+
+    /// Deactivate the constraint
+    ///
+    /// Notifies the client that the pointer is no longer locked or confined. A oneshot
+    /// constraint cannot be activated again afterwards. Does nothing if the constraint is
+    /// not active.
+    pub fn deactivate(&self) {
+        if !self.resource_alive() || !self.is_active() {
+            return;
+        }
+        self.with_data(|data| {
+            data.active = false;
+            if data.lifetime == ConstraintLifetime::Oneshot {
+                data.defunct = true;
+            }
+        });
+        match self.resource {
+            ConstraintResource::Lock(ref lock) => lock.unlocked(),
+            ConstraintResource::Confine(ref confine) => confine.unconfined(),
+        }
+    }
+}
+
+/// State of the pointer constraints global
+///
+/// It allows you to retrieve the constraints currently requested by the clients.
+pub struct PointerConstraintsState {
+    known_constraints: Vec<PointerConstraint>,
+}
+
+impl PointerConstraintsState {
+    /// Access all the constraints currently known
+    pub fn constraints(&self) -> &[PointerConstraint] {
+        &self.known_constraints
+    }
+
+    /// Retrieve the constraint of a surface for a given pointer, if any
+    pub fn constraint_for(&self, surface: &WlSurface, pointer: &PointerHandle) -> Option<PointerConstraint> {
+        self.known_constraints
+            .iter()
+            .find(|constraint| {
+                constraint.resource_alive()
+                    && constraint.with_data(|data| {
+                        data.surface.as_ref().equals(surface.as_ref()) && &data.pointer == pointer
+                    })
+            })
+            .cloned()
+    }
+}
+
+/// Create a new `zwp_pointer_constraints_v1` global
+///
+/// Your implementation is called with every new constraint requested by the clients.
+///
+/// The global is directly created on the provided [`Display`](wayland_server::Display),
+/// and this function returns the constraints state and the global handle, in case you
+/// wish to remove this global in the future.
+pub fn init_pointer_constraints_global<U, R, L, Impl>(
+    display: &mut Display,
+    token: CompositorToken<U, R>,
+    implementation: Impl,
+    logger: L,
+) -> (
+    Arc<Mutex<PointerConstraintsState>>,
+    Global<ZwpPointerConstraintsV1>,
+)
+where
+    U: 'static,
+    R: RoleType + 'static,
+    L: Into<Option<::slog::Logger>>,
+    Impl: FnMut(PointerConstraint) + 'static,
+{
+    let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "pointer_constraints_handler"));
+    let state = Arc::new(Mutex::new(PointerConstraintsState {
+        known_constraints: Vec::new(),
+    }));
+    let implementation = Rc::new(RefCell::new(implementation));
+
+    let global_state = state.clone();
+    let global = display.create_global::<ZwpPointerConstraintsV1, _>(1, move |new_constraints, _version| {
+        let state = global_state.clone();
+        let implementation = implementation.clone();
+        let log = log.clone();
+        new_constraints.implement_closure(
+            move |request, constraints: ZwpPointerConstraintsV1| {
+                let (id, surface, pointer, region, lifetime) = match request {
+                    zwp_pointer_constraints_v1::Request::LockPointer {
+                        id,
+                        surface,
+                        pointer,
+                        region,
+                        lifetime,
+                    } => (NewConstraint::Lock(id), surface, pointer, region, lifetime),
+                    zwp_pointer_constraints_v1::Request::ConfinePointer {
+                        id,
+                        surface,
+                        pointer,
+                        region,
+                        lifetime,
+                    } => (NewConstraint::Confine(id), surface, pointer, region, lifetime),
+                    zwp_pointer_constraints_v1::Request::Destroy => return,
+                    _ => unreachable!(),
+                };
+                let lifetime = match Lifetime::from_raw(lifetime) {
+                    Some(Lifetime::Oneshot) => ConstraintLifetime::Oneshot,
+                    Some(Lifetime::Persistent) => ConstraintLifetime::Persistent,
+                    _ => {
+                        // the protocol does not define an error code for invalid lifetimes
+                        constraints
+                            .as_ref()
+                            .post_error(0, format!("Invalid constraint lifetime: {}.", lifetime));
+                        return;
+                    }
+                };
+                let handle = match PointerHandle::from_resource(&pointer) {
+                    Some(handle) => handle,
+                    None => {
+                        // the pointer is inert, so will be the constraint
+                        trace!(log, "Constraint requested for an inert pointer");
+                        implement_inert_constraint(id);
+                        return;
+                    }
+                };
+                if state.lock().unwrap().constraint_for(&surface, &handle).is_some() {
+                    constraints.as_ref().post_error(
+                        zwp_pointer_constraints_v1::Error::AlreadyConstrained as u32,
+                        "This surface is already constrained for this pointer.".into(),
+                    );
+                    return;
+                }
+                let data = ConstraintData {
+                    surface,
+                    pointer: handle,
+                    lifetime,
+                    region: region.map(|region| token.get_region_attributes(&region)),
+                    cursor_hint: None,
+                    active: false,
+                    defunct: false,
+                };
+                let hooked = token
+                    .with_current_state::<SurfaceConstraints, _, _>(&data.surface, |constraints| {
+                        mem::replace(&mut constraints.hooked, true)
+                    });
+                if !hooked {
+                    let hook_state = state.clone();
+                    token.add_post_commit_hook(&data.surface, move |surface| {
+                        apply_committed_state(&hook_state, token, surface)
+                    });
+                }
+                let constraint = implement_constraint(id, data, token, state.clone());
+                debug!(log, "New pointer constraint"; "kind" => format!("{:?}", constraint.kind()));
+                state.lock().unwrap().known_constraints.push(constraint.clone());
+                (&mut *implementation.borrow_mut())(constraint);
+            },
+            None::<fn(_)>,
+            (),
+        );
+    });
+
+    (state, global)
+}
+
+enum NewConstraint {
+    Lock(NewResource<ZwpLockedPointerV1>),
+    Confine(NewResource<ZwpConfinedPointerV1>),
+}
+
+// Move the changes applied by a commit of the surface to its constraints
+fn apply_committed_state<U: 'static, R: 'static>(
+    state: &Mutex<PointerConstraintsState>,
+    token: CompositorToken<U, R>,
+    surface: &WlSurface,
+) {
+    let applied = token.with_current_state::<SurfaceConstraints, _, _>(surface, |constraints| {
+        mem::replace(&mut constraints.changes, Vec::new())
+    });
+    if applied.is_empty() {
+        return;
+    }
+    let state = state.lock().unwrap();
+    for (pointer, changes) in applied {
+        let constraint = match state.constraint_for(surface, &pointer) {
+            Some(constraint) => constraint,
+            // the constraint was destroyed in the meantime
+            None => continue,
+        };
+        constraint.with_data(|data| {
+            if let Some(region) = changes.region {
+                data.region = region;
+            }
+            if let Some(hint) = changes.cursor_hint {
+                data.cursor_hint = Some(hint);
+            }
+        });
+    }
+}
+
+fn with_pending_changes<U, R, F>(constraint: &PointerConstraint, token: CompositorToken<U, R>, f: F)
+where
+    U: 'static,
+    R: 'static,
+    F: FnOnce(&mut ConstraintChanges),
+{
+    let (surface, pointer) = constraint.with_data(|data| (data.surface.clone(), data.pointer.clone()));
+    if !surface.as_ref().is_alive() {
+        return;
+    }
+    token.with_pending_state::<SurfaceConstraints, _, _>(&surface, |constraints| {
+        f(constraints.changes_for(&pointer))
+    });
+}
+
+fn region_request<U: 'static, R: RoleType + 'static>(
+    constraint: &PointerConstraint,
+    region: Option<WlRegion>,
+    token: CompositorToken<U, R>,
+) {
+    let region = region.map(|region| token.get_region_attributes(&region));
+    with_pending_changes(constraint, token, |changes| changes.region = Some(region));
+}
+
+fn forget_constraint<U: 'static, R: 'static>(
+    state: &Mutex<PointerConstraintsState>,
+    destroyed: &PointerConstraint,
+    token: CompositorToken<U, R>,
+) {
+    // a new constraint for the same pointer must not inherit the pending changes of this one
+    let (surface, pointer) = destroyed.with_data(|data| (data.surface.clone(), data.pointer.clone()));
+    if surface.as_ref().is_alive() {
+        token.with_pending_state::<SurfaceConstraints, _, _>(&surface, |constraints| {
+            constraints.changes.retain(|(other, _)| other != &pointer)
+        });
+    }
+    // the destroyed constraint is already dead, as well as any leftover one
+    state
+        .lock()
+        .unwrap()
+        .known_constraints
+        .retain(|constraint| constraint.resource_alive());
+}
+
+fn implement_constraint<U: 'static, R: RoleType + 'static>(
+    id: NewConstraint,
+    data: ConstraintData,
+    token: CompositorToken<U, R>,
+    state: Arc<Mutex<PointerConstraintsState>>,
+) -> PointerConstraint {
+    let resource = match id {
+        NewConstraint::Lock(id) => {
+            let lock = id.implement_closure(
+                move |request, lock: ZwpLockedPointerV1| {
+                    let constraint = PointerConstraint {
+                        resource: ConstraintResource::Lock(lock),
+                    };
+                    match request {
+                        zwp_locked_pointer_v1::Request::SetCursorPositionHint { surface_x, surface_y } => {
+                            with_pending_changes(&constraint, token, |changes| {
+                                changes.cursor_hint = Some((surface_x, surface_y))
+                            });
+                        }
+                        zwp_locked_pointer_v1::Request::SetRegion { region } => {
+                            region_request(&constraint, region, token);
+                        }
+                        zwp_locked_pointer_v1::Request::Destroy => {
+                            // our destructor already handles it
+                        }
+                        _ => unreachable!(),
+                    }
+                },
+                Some(move |lock: ZwpLockedPointerV1| {
+                    forget_constraint(
+                        &state,
+                        &PointerConstraint {
+                            resource: ConstraintResource::Lock(lock),
+                        },
+                        token,
+                    );
+                }),
+                RefCell::new(data),
+            );
+            ConstraintResource::Lock(lock)
+        }
+        NewConstraint::Confine(id) => {
+            let confine = id.implement_closure(
+                move |request, confine: ZwpConfinedPointerV1| {
+                    let constraint = PointerConstraint {
+                        resource: ConstraintResource::Confine(confine),
+                    };
+                    match request {
+                        zwp_confined_pointer_v1::Request::SetRegion { region } => {
+                            region_request(&constraint, region, token);
+                        }
+                        zwp_confined_pointer_v1::Request::Destroy => {
+                            // our destructor already handles it
+                        }
+                        _ => unreachable!(),
+                    }
+                },
+                Some(move |confine: ZwpConfinedPointerV1| {
+                    forget_constraint(
+                        &state,
+                        &PointerConstraint {
+                            resource: ConstraintResource::Confine(confine),
+                        },
+                        token,
+                    );
+                }),
+                RefCell::new(data),
+            );
+            ConstraintResource::Confine(confine)
+        }
+    };
+    PointerConstraint { resource }
+}
+
+fn implement_inert_constraint(id: NewConstraint) {
+    // a constraint on an inert pointer is never activated, it only needs to be destroyable
+    match id {
+        NewConstraint::Lock(id) => {
+            id.implement_closure(|_, _| {}, None::<fn(_)>, ());
+        }
+        NewConstraint::Confine(id) => {
+            id.implement_closure(|_, _| {}, None::<fn(_)>, ());
+        }
+    }
+}
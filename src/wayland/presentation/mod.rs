@@ -0,0 +1,262 @@
+//! Utilities for handling the `wp_presentation` protocol
+//!
+//! This protocol allows clients to request feedback about when the contents of their
+//! surfaces were actually shown to the user, typically to synchronize audio and video or
+//! to measure their latency.
+//!
+//! ## How to use it
+//!
+//! Initialize the global with [`init_presentation_global`], providing the id of the clock
+//! your timestamps come from. When using the [`drm`](::backend::drm) backends, this is
+//! `CLOCK_MONOTONIC`, the clock of the [`VblankTiming`](::backend::drm::VblankTiming)
+//! reported by the kernel.
+//!
+//! Feedback requests are double-buffered state of the surfaces: call
+//! [`PresentationState::surface_commit`] from your commit handler to queue them. Once a frame
+//! containing some surfaces has been shown on an output, report it with
+//! [`PresentationState::presented`]. If you decide not to show the contents of a surface,
+//! use [`PresentationState::discarded`].
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # extern crate smithay;
+//! # extern crate nix;
+//! use smithay::wayland::presentation::init_presentation_global;
+//!
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! let (presentation_state, _global) = init_presentation_global(
+//!     &mut display,
+//!     nix::libc::CLOCK_MONOTONIC as u32,
+//!     None // put a logger here
+//! );
+//! # }
+//! ```
+//!
+//! Then, once a frame has been shown:
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # extern crate smithay;
+//! # use std::time::Duration;
+//! # use wayland_server::protocol::wl_surface::WlSurface;
+//! # use smithay::wayland::output::Output;
+//! # use smithay::wayland::presentation::PresentationState;
+//! use smithay::wayland::presentation::{Kind, PresentationInfo};
+//!
+//! # fn wrap(state: &mut PresentationState, surfaces: &[WlSurface], output: &Output, time: Duration, sequence: u32) {
+//! state.presented(
+//!     surfaces,
+//!     output,
+//!     &PresentationInfo {
+//!         time,
+//!         refresh: Some(Duration::from_nanos(16_666_667)),
+//!         sequence: Some(sequence as u64),
+//!         flags: Kind::Vsync as u32 | Kind::HwClock as u32 | Kind::HwCompletion as u32,
+//!     },
+//! );
+//! # }
+//! # fn main() {}
+//! ```
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use wayland_protocols::presentation_time::server::{
+    wp_presentation::{self, WpPresentation},
+    wp_presentation_feedback::WpPresentationFeedback,
+};
+use wayland_server::{protocol::wl_surface::WlSurface, Display, Global, NewResource};
+
+use crate::wayland::output::Output;
+
+pub use wayland_protocols::presentation_time::server::wp_presentation_feedback::Kind;
+
+/// Information about a frame that has been shown on an output
+#[derive(Debug, Clone, Copy)]
+pub struct PresentationInfo {
+    /// The time at which the frame was shown, in the clock of the global
+    pub time: Duration,
+    /// The duration until the next expected presentation on this output
+    ///
+    /// `None` if the output does not have a constant refresh rate.
+    pub refresh: Option<Duration>,
+    /// The sequence number of the frame on this output, if the output has one
+    ///
+    /// This is typically the vblank counter of the output.
+    pub sequence: Option<u64>,
+    /// Details about how the frame was presented
+    ///
+    /// This is a bitfield of [`Kind`] values, combined with `Kind::Vsync as u32 | Kind::HwClock as u32`.
+    pub flags: u32,
+}
+
+/// State of the presentation global
+///
+/// It tracks the feedback requested by clients for their surfaces.
+pub struct PresentationState {
+    clock_id: u32,
+    pending: Vec<(WlSurface, WpPresentationFeedback)>,
+    queued: Vec<(WlSurface, WpPresentationFeedback)>,
+    log: ::slog::Logger,
+}
+
+impl PresentationState {
+    /// The id of the clock used for the timestamps
+    pub fn clock_id(&self) -> u32 {
+        self.clock_id
+    }
+
+    /// Check if feedback is waiting for the current content of this surface to be presented
+    pub fn has_feedback(&self, surface: &WlSurface) -> bool {
+        self.queued
+            .iter()
+            .any(|&(ref s, _)| s.as_ref().equals(surface.as_ref()))
+    }
+
+    /// Queue the feedback requested for the new content of a surface
+    ///
+    /// The feedback requests are double-buffered: you need to call this method when the
+    /// surface is committed. Feedback queued for the previous content of the surface, which
+    /// will thus never be presented, is discarded.
+    pub fn surface_commit(&mut self, surface: &WlSurface) {
+        self.discarded(surface);
+        let (committed, pending): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|&(ref s, _)| s.as_ref().equals(surface.as_ref()));
+        self.pending = pending;
+        self.queued.extend(committed);
+        self.cleanup();
+    }
+
+    /// Notify that the content of these surfaces has been shown on given output
+    ///
+    /// The feedback queued for these surfaces is resolved and sent to the clients.
+    pub fn presented<'a, I>(&mut self, surfaces: I, output: &Output, info: &PresentationInfo)
+    where
+        I: IntoIterator<Item = &'a WlSurface>,
+    {
+        let secs = info.time.as_secs();
+        let refresh = info
+            .refresh
+            .map(|refresh| {
+                let nanos = refresh
+                    .as_secs()
+                    .saturating_mul(1_000_000_000)
+                    .saturating_add(u64::from(refresh.subsec_nanos()));
+                // the protocol only transmits 32 bits of nanoseconds
+                ::std::cmp::min(nanos, u64::from(::std::u32::MAX)) as u32
+            })
+            .unwrap_or(0);
+        let sequence = info.sequence.unwrap_or(0);
+        let mut flags = info.flags;
+        if info.sequence.is_none() {
+            // the vsync flag requires a sequence counter
+            flags &= !(Kind::Vsync as u32);
+        }
+
+        for surface in surfaces {
+            let (presented, queued): (Vec<_>, Vec<_>) = self
+                .queued
+                .drain(..)
+                .partition(|&(ref s, _)| s.as_ref().equals(surface.as_ref()));
+            self.queued = queued;
+            for (_, feedback) in presented {
+                if let Some(client) = feedback.as_ref().client() {
+                    output.with_client_outputs(&client, |wl_output| feedback.sync_output(wl_output));
+                }
+                feedback.presented(
+                    (secs >> 32) as u32,
+                    secs as u32,
+                    info.time.subsec_nanos(),
+                    refresh,
+                    (sequence >> 32) as u32,
+                    sequence as u32,
+                    flags,
+                );
+            }
+        }
+        trace!(self.log, "Frame presented"; "sequence" => sequence);
+        self.cleanup();
+    }
+
+    /// Notify that the current content of this surface will never be presented
+    pub fn discarded(&mut self, surface: &WlSurface) {
+        self.queued.retain(|&(ref s, ref feedback)| {
+            if s.as_ref().equals(surface.as_ref()) {
+                feedback.discarded();
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    // discard the feedback of destroyed surfaces
+    fn cleanup(&mut self) {
+        let alive = |&(ref surface, ref feedback): &(WlSurface, WpPresentationFeedback)| {
+            if surface.as_ref().is_alive() {
+                true
+            } else {
+                feedback.discarded();
+                false
+            }
+        };
+        self.pending.retain(alive);
+        self.queued.retain(alive);
+    }
+}
+
+/// Create a new `wp_presentation` global
+///
+/// You need to provide the id of the clock in which you will report the presentation
+/// timestamps, as defined by `clock_gettime`.
+///
+/// The global is directly created on the provided [`Display`](wayland_server::Display),
+/// and this function returns the presentation state and the global handle, in case you
+/// wish to remove this global in the future.
+pub fn init_presentation_global<L>(
+    display: &mut Display,
+    clock_id: u32,
+    logger: L,
+) -> (Arc<Mutex<PresentationState>>, Global<WpPresentation>)
+where
+    L: Into<Option<::slog::Logger>>,
+{
+    let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "presentation_handler"));
+    let state = Arc::new(Mutex::new(PresentationState {
+        clock_id,
+        pending: Vec::new(),
+        queued: Vec::new(),
+        log,
+    }));
+
+    let global_state = state.clone();
+    let global = display.create_global::<WpPresentation, _>(1, move |new_presentation, _version| {
+        let state = global_state.clone();
+        let presentation = new_presentation.implement_closure(
+            move |request, _presentation| match request {
+                wp_presentation::Request::Feedback { surface, callback } => {
+                    let feedback = implement_feedback(callback);
+                    state.lock().unwrap().pending.push((surface, feedback));
+                }
+                wp_presentation::Request::Destroy => {}
+                _ => unreachable!(),
+            },
+            None::<fn(_)>,
+            (),
+        );
+        presentation.clock_id(clock_id);
+    });
+
+    (state, global)
+}
+
+fn implement_feedback(feedback: NewResource<WpPresentationFeedback>) -> WpPresentationFeedback {
+    // this object has no requests
+    feedback.implement_closure(|_, _| {}, None::<fn(_)>, ())
+}
@@ -0,0 +1,561 @@
+//! Utilities for manipulating the primary selection
+//!
+//! The primary selection is the mechanism behind middle-click paste: the text selected
+//! by the user in a client is made available to be pasted in another one, independently
+//! from the regular clipboard handled by the [`data_device`](::wayland::data_device).
+//! Like the regular selection, it is a per-seat notion.
+//!
+//! This module handles both the `zwp_primary_selection_device_manager_v1` protocol and
+//! the older `gtk_primary_selection_device_manager` protocol used by GTK3 clients, clients
+//! of both protocols can exchange their selections.
+//!
+//! It provides 3 main freestanding functions, mirroring the ones of the data device:
+//!
+//! - [`init_primary_selection`](::wayland::primary_selection::init_primary_selection): this function
+//!   must be called during the compositor startup to initialize the primary selection logic
+//! - [`set_primary_focus`](::wayland::primary_selection::set_primary_focus): this function sets
+//!   the primary selection focus for a given seat; you'd typically call it whenever the keyboard focus
+//!   changes, alongside [`set_data_device_focus`](::wayland::data_device::set_data_device_focus)
+//! - [`set_primary_selection`](::wayland::primary_selection::set_primary_selection): this function
+//!   allows you to set the contents of the primary selection for your clients
+//!
+//! ## Initialization
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # extern crate smithay;
+//! use smithay::wayland::primary_selection::{init_primary_selection, PrimarySelectionEvent};
+//!
+//! # fn main(){
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! let (_zwp_global, _gtk_global) = init_primary_selection(
+//!     &mut display,
+//!     |event: PrimarySelectionEvent| { /* a callback to react to client selection actions */ },
+//!     None // insert a logger here
+//! );
+//! # }
+//! ```
+
+use std::cell::RefCell;
+use std::os::unix::io::RawFd;
+use std::rc::Rc;
+
+use wayland_protocols::{
+    misc::gtk_primary_selection::server::{
+        gtk_primary_selection_device::{self, GtkPrimarySelectionDevice},
+        gtk_primary_selection_device_manager::{self, GtkPrimarySelectionDeviceManager},
+        gtk_primary_selection_offer::{self, GtkPrimarySelectionOffer},
+        gtk_primary_selection_source::{self, GtkPrimarySelectionSource},
+    },
+    unstable::primary_selection::v1::server::{
+        zwp_primary_selection_device_manager_v1::{self, ZwpPrimarySelectionDeviceManagerV1},
+        zwp_primary_selection_device_v1::{self, ZwpPrimarySelectionDeviceV1},
+        zwp_primary_selection_offer_v1::{self, ZwpPrimarySelectionOfferV1},
+        zwp_primary_selection_source_v1::{self, ZwpPrimarySelectionSourceV1},
+    },
+};
+use wayland_server::{Client, Display, Global, NewResource};
+
+use crate::wayland::seat::Seat;
+
+/// Events that are generated by interactions of the clients with the primary selection
+pub enum PrimarySelectionEvent {
+    /// A client has set the primary selection
+    NewSelection(Option<PrimarySource>),
+    /// A client requested to read the server-set primary selection
+    SendSelection {
+        /// the requested mime type
+        mime_type: String,
+        /// the fd to write into
+        fd: RawFd,
+    },
+}
+
+/// A primary selection source provided by a client
+#[derive(Clone)]
+pub enum PrimarySource {
+    /// A source of the `zwp_primary_selection_device_manager_v1` protocol
+    Zwp(ZwpPrimarySelectionSourceV1),
+    /// A source of the `gtk_primary_selection_device_manager` protocol
+    Gtk(GtkPrimarySelectionSource),
+}
+
+impl PrimarySource {
+    /// Is the source still alive?
+    pub fn alive(&self) -> bool {
+        match *self {
+            PrimarySource::Zwp(ref s) => s.as_ref().is_alive(),
+            PrimarySource::Gtk(ref s) => s.as_ref().is_alive(),
+        }
+    }
+
+    /// The MIME types offered by this source
+    pub fn mime_types(&self) -> Vec<String> {
+        let data = match *self {
+            PrimarySource::Zwp(ref s) => s.as_ref().user_data::<RefCell<Vec<String>>>(),
+            PrimarySource::Gtk(ref s) => s.as_ref().user_data::<RefCell<Vec<String>>>(),
+        };
+        data.map(|mime_types| mime_types.borrow().clone())
+            .unwrap_or_else(Vec::new)
+    }
+
+    /// Request the client to write the contents of the selection in given mime type to this fd
+    pub fn send(&self, mime_type: String, fd: RawFd) {
+        match *self {
+            PrimarySource::Zwp(ref s) => s.send(mime_type, fd),
+            PrimarySource::Gtk(ref s) => s.send(mime_type, fd),
+        }
+    }
+
+    fn cancel(&self) {
+        match *self {
+            PrimarySource::Zwp(ref s) => s.cancelled(),
+            PrimarySource::Gtk(ref s) => s.cancelled(),
+        }
+    }
+
+    fn equals(&self, other: &PrimarySource) -> bool {
+        match (self, other) {
+            (&PrimarySource::Zwp(ref s1), &PrimarySource::Zwp(ref s2)) => s1.as_ref().equals(s2.as_ref()),
+            (&PrimarySource::Gtk(ref s1), &PrimarySource::Gtk(ref s2)) => s1.as_ref().equals(s2.as_ref()),
+            _ => false,
+        }
+    }
+}
+
+enum Selection {
+    Empty,
+    Client(PrimarySource),
+    Compositor(Vec<String>),
+}
+
+#[derive(Clone)]
+enum Device {
+    Zwp(ZwpPrimarySelectionDeviceV1),
+    Gtk(GtkPrimarySelectionDevice),
+}
+
+struct DeviceData {
+    callback: Rc<RefCell<dyn FnMut(PrimarySelectionEvent) + 'static>>,
+}
+
+impl Device {
+    fn client(&self) -> Option<Client> {
+        match *self {
+            Device::Zwp(ref d) => d.as_ref().client(),
+            Device::Gtk(ref d) => d.as_ref().client(),
+        }
+    }
+
+    fn alive(&self) -> bool {
+        match *self {
+            Device::Zwp(ref d) => d.as_ref().is_alive(),
+            Device::Gtk(ref d) => d.as_ref().is_alive(),
+        }
+    }
+
+    fn equals(&self, other: &Device) -> bool {
+        match (self, other) {
+            (&Device::Zwp(ref d1), &Device::Zwp(ref d2)) => d1.as_ref().equals(d2.as_ref()),
+            (&Device::Gtk(ref d1), &Device::Gtk(ref d2)) => d1.as_ref().equals(d2.as_ref()),
+            _ => false,
+        }
+    }
+
+    fn callback(&self) -> Rc<RefCell<dyn FnMut(PrimarySelectionEvent) + 'static>> {
+        let data = match *self {
+            Device::Zwp(ref d) => d.as_ref().user_data::<DeviceData>(),
+            Device::Gtk(ref d) => d.as_ref().user_data::<DeviceData>(),
+        };
+        data.unwrap().callback.clone()
+    }
+
+    fn clear_selection(&self) {
+        match *self {
+            Device::Zwp(ref d) => d.selection(None),
+            Device::Gtk(ref d) => d.selection(None),
+        }
+    }
+
+    // create an offer for these mime types and advertise it as the selection
+    fn offer_selection<F>(&self, client: &Client, mime_types: &[String], mut receive: F)
+    where
+        F: FnMut(String, RawFd) + 'static,
+    {
+        match *self {
+            Device::Zwp(ref d) => {
+                let offer = client
+                    .create_resource::<ZwpPrimarySelectionOfferV1>(d.as_ref().version())
+                    .unwrap()
+                    .implement_closure(
+                        move |req, _offer| match req {
+                            zwp_primary_selection_offer_v1::Request::Receive { mime_type, fd } => {
+                                receive(mime_type, fd)
+                            }
+                            zwp_primary_selection_offer_v1::Request::Destroy => {}
+                            _ => unreachable!(),
+                        },
+                        None::<fn(_)>,
+                        (),
+                    );
+                d.data_offer(&offer);
+                for mime_type in mime_types.iter().cloned() {
+                    offer.offer(mime_type);
+                }
+                d.selection(Some(&offer));
+            }
+            Device::Gtk(ref d) => {
+                let offer = client
+                    .create_resource::<GtkPrimarySelectionOffer>(d.as_ref().version())
+                    .unwrap()
+                    .implement_closure(
+                        move |req, _offer| match req {
+                            gtk_primary_selection_offer::Request::Receive { mime_type, fd } => {
+                                receive(mime_type, fd)
+                            }
+                            gtk_primary_selection_offer::Request::Destroy => {}
+                            _ => unreachable!(),
+                        },
+                        None::<fn(_)>,
+                        (),
+                    );
+                d.data_offer(&offer);
+                for mime_type in mime_types.iter().cloned() {
+                    offer.offer(mime_type);
+                }
+                d.selection(Some(&offer));
+            }
+        }
+    }
+}
+
+struct SeatData {
+    known_devices: Vec<Device>,
+    selection: Selection,
+    log: ::slog::Logger,
+    current_focus: Option<Client>,
+}
+
+impl SeatData {
+    fn new(log: ::slog::Logger) -> SeatData {
+        SeatData {
+            known_devices: Vec::new(),
+            selection: Selection::Empty,
+            log,
+            current_focus: None,
+        }
+    }
+
+    fn set_selection(&mut self, new_selection: Selection) {
+        if let Selection::Client(ref old_source) = self.selection {
+            let replaced = match new_selection {
+                Selection::Client(ref new_source) => !new_source.equals(old_source),
+                _ => true,
+            };
+            if replaced {
+                old_source.cancel();
+            }
+        }
+        self.selection = new_selection;
+        self.send_selection();
+    }
+
+    fn set_focus(&mut self, new_focus: Option<Client>) {
+        self.current_focus = new_focus;
+        self.send_selection();
+    }
+
+    fn send_selection(&mut self) {
+        let client = match self.current_focus.as_ref() {
+            Some(c) => c,
+            None => return,
+        };
+        // first sanitize the selection, reseting it to null if the client holding
+        // it dropped it
+        let cleanup = if let Selection::Client(ref source) = self.selection {
+            !source.alive()
+        } else {
+            false
+        };
+        if cleanup {
+            self.selection = Selection::Empty;
+        }
+        for device in &self.known_devices {
+            // skip devices not belonging to our client
+            if device.client().map(|c| !c.equals(client)).unwrap_or(true) {
+                continue;
+            }
+            match self.selection {
+                Selection::Empty => device.clear_selection(),
+                Selection::Client(ref source) => {
+                    let source = source.clone();
+                    let mime_types = source.mime_types();
+                    let log = self.log.clone();
+                    device.offer_selection(client, &mime_types, move |mime_type, fd| {
+                        // check if the source and associated mime type is still valid
+                        if source.alive() && source.mime_types().contains(&mime_type) {
+                            source.send(mime_type, fd);
+                        } else {
+                            // deny the receive
+                            debug!(log, "Denying a primary selection receive with invalid source.");
+                        }
+                        let _ = ::nix::unistd::close(fd);
+                    });
+                }
+                Selection::Compositor(ref mime_types) => {
+                    let offer_mime_types = mime_types.clone();
+                    let callback = device.callback();
+                    let log = self.log.clone();
+                    device.offer_selection(client, mime_types, move |mime_type, fd| {
+                        // check if the associated mime type is valid
+                        if offer_mime_types.contains(&mime_type) {
+                            (&mut *callback.borrow_mut())(PrimarySelectionEvent::SendSelection {
+                                mime_type,
+                                fd,
+                            });
+                        } else {
+                            // deny the receive
+                            debug!(log, "Denying a primary selection receive with invalid source.");
+                            let _ = ::nix::unistd::close(fd);
+                        }
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn seat_data(seat: &Seat) -> &RefCell<SeatData> {
+    // ensure the seat user_data is ready
+    seat.user_data().insert_if_missing(|| {
+        RefCell::new(SeatData::new(
+            seat.arc.log.new(o!("smithay_module" => "primary_selection_mgr")),
+        ))
+    });
+    seat.user_data().get::<RefCell<SeatData>>().unwrap()
+}
+
+/// Initialize the primary selection globals
+///
+/// This creates both the `zwp_primary_selection_device_manager_v1` and the
+/// `gtk_primary_selection_device_manager` globals, and returns their handles.
+///
+/// You can provide a callback to peek into the actions of your clients over the primary
+/// selection (allowing you to retrieve the current selection buffer). See the
+/// [`PrimarySelectionEvent`] type for details about what notifications you can receive.
+pub fn init_primary_selection<C, L>(
+    display: &mut Display,
+    callback: C,
+    logger: L,
+) -> (
+    Global<ZwpPrimarySelectionDeviceManagerV1>,
+    Global<GtkPrimarySelectionDeviceManager>,
+)
+where
+    C: FnMut(PrimarySelectionEvent) + 'static,
+    L: Into<Option<::slog::Logger>>,
+{
+    let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "primary_selection_mgr"));
+    let callback: Rc<RefCell<dyn FnMut(PrimarySelectionEvent)>> = Rc::new(RefCell::new(callback));
+
+    let zwp_callback = callback.clone();
+    let zwp_log = log.clone();
+    let zwp_global = display.create_global(1, move |new_manager, _version| {
+        implement_zwp_manager(new_manager, zwp_callback.clone(), zwp_log.clone());
+    });
+
+    let gtk_global = display.create_global(1, move |new_manager, _version| {
+        implement_gtk_manager(new_manager, callback.clone(), log.clone());
+    });
+
+    (zwp_global, gtk_global)
+}
+
+/// Set the primary selection focus to a certain client for a given seat
+pub fn set_primary_focus(seat: &Seat, client: Option<Client>) {
+    seat_data(seat).borrow_mut().set_focus(client);
+}
+
+/// Set a compositor-provided primary selection for this seat
+///
+/// You need to provide the available mime types for this selection.
+///
+/// Whenever a client requests to read the selection, your callback will
+/// receive a [`PrimarySelectionEvent::SendSelection`] event.
+pub fn set_primary_selection(seat: &Seat, mime_types: Vec<String>) {
+    seat_data(seat)
+        .borrow_mut()
+        .set_selection(Selection::Compositor(mime_types));
+}
+
+// handle a set_selection request of a device
+fn device_set_selection(
+    seat: &Seat,
+    device: &Device,
+    source: Option<PrimarySource>,
+    callback: &Rc<RefCell<dyn FnMut(PrimarySelectionEvent)>>,
+    log: &::slog::Logger,
+) {
+    if let Some(keyboard) = seat.get_keyboard() {
+        if device
+            .client()
+            .as_ref()
+            .map(|c| keyboard.has_focus(c))
+            .unwrap_or(false)
+        {
+            (&mut *callback.borrow_mut())(PrimarySelectionEvent::NewSelection(source.clone()));
+            // The client has kbd focus, it can set the selection
+            seat_data(seat)
+                .borrow_mut()
+                .set_selection(source.map(Selection::Client).unwrap_or(Selection::Empty));
+            return;
+        }
+    }
+    debug!(log, "denying setting primary selection by a non-focused client");
+}
+
+fn forget_device(seat: &Seat, device: &Device) {
+    seat_data(seat)
+        .borrow_mut()
+        .known_devices
+        .retain(|d| d.alive() && !d.equals(device));
+}
+
+/*
+ * zwp_primary_selection_device_manager_v1
+ */
+
+fn implement_zwp_manager(
+    new_manager: NewResource<ZwpPrimarySelectionDeviceManagerV1>,
+    callback: Rc<RefCell<dyn FnMut(PrimarySelectionEvent)>>,
+    log: ::slog::Logger,
+) -> ZwpPrimarySelectionDeviceManagerV1 {
+    use self::zwp_primary_selection_device_manager_v1::Request;
+    new_manager.implement_closure(
+        move |req, _manager| match req {
+            Request::CreateSource { id } => {
+                id.implement_closure(
+                    |req, source| match req {
+                        zwp_primary_selection_source_v1::Request::Offer { mime_type } => {
+                            let data = source.as_ref().user_data::<RefCell<Vec<String>>>().unwrap();
+                            data.borrow_mut().push(mime_type);
+                        }
+                        zwp_primary_selection_source_v1::Request::Destroy => {}
+                        _ => unreachable!(),
+                    },
+                    None::<fn(_)>,
+                    RefCell::new(Vec::<String>::new()),
+                );
+            }
+            Request::GetDevice { id, seat } => match Seat::from_resource(&seat) {
+                Some(seat) => {
+                    let dd_callback = callback.clone();
+                    let dd_log = log.clone();
+                    let dd_seat = seat.clone();
+                    let device = id.implement_closure(
+                        move |req, device| match req {
+                            zwp_primary_selection_device_v1::Request::SetSelection { source, .. } => {
+                                device_set_selection(
+                                    &dd_seat,
+                                    &Device::Zwp(device),
+                                    source.map(PrimarySource::Zwp),
+                                    &dd_callback,
+                                    &dd_log,
+                                );
+                            }
+                            zwp_primary_selection_device_v1::Request::Destroy => {
+                                forget_device(&dd_seat, &Device::Zwp(device));
+                            }
+                            _ => unreachable!(),
+                        },
+                        None::<fn(_)>,
+                        DeviceData {
+                            callback: callback.clone(),
+                        },
+                    );
+                    seat_data(&seat)
+                        .borrow_mut()
+                        .known_devices
+                        .push(Device::Zwp(device));
+                }
+                None => {
+                    error!(log, "Unmanaged seat given to a primary selection device.");
+                }
+            },
+            Request::Destroy => {}
+            _ => unreachable!(),
+        },
+        None::<fn(_)>,
+        (),
+    )
+}
+
+/*
+ * gtk_primary_selection_device_manager
+ */
+
+fn implement_gtk_manager(
+    new_manager: NewResource<GtkPrimarySelectionDeviceManager>,
+    callback: Rc<RefCell<dyn FnMut(PrimarySelectionEvent)>>,
+    log: ::slog::Logger,
+) -> GtkPrimarySelectionDeviceManager {
+    use self::gtk_primary_selection_device_manager::Request;
+    new_manager.implement_closure(
+        move |req, _manager| match req {
+            Request::CreateSource { id } => {
+                id.implement_closure(
+                    |req, source| match req {
+                        gtk_primary_selection_source::Request::Offer { mime_type } => {
+                            let data = source.as_ref().user_data::<RefCell<Vec<String>>>().unwrap();
+                            data.borrow_mut().push(mime_type);
+                        }
+                        gtk_primary_selection_source::Request::Destroy => {}
+                        _ => unreachable!(),
+                    },
+                    None::<fn(_)>,
+                    RefCell::new(Vec::<String>::new()),
+                );
+            }
+            Request::GetDevice { id, seat } => match Seat::from_resource(&seat) {
+                Some(seat) => {
+                    let dd_callback = callback.clone();
+                    let dd_log = log.clone();
+                    let dd_seat = seat.clone();
+                    let device = id.implement_closure(
+                        move |req, device| match req {
+                            gtk_primary_selection_device::Request::SetSelection { source, .. } => {
+                                device_set_selection(
+                                    &dd_seat,
+                                    &Device::Gtk(device),
+                                    source.map(PrimarySource::Gtk),
+                                    &dd_callback,
+                                    &dd_log,
+                                );
+                            }
+                            gtk_primary_selection_device::Request::Destroy => {
+                                forget_device(&dd_seat, &Device::Gtk(device));
+                            }
+                            _ => unreachable!(),
+                        },
+                        None::<fn(_)>,
+                        DeviceData {
+                            callback: callback.clone(),
+                        },
+                    );
+                    seat_data(&seat)
+                        .borrow_mut()
+                        .known_devices
+                        .push(Device::Gtk(device));
+                }
+                None => {
+                    error!(log, "Unmanaged seat given to a primary selection device.");
+                }
+            },
+            Request::Destroy => {}
+            _ => unreachable!(),
+        },
+        None::<fn(_)>,
+        (),
+    )
+}
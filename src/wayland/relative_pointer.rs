@@ -0,0 +1,98 @@
+//! Utilities for handling the `zwp_relative_pointer_v1` protocol
+//!
+//! This protocol allows clients to receive the relative motion of the pointer, unaffected
+//! by the boundaries of the outputs and with or without pointer acceleration applied.
+//! It is typically used by games and 3D tools, in combination with a
+//! [pointer lock](::wayland::pointer_constraints).
+//!
+//! ## How to use it
+//!
+//! Initialize the global with [`init_relative_pointer_manager_global`], then forward the
+//! relative motion reported by your input backend using
+//! [`PointerHandle::relative_motion`](::wayland::seat::PointerHandle::relative_motion).
+//! The events will be sent to the relative pointers of the client currently focused by
+//! the pointer.
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # extern crate smithay;
+//! use smithay::wayland::relative_pointer::init_relative_pointer_manager_global;
+//!
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! let relative_pointer_global = init_relative_pointer_manager_global(
+//!     &mut display,
+//!     None // put a logger here
+//! );
+//! # }
+//! ```
+
+use crate::wayland::seat::PointerHandle;
+use wayland_protocols::unstable::relative_pointer::v1::server::{
+    zwp_relative_pointer_manager_v1::{self, ZwpRelativePointerManagerV1},
+    zwp_relative_pointer_v1::{self, ZwpRelativePointerV1},
+};
+use wayland_server::{Display, Global, NewResource};
+
+/// Create a new `zwp_relative_pointer_manager_v1` global
+///
+/// The global is directly created on the provided [`Display`](wayland_server::Display),
+/// and this function returns the global handle, in case you wish to remove this global in
+/// the future.
+pub fn init_relative_pointer_manager_global<L>(
+    display: &mut Display,
+    logger: L,
+) -> Global<ZwpRelativePointerManagerV1>
+where
+    L: Into<Option<::slog::Logger>>,
+{
+    let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "relative_pointer_handler"));
+
+    display.create_global::<ZwpRelativePointerManagerV1, _>(1, move |new_manager, _version| {
+        let log = log.clone();
+        new_manager.implement_closure(
+            move |request, _manager| match request {
+                zwp_relative_pointer_manager_v1::Request::GetRelativePointer { id, pointer } => {
+                    let handle = PointerHandle::from_resource(&pointer);
+                    if handle.is_none() {
+                        // this pointer is inert, so will be the relative pointer
+                        trace!(log, "Relative pointer requested for an inert pointer");
+                    }
+                    implement_relative_pointer(id, handle);
+                }
+                zwp_relative_pointer_manager_v1::Request::Destroy => {
+                    // nothing to do
+                }
+                _ => unreachable!(),
+            },
+            None::<fn(_)>,
+            (),
+        );
+    })
+}
+
+fn implement_relative_pointer(
+    new_pointer: NewResource<ZwpRelativePointerV1>,
+    handle: Option<PointerHandle>,
+) -> ZwpRelativePointerV1 {
+    let destructor = handle.clone().map(|handle| {
+        move |pointer: ZwpRelativePointerV1| {
+            handle.remove_relative_pointer(&pointer);
+        }
+    });
+    let pointer = new_pointer.implement_closure(
+        |request, _pointer| match request {
+            zwp_relative_pointer_v1::Request::Destroy => {
+                // our destructor already handles it
+            }
+            _ => unreachable!(),
+        },
+        destructor,
+        (),
+    );
+    if let Some(handle) = handle {
+        handle.new_relative_pointer(pointer.clone());
+    }
+    pointer
+}
@@ -1,4 +1,5 @@
 use crate::backend::input::KeyState;
+use crate::wayland::text_input::TextInputHandle;
 use std::{
     cell::RefCell,
     default::Default,
@@ -104,6 +105,12 @@ impl<'a> Default for XkbConfig<'a> {
     }
 }
 
+enum GrabStatus {
+    None,
+    Active(u32, Box<dyn KeyboardGrab>),
+    Borrowed,
+}
+
 struct KbdInternal {
     known_kbds: Vec<WlKeyboard>,
     focus: Option<WlSurface>,
@@ -114,6 +121,8 @@ struct KbdInternal {
     repeat_rate: i32,
     repeat_delay: i32,
     focus_hook: Box<dyn FnMut(Option<&WlSurface>)>,
+    grab: GrabStatus,
+    text_input: TextInputHandle,
 }
 
 // This is OK because all parts of `xkb` will remain on the
@@ -125,6 +134,7 @@ impl KbdInternal {
         xkb_config: XkbConfig<'_>,
         repeat_rate: i32,
         repeat_delay: i32,
+        text_input: TextInputHandle,
         focus_hook: Box<dyn FnMut(Option<&WlSurface>)>,
     ) -> Result<KbdInternal, ()> {
         // we create a new contex for each keyboard because libxkbcommon is actually NOT threadsafe
@@ -155,6 +165,8 @@ impl KbdInternal {
             repeat_rate,
             repeat_delay,
             focus_hook,
+            grab: GrabStatus::None,
+            text_input,
         })
     }
 
@@ -216,6 +228,27 @@ impl KbdInternal {
             }
         }
     }
+
+    fn with_grab<F>(&mut self, logger: &::slog::Logger, f: F)
+    where
+        F: FnOnce(KeyboardInnerHandle<'_>, &mut dyn KeyboardGrab),
+    {
+        let mut grab = ::std::mem::replace(&mut self.grab, GrabStatus::Borrowed);
+        match grab {
+            GrabStatus::Borrowed => panic!("Accessed a keyboard grab from within a keyboard grab access."),
+            GrabStatus::Active(_, ref mut handler) => {
+                f(KeyboardInnerHandle { inner: self, logger }, &mut **handler);
+            }
+            GrabStatus::None => {
+                f(KeyboardInnerHandle { inner: self, logger }, &mut DefaultGrab);
+            }
+        }
+
+        if let GrabStatus::Borrowed = self.grab {
+            // the grab has not been ended nor replaced, put it back in place
+            self.grab = grab;
+        }
+    }
 }
 
 /// Errors that can be encountered when creating a keyboard handler
@@ -233,6 +266,7 @@ pub(crate) fn create_keyboard_handler<F>(
     repeat_delay: i32,
     repeat_rate: i32,
     logger: &::slog::Logger,
+    text_input: TextInputHandle,
     focus_hook: F,
 ) -> Result<KeyboardHandle, Error>
 where
@@ -243,11 +277,17 @@ where
         "rules" => xkb_config.rules, "model" => xkb_config.model, "layout" => xkb_config.layout,
         "variant" => xkb_config.variant, "options" => &xkb_config.options
     );
-    let internal =
-        KbdInternal::new(xkb_config, repeat_rate, repeat_delay, Box::new(focus_hook)).map_err(|_| {
-            debug!(log, "Loading keymap failed");
-            Error::BadKeymap
-        })?;
+    let internal = KbdInternal::new(
+        xkb_config,
+        repeat_rate,
+        repeat_delay,
+        text_input,
+        Box::new(focus_hook),
+    )
+    .map_err(|_| {
+        debug!(log, "Loading keymap failed");
+        Error::BadKeymap
+    })?;
 
     info!(log, "Loaded Keymap"; "name" => internal.keymap.layouts().next());
 
@@ -279,6 +319,9 @@ struct KbdRc {
 /// - process key inputs from the input backend, allowing them to be caught at the compositor-level
 ///   or forwarded to the client. See the documentation of the [`KeyboardHandle::input`] method for
 ///   details.
+///
+/// Key inputs and focus changes are forwarded to the current keyboard grab if any is active.
+/// See the [`KeyboardGrab`] trait for details.
 #[derive(Clone)]
 pub struct KeyboardHandle {
     arc: Rc<KbdRc>,
@@ -320,7 +363,7 @@ impl KeyboardHandle {
             return;
         }
 
-        // forward to client if no keybinding is triggered
+        // forward to the grab if no keybinding is triggered
         let modifiers = if mods_changed {
             Some(guard.serialize_modifiers())
         } else {
@@ -330,17 +373,9 @@ impl KeyboardHandle {
             KeyState::Pressed => WlKeyState::Pressed,
             KeyState::Released => WlKeyState::Released,
         };
-        guard.with_focused_kbds(|kbd, _| {
-            if let Some((dep, la, lo, gr)) = modifiers {
-                kbd.modifiers(serial, dep, la, lo, gr);
-            }
-            kbd.key(serial, time, keycode, wl_state);
+        guard.with_grab(&self.arc.logger, move |mut handle, grab| {
+            grab.input(&mut handle, keycode, wl_state, modifiers, serial, time);
         });
-        if guard.focus.is_some() {
-            trace!(self.arc.logger, "Input forwarded to client");
-        } else {
-            trace!(self.arc.logger, "No client currently focused");
-        }
     }
 
     /// Set the current focus of this keyboard
@@ -349,47 +384,52 @@ impl KeyboardHandle {
     /// will be sent a [`wl_keyboard::Event::Leave`](wayland_server::protocol::wl_keyboard::Event::Leave)
     /// event, and if the new focus is not `None`,
     /// a [`wl_keyboard::Event::Enter`](wayland_server::protocol::wl_keyboard::Event::Enter) event will be sent.
+    ///
+    /// If a grab is active, it decides whether the focus is actually changed.
     pub fn set_focus(&self, focus: Option<&WlSurface>, serial: u32) {
-        let mut guard = self.arc.internal.borrow_mut();
+        self.arc
+            .internal
+            .borrow_mut()
+            .with_grab(&self.arc.logger, move |mut handle, grab| {
+                grab.set_focus(&mut handle, focus, serial);
+            });
+    }
 
-        let same = guard
-            .focus
-            .as_ref()
-            .and_then(|f| focus.map(|s| s.as_ref().equals(f.as_ref())))
-            .unwrap_or(false);
+    /// Change the current grab on this keyboard to the provided grab
+    ///
+    /// Overwrites any current grab.
+    pub fn set_grab<G: KeyboardGrab + 'static>(&self, grab: G, serial: u32) {
+        self.arc.internal.borrow_mut().grab = GrabStatus::Active(serial, Box::new(grab));
+    }
 
-        if !same {
-            // unset old focus
-            guard.with_focused_kbds(|kbd, s| {
-                kbd.leave(serial, &s);
-            });
+    /// Remove any current grab on this keyboard, reseting it to the default behavior
+    pub fn unset_grab(&self) {
+        self.arc.internal.borrow_mut().grab = GrabStatus::None;
+    }
 
-            // set new focus
-            guard.focus = focus.cloned();
-            let (dep, la, lo, gr) = guard.serialize_modifiers();
-            let keys = guard.serialize_pressed_keys();
-            guard.with_focused_kbds(|kbd, surface| {
-                kbd.modifiers(serial, dep, la, lo, gr);
-                kbd.enter(serial, &surface, keys.clone());
-            });
-            {
-                let KbdInternal {
-                    ref focus,
-                    ref mut focus_hook,
-                    ..
-                } = *guard;
-                focus_hook(focus.as_ref());
-            }
-            if guard.focus.is_some() {
-                trace!(self.arc.logger, "Focus set to new surface");
-            } else {
-                trace!(self.arc.logger, "Focus unset");
-            }
-        } else {
-            trace!(self.arc.logger, "Focus unchanged");
+    /// Check if this keyboard is currently grabbed with this serial
+    pub fn has_grab(&self, serial: u32) -> bool {
+        let guard = self.arc.internal.borrow_mut();
+        match guard.grab {
+            GrabStatus::Active(s, _) => s == serial,
+            _ => false,
+        }
+    }
+
+    /// Check if this keyboard is currently being grabbed
+    pub fn is_grabbed(&self) -> bool {
+        let guard = self.arc.internal.borrow_mut();
+        match guard.grab {
+            GrabStatus::None => false,
+            _ => true,
         }
     }
 
+    /// Access the surface currently focused by this keyboard
+    pub fn current_focus(&self) -> Option<WlSurface> {
+        self.arc.internal.borrow().focus.clone()
+    }
+
     /// Check if given client currently has keyboard focus
     pub fn has_focus(&self, client: &Client) -> bool {
         self.arc
@@ -448,6 +488,161 @@ impl KeyboardHandle {
     }
 }
 
+/// A trait to implement a keyboard grab
+///
+/// In some context, it is necessary to temporarily change the behavior of the keyboard. This is
+/// typically known as a keyboard grab. A typical example would be an alt-tab window switcher or a
+/// lock screen, that need to receive all key events regardless of the surface that has the focus.
+///
+/// This trait is the interface to intercept regular keyboard events and change them as needed, its
+/// interface mimics the [`KeyboardHandle`] interface.
+///
+/// Key events only reach the grab if they were not intercepted by the filter provided to
+/// [`KeyboardHandle::input`].
+///
+/// If your logic decides that the grab should end, both [`KeyboardInnerHandle`] and [`KeyboardHandle`] have
+/// a method to change it.
+///
+/// When your grab ends (either as you requested it or if it was forcefully cancelled by the server),
+/// the struct implementing this trait will be dropped. As such you should put clean-up logic in the destructor,
+/// rather than trying to guess when the grab will end.
+pub trait KeyboardGrab {
+    /// A key was pressed or released
+    ///
+    /// If this key changed the state of the modifiers, `modifiers` contains their new serialized
+    /// state, as `(depressed, latched, locked, group)`.
+    fn input(
+        &mut self,
+        handle: &mut KeyboardInnerHandle<'_>,
+        keycode: u32,
+        state: WlKeyState,
+        modifiers: Option<(u32, u32, u32, u32)>,
+        serial: u32,
+        time: u32,
+    );
+    /// The compositor requested a change of the keyboard focus
+    fn set_focus(&mut self, handle: &mut KeyboardInnerHandle<'_>, focus: Option<&WlSurface>, serial: u32);
+}
+
+/// This inner handle is accessed from inside a keyboard grab logic, and directly
+/// sends event to the client
+pub struct KeyboardInnerHandle<'a> {
+    inner: &'a mut KbdInternal,
+    logger: &'a ::slog::Logger,
+}
+
+impl<'a> KeyboardInnerHandle<'a> {
+    /// Change the current grab on this keyboard to the provided grab
+    ///
+    /// Overwrites any current grab.
+    pub fn set_grab<G: KeyboardGrab + 'static>(&mut self, serial: u32, grab: G) {
+        self.inner.grab = GrabStatus::Active(serial, Box::new(grab));
+    }
+
+    /// Remove any current grab on this keyboard, resetting it to the default behavior
+    pub fn unset_grab(&mut self) {
+        self.inner.grab = GrabStatus::None;
+    }
+
+    /// Access the current focus of this keyboard
+    pub fn current_focus(&self) -> Option<&WlSurface> {
+        self.inner.focus.as_ref()
+    }
+
+    /// Access the current state of the modifiers
+    pub fn modifiers_state(&self) -> ModifiersState {
+        self.inner.mods_state
+    }
+
+    /// The keycodes of the currently physically pressed keys
+    ///
+    /// This still includes keys that your grab has intercepted and not sent
+    /// to the client.
+    pub fn pressed_keys(&self) -> &[u32] {
+        &self.inner.pressed_keys
+    }
+
+    /// Send a key event to the currently focused client
+    ///
+    /// If `modifiers` is provided, a modifiers event is sent before the key event.
+    pub fn input(
+        &mut self,
+        keycode: u32,
+        state: WlKeyState,
+        modifiers: Option<(u32, u32, u32, u32)>,
+        serial: u32,
+        time: u32,
+    ) {
+        self.inner.with_focused_kbds(|kbd, _| {
+            if let Some((dep, la, lo, gr)) = modifiers {
+                kbd.modifiers(serial, dep, la, lo, gr);
+            }
+            kbd.key(serial, time, keycode, state);
+        });
+        if self.inner.focus.is_some() {
+            trace!(self.logger, "Input forwarded to client");
+        } else {
+            trace!(self.logger, "No client currently focused");
+        }
+    }
+
+    /// Send the current state of the modifiers to the currently focused client
+    ///
+    /// This can be used to let the client know about modifier changes your grab did not forward.
+    pub fn modifiers(&mut self, serial: u32) {
+        let (dep, la, lo, gr) = self.inner.serialize_modifiers();
+        self.inner.with_focused_kbds(|kbd, _| {
+            kbd.modifiers(serial, dep, la, lo, gr);
+        });
+    }
+
+    /// Change the current focus of this keyboard
+    ///
+    /// If the new focus is different from the previous one, any previous focus
+    /// will be sent a [`wl_keyboard::Event::Leave`](wayland_server::protocol::wl_keyboard::Event::Leave)
+    /// event, and if the new focus is not `None`,
+    /// a [`wl_keyboard::Event::Enter`](wayland_server::protocol::wl_keyboard::Event::Enter) event will be sent.
+    pub fn set_focus(&mut self, focus: Option<&WlSurface>, serial: u32) {
+        let same = self
+            .inner
+            .focus
+            .as_ref()
+            .and_then(|f| focus.map(|s| s.as_ref().equals(f.as_ref())))
+            .unwrap_or(false);
+
+        if same {
+            trace!(self.logger, "Focus unchanged");
+            return;
+        }
+
+        // unset old focus
+        self.inner.with_focused_kbds(|kbd, s| {
+            kbd.leave(serial, &s);
+        });
+
+        // set new focus
+        self.inner.focus = focus.cloned();
+        let (dep, la, lo, gr) = self.inner.serialize_modifiers();
+        let keys = self.inner.serialize_pressed_keys();
+        self.inner.with_focused_kbds(|kbd, surface| {
+            kbd.modifiers(serial, dep, la, lo, gr);
+            kbd.enter(serial, &surface, keys.clone());
+        });
+        self.inner.text_input.set_focus(focus);
+        let KbdInternal {
+            ref focus,
+            ref mut focus_hook,
+            ..
+        } = *self.inner;
+        focus_hook(focus.as_ref());
+        if focus.is_some() {
+            trace!(self.logger, "Focus set to new surface");
+        } else {
+            trace!(self.logger, "Focus unset");
+        }
+    }
+}
+
 pub(crate) fn implement_keyboard(
     new_keyboard: NewResource<WlKeyboard>,
     handle: Option<&KeyboardHandle>,
@@ -477,3 +672,28 @@ pub(crate) fn implement_keyboard(
         (),
     )
 }
+
+/*
+ * Grabs definition
+ */
+
+// The default grab, the behavior when no particular grab is in progress
+struct DefaultGrab;
+
+impl KeyboardGrab for DefaultGrab {
+    fn input(
+        &mut self,
+        handle: &mut KeyboardInnerHandle<'_>,
+        keycode: u32,
+        state: WlKeyState,
+        modifiers: Option<(u32, u32, u32, u32)>,
+        serial: u32,
+        time: u32,
+    ) {
+        handle.input(keycode, state, modifiers, serial, time);
+    }
+
+    fn set_focus(&mut self, handle: &mut KeyboardInnerHandle<'_>, focus: Option<&WlSurface>, serial: u32) {
+        handle.set_focus(focus, serial);
+    }
+}
@@ -35,11 +35,11 @@
 //!
 //! Once the seat is initialized, you can add capabilities to it.
 //!
-//! Currently, only pointer and keyboard capabilities are supported by
-//! smithay.
+//! Pointer, keyboard and touch capabilities are supported by smithay.
 //!
 //! You can add these capabilities via methods of the [`Seat`](::wayland::seat::Seat) struct:
-//! [`add_keyboard`](::wayland::seat::Seat::add_keyboard), [`add_pointer`](::wayland::seat::Seat::add_pointer).
+//! [`add_keyboard`](::wayland::seat::Seat::add_keyboard), [`add_pointer`](::wayland::seat::Seat::add_pointer),
+//! [`add_touch`](::wayland::seat::Seat::add_touch).
 //! These methods return handles that can be cloned and sent across thread, so you can keep one around
 //! in your event-handling code to forward inputs to your clients.
 
@@ -47,15 +47,25 @@ use std::{cell::RefCell, rc::Rc};
 
 mod keyboard;
 mod pointer;
+mod touch;
 
 pub use self::{
-    keyboard::{keysyms, Error as KeyboardError, KeyboardHandle, Keysym, ModifiersState, XkbConfig},
+    keyboard::{
+        keysyms, Error as KeyboardError, KeyboardGrab, KeyboardHandle, KeyboardInnerHandle, Keysym,
+        ModifiersState, XkbConfig,
+    },
     pointer::{
         AxisFrame, CursorImageRole, CursorImageStatus, PointerGrab, PointerHandle, PointerInnerHandle,
     },
+    touch::{TouchGrab, TouchHandle, TouchInnerHandle},
 };
 
-use crate::wayland::compositor::{roles::Role, CompositorToken};
+pub(crate) use self::touch::slot_or_default;
+
+use crate::wayland::{
+    compositor::{roles::Role, CompositorToken},
+    text_input::TextInputHandle,
+};
 
 use wayland_commons::utils::UserDataMap;
 
@@ -67,12 +77,14 @@ use wayland_server::{
 struct Inner {
     pointer: Option<PointerHandle>,
     keyboard: Option<KeyboardHandle>,
+    touch: Option<TouchHandle>,
     known_seats: Vec<wl_seat::WlSeat>,
 }
 
 pub(crate) struct SeatRc {
     inner: RefCell<Inner>,
     user_data: UserDataMap,
+    text_input: TextInputHandle,
     pub(crate) log: ::slog::Logger,
     name: String,
 }
@@ -86,6 +98,9 @@ impl Inner {
         if self.keyboard.is_some() {
             caps |= wl_seat::Capability::Keyboard;
         }
+        if self.touch.is_some() {
+            caps |= wl_seat::Capability::Touch;
+        }
         caps
     }
 
@@ -137,11 +152,13 @@ impl Seat {
             inner: RefCell::new(Inner {
                 pointer: None,
                 keyboard: None,
+                touch: None,
                 known_seats: Vec::new(),
             }),
             log: log.new(o!("smithay_module" => "seat_handler", "seat_name" => name.clone())),
             name,
             user_data: UserDataMap::new(),
+            text_input: TextInputHandle::default(),
         });
         let seat = Seat { arc: arc.clone() };
         let global = display.create_global(5, move |new_seat, _version| {
@@ -294,6 +311,7 @@ impl Seat {
             repeat_delay,
             repeat_rate,
             &self.arc.log,
+            self.arc.text_input.clone(),
             move |focus| focus_hook(&me, focus),
         )?;
         if inner.keyboard.is_some() {
@@ -312,6 +330,14 @@ impl Seat {
         self.arc.inner.borrow_mut().keyboard.clone()
     }
 
+    /// Access the text inputs of this seat
+    ///
+    /// They follow the focus of the keyboard of the seat, see the
+    /// [`text_input`](::wayland::text_input) module for details.
+    pub fn get_text_input(&self) -> TextInputHandle {
+        self.arc.text_input.clone()
+    }
+
     /// Remove the keyboard capability from this seat
     ///
     /// Clients will be appropriately notified.
@@ -323,6 +349,53 @@ impl Seat {
         }
     }
 
+    /// Adds the touch capability to this seat
+    ///
+    /// You are provided a [`TouchHandle`], which allows you to send input events
+    /// to this touch device. This handle can be cloned.
+    ///
+    /// Calling this method on a seat that already has a touch capability
+    /// will overwrite it, and will be seen by the clients as if the
+    /// touchscreen was unplugged and a new one was plugged.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # extern crate smithay;
+    /// # use smithay::wayland::seat::Seat;
+    /// # let mut seat: Seat = unimplemented!();
+    /// let touch_handle = seat.add_touch();
+    /// ```
+    pub fn add_touch(&mut self) -> TouchHandle {
+        let mut inner = self.arc.inner.borrow_mut();
+        let touch = self::touch::create_touch_handler();
+        if inner.touch.is_some() {
+            // there is already a touch device, remove it and notify the clients
+            // of the change
+            inner.touch = None;
+            inner.send_all_caps();
+        }
+        inner.touch = Some(touch.clone());
+        inner.send_all_caps();
+        touch
+    }
+
+    /// Access the touch device of this seat if any
+    pub fn get_touch(&self) -> Option<TouchHandle> {
+        self.arc.inner.borrow_mut().touch.clone()
+    }
+
+    /// Remove the touch capability from this seat
+    ///
+    /// Clients will be appropriately notified.
+    pub fn remove_touch(&mut self) {
+        let mut inner = self.arc.inner.borrow_mut();
+        if inner.touch.is_some() {
+            inner.touch = None;
+            inner.send_all_caps();
+        }
+    }
+
     /// Checks whether a given [`WlSeat`](wl_seat::WlSeat) is associated with this [`Seat`]
     pub fn owns(&self, seat: &wl_seat::WlSeat) -> bool {
         let inner = self.arc.inner.borrow_mut();
@@ -368,8 +441,13 @@ where
                         // same as pointer, should error but cannot
                     }
                 }
-                wl_seat::Request::GetTouch { id: _ } => {
-                    // TODO
+                wl_seat::Request::GetTouch { id } => {
+                    let touch = self::touch::implement_touch(id, inner.touch.as_ref());
+                    if let Some(ref touch_handle) = inner.touch {
+                        touch_handle.new_touch(touch);
+                    } else {
+                        // same as pointer, should error but cannot
+                    }
                 }
                 wl_seat::Request::Release => {
                     // Our destructors already handle it
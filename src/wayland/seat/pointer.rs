@@ -1,6 +1,7 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use wayland_protocols::unstable::relative_pointer::v1::server::zwp_relative_pointer_v1::ZwpRelativePointerV1;
 use wayland_server::{
     protocol::{
         wl_pointer::{self, Axis, AxisSource, ButtonState, Request, WlPointer},
@@ -37,6 +38,7 @@ enum GrabStatus {
 
 struct PointerInternal {
     known_pointers: Vec<WlPointer>,
+    known_relative_pointers: Vec<ZwpRelativePointerV1>,
     focus: Option<(WlSurface, (f64, f64))>,
     pending_focus: Option<(WlSurface, (f64, f64))>,
     location: (f64, f64),
@@ -73,6 +75,7 @@ impl PointerInternal {
 
         PointerInternal {
             known_pointers: Vec::new(),
+            known_relative_pointers: Vec::new(),
             focus: None,
             pending_focus: None,
             location: (0.0, 0.0),
@@ -95,6 +98,19 @@ impl PointerInternal {
         }
     }
 
+    fn with_focused_relative_pointers<F>(&self, mut f: F)
+    where
+        F: FnMut(&ZwpRelativePointerV1),
+    {
+        if let Some((ref focus, _)) = self.focus {
+            for ptr in &self.known_relative_pointers {
+                if ptr.as_ref().same_client_as(focus.as_ref()) {
+                    f(ptr)
+                }
+            }
+        }
+    }
+
     fn with_grab<F>(&mut self, f: F)
     where
         F: FnOnce(PointerInnerHandle<'_>, &mut dyn PointerGrab),
@@ -137,6 +153,29 @@ impl PointerHandle {
         guard.known_pointers.push(pointer);
     }
 
+    pub(crate) fn new_relative_pointer(&self, pointer: ZwpRelativePointerV1) {
+        let mut guard = self.inner.borrow_mut();
+        guard.known_relative_pointers.push(pointer);
+    }
+
+    pub(crate) fn remove_relative_pointer(&self, pointer: &ZwpRelativePointerV1) {
+        let mut guard = self.inner.borrow_mut();
+        guard
+            .known_relative_pointers
+            .retain(|p| !p.as_ref().equals(pointer.as_ref()));
+    }
+
+    /// Retrieve the [`PointerHandle`] a `wl_pointer` object was created from
+    ///
+    /// Returns `None` if the pointer is not managed by smithay, or if it was created
+    /// while the seat had no pointer capability.
+    pub(crate) fn from_resource(pointer: &WlPointer) -> Option<PointerHandle> {
+        pointer
+            .as_ref()
+            .user_data::<Option<PointerHandle>>()
+            .and_then(|handle| handle.clone())
+    }
+
     /// Change the current grab on this pointer to the provided grab
     ///
     /// Overwrites any current grab.
@@ -220,6 +259,52 @@ impl PointerHandle {
             grab.axis(&mut handle, details);
         });
     }
+
+    /// Notify of a relative motion of the pointer
+    ///
+    /// You provide the motion vector of the pointer, both with and without acceleration
+    /// applied, as well as the timestamp of the motion in microseconds.
+    ///
+    /// This sends the `zwp_relative_pointer_v1` events to the client owning the currently
+    /// focused surface, if it bound the [`relative_pointer`](::wayland::relative_pointer)
+    /// global. It is independent of [`motion`](PointerHandle::motion), and is not
+    /// intercepted by pointer grabs: it should be called for every relative motion reported
+    /// by the input device, even if the pointer is locked by a
+    /// [pointer constraint](::wayland::pointer_constraints).
+    pub fn relative_motion(&self, delta: (f64, f64), delta_unaccel: (f64, f64), utime: u64) {
+        let inner = self.inner.borrow();
+        let mut sent = false;
+        inner.with_focused_relative_pointers(|ptr| {
+            sent = true;
+            ptr.relative_motion(
+                (utime >> 32) as u32,
+                utime as u32,
+                delta.0,
+                delta.1,
+                delta_unaccel.0,
+                delta_unaccel.1,
+            );
+        });
+        if sent {
+            inner.with_focused_pointers(|pointer, _| {
+                if pointer.as_ref().version() >= 5 {
+                    pointer.frame();
+                }
+            });
+        }
+    }
+
+    /// Access the surface currently focused by this pointer, and the location of its origin
+    /// in the global compositor space
+    pub fn current_focus(&self) -> Option<(WlSurface, (f64, f64))> {
+        self.inner.borrow().focus.clone()
+    }
+}
+
+impl ::std::cmp::PartialEq for PointerHandle {
+    fn eq(&self, other: &PointerHandle) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
 }
 
 /// A trait to implement a pointer grab
@@ -598,7 +683,7 @@ where
             }
         },
         destructor,
-        (),
+        handle.cloned(),
     )
 }
 
@@ -0,0 +1,539 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use wayland_server::{
+    protocol::{
+        wl_surface::WlSurface,
+        wl_touch::{Request, WlTouch},
+    },
+    NewResource,
+};
+
+use crate::backend::input::TouchSlot;
+
+enum GrabStatus {
+    None,
+    Active(u32, Box<dyn TouchGrab>),
+    Borrowed,
+}
+
+struct TouchInternal {
+    known_touches: Vec<WlTouch>,
+    focus: HashMap<TouchSlot, (WlSurface, (f64, f64))>,
+    active_slots: Vec<TouchSlot>,
+    down_serials: HashMap<TouchSlot, u32>,
+    pending_frame: Vec<WlTouch>,
+    grab: GrabStatus,
+}
+
+impl TouchInternal {
+    fn new() -> TouchInternal {
+        TouchInternal {
+            known_touches: Vec::new(),
+            focus: HashMap::new(),
+            active_slots: Vec::new(),
+            down_serials: HashMap::new(),
+            pending_frame: Vec::new(),
+            grab: GrabStatus::None,
+        }
+    }
+
+    // Calls `f` for all touch objects of the client owning `surface`, and remember
+    // them as needing a frame event
+    fn with_touches_of<F>(&mut self, surface: &WlSurface, mut f: F)
+    where
+        F: FnMut(&WlTouch),
+    {
+        let TouchInternal {
+            ref known_touches,
+            ref mut pending_frame,
+            ..
+        } = *self;
+        for touch in known_touches {
+            if touch.as_ref().same_client_as(surface.as_ref()) {
+                f(touch);
+                if !pending_frame.iter().any(|t| t.as_ref().equals(touch.as_ref())) {
+                    pending_frame.push(touch.clone());
+                }
+            }
+        }
+    }
+
+    fn with_grab<F>(&mut self, f: F)
+    where
+        F: FnOnce(TouchInnerHandle<'_>, &mut dyn TouchGrab),
+    {
+        let mut grab = ::std::mem::replace(&mut self.grab, GrabStatus::Borrowed);
+        match grab {
+            GrabStatus::Borrowed => panic!("Accessed a touch grab from within a touch grab access."),
+            GrabStatus::Active(_, ref mut handler) => {
+                f(TouchInnerHandle { inner: self }, &mut **handler);
+            }
+            GrabStatus::None => {
+                f(TouchInnerHandle { inner: self }, &mut DefaultGrab);
+            }
+        }
+
+        if let GrabStatus::Borrowed = self.grab {
+            // the grab has not been ended nor replaced, put it back in place
+            self.grab = grab;
+        }
+    }
+}
+
+// Devices without multi-touch capabilities do not report a slot, they are
+// all mapped to the same one
+pub(crate) fn slot_or_default(slot: Option<TouchSlot>) -> TouchSlot {
+    slot.unwrap_or_else(|| TouchSlot::new(0))
+}
+
+/// An handle to a touch handler
+///
+/// It can be cloned and all clones manipulate the same internal state.
+///
+/// This handle gives you access to an interface to send touch events to your
+/// clients.
+///
+/// Each touch point (or slot) keeps the focus it was given when it went down
+/// until it goes up or the sequence is cancelled.
+///
+/// When sending events using this handle, they will be intercepted by a touch
+/// grab if any is active. See the [`TouchGrab`] trait for details.
+#[derive(Clone)]
+pub struct TouchHandle {
+    inner: Rc<RefCell<TouchInternal>>,
+}
+
+impl TouchHandle {
+    pub(crate) fn new_touch(&self, touch: WlTouch) {
+        let mut guard = self.inner.borrow_mut();
+        guard.known_touches.push(touch);
+    }
+
+    /// Change the current grab on this touch device to the provided grab
+    ///
+    /// Overwrites any current grab.
+    pub fn set_grab<G: TouchGrab + 'static>(&self, grab: G, serial: u32) {
+        self.inner.borrow_mut().grab = GrabStatus::Active(serial, Box::new(grab));
+    }
+
+    /// Remove any current grab on this touch device, reseting it to the default behavior
+    pub fn unset_grab(&self) {
+        self.inner.borrow_mut().grab = GrabStatus::None;
+    }
+
+    /// Check if this touch device is currently grabbed with this serial
+    pub fn has_grab(&self, serial: u32) -> bool {
+        let guard = self.inner.borrow_mut();
+        match guard.grab {
+            GrabStatus::Active(s, _) => s == serial,
+            _ => false,
+        }
+    }
+
+    /// Check if this touch device is currently being grabbed
+    pub fn is_grabbed(&self) -> bool {
+        let guard = self.inner.borrow_mut();
+        match guard.grab {
+            GrabStatus::None => false,
+            _ => true,
+        }
+    }
+
+    /// Notify that a new touch point appeared
+    ///
+    /// You provide the new location of the touch point, in the form of:
+    ///
+    /// - The slot of this touch point, as reported by [`TouchDownEvent::slot`](::backend::input::TouchDownEvent::slot)
+    /// - The coordinates of the touch point in the global compositor space
+    /// - The surface under the touch point, and the coordinates of its origin in the
+    ///   global compositor space (or `None` if the touch point is not on top of a client surface).
+    ///
+    /// Outside of a grab, the surface will remain the focus of this touch point until
+    /// it is lifted by [`up`](TouchHandle::up) or the sequence is cancelled.
+    pub fn down(
+        &self,
+        slot: Option<TouchSlot>,
+        location: (f64, f64),
+        focus: Option<(WlSurface, (f64, f64))>,
+        serial: u32,
+        time: u32,
+    ) {
+        let mut inner = self.inner.borrow_mut();
+        let touch_slot = slot_or_default(slot);
+        if !inner.active_slots.contains(&touch_slot) {
+            inner.active_slots.push(touch_slot);
+        }
+        inner.down_serials.insert(touch_slot, serial);
+        inner.with_grab(move |mut handle, grab| {
+            grab.down(&mut handle, slot, location, focus, serial, time);
+        });
+    }
+
+    /// Notify that a touch point moved
+    ///
+    /// You provide the new location of the touch point, in the form of:
+    ///
+    /// - The coordinates of the touch point in the global compositor space
+    /// - The surface now under the touch point, and the coordinates of its origin in the
+    ///   global compositor space (or `None` if the touch point is not on top of a client surface).
+    ///
+    /// Outside of a grab, the event is forwarded to the surface this touch point went
+    /// down on, regardless of the surface currently under it.
+    pub fn motion(
+        &self,
+        slot: Option<TouchSlot>,
+        location: (f64, f64),
+        focus: Option<(WlSurface, (f64, f64))>,
+        time: u32,
+    ) {
+        self.inner.borrow_mut().with_grab(move |mut handle, grab| {
+            grab.motion(&mut handle, slot, location, focus, time);
+        });
+    }
+
+    /// Notify that a touch point was lifted
+    pub fn up(&self, slot: Option<TouchSlot>, serial: u32, time: u32) {
+        let mut inner = self.inner.borrow_mut();
+        let touch_slot = slot_or_default(slot);
+        inner.active_slots.retain(|s| *s != touch_slot);
+        inner.down_serials.remove(&touch_slot);
+        inner.with_grab(move |mut handle, grab| {
+            grab.up(&mut handle, slot, serial, time);
+        });
+    }
+
+    /// Notify the end of a set of touch events that belong together
+    pub fn frame(&self) {
+        self.inner.borrow_mut().with_grab(|mut handle, grab| {
+            grab.frame(&mut handle);
+        });
+    }
+
+    /// Notify that the current touch sequence was cancelled
+    ///
+    /// This is typically used when the compositor recognizes a gesture and takes
+    /// over the touch points.
+    pub fn cancel(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.active_slots.clear();
+        inner.down_serials.clear();
+        inner.with_grab(|mut handle, grab| {
+            grab.cancel(&mut handle);
+        });
+    }
+
+    /// Access the surface the touch point of given slot is focused on, if any
+    ///
+    /// The returned coordinates are the ones of the origin of the surface in the
+    /// global compositor space, as provided to [`down`](TouchHandle::down).
+    pub fn current_focus(&self, slot: Option<TouchSlot>) -> Option<(WlSurface, (f64, f64))> {
+        self.inner.borrow().focus.get(&slot_or_default(slot)).cloned()
+    }
+
+    /// Check if at least one touch point is currently active
+    pub fn is_active(&self) -> bool {
+        !self.inner.borrow().active_slots.is_empty()
+    }
+
+    /// The slots of the touch points currently down, in the order they went down
+    pub fn current_slots(&self) -> Vec<TouchSlot> {
+        self.inner.borrow().active_slots.clone()
+    }
+
+    // The slot of the touch point currently down whose down event had given serial
+    pub(crate) fn slot_of_serial(&self, serial: u32) -> Option<TouchSlot> {
+        self.inner
+            .borrow()
+            .down_serials
+            .iter()
+            .find(|&(_, &s)| s == serial)
+            .map(|(&slot, _)| slot)
+    }
+}
+
+/// A trait to implement a touch grab
+///
+/// In some context, it is necessary to temporarily change the behavior of the touch device.
+/// A typical example would be moving a window by dragging its titlebar with a finger, or a
+/// drag'n'drop operation started by a touch, during which the underlying surfaces no longer
+/// receive classic touch events.
+///
+/// This trait is the interface to intercept regular touch events and change them as needed, its
+/// interface mimics the [`TouchHandle`] interface.
+///
+/// If your logic decides that the grab should end, both [`TouchInnerHandle`] and [`TouchHandle`] have
+/// a method to change it.
+///
+/// When your grab ends (either as you requested it or if it was forcefully cancelled by the server),
+/// the struct implementing this trait will be dropped. As such you should put clean-up logic in the destructor,
+/// rather than trying to guess when the grab will end.
+pub trait TouchGrab {
+    /// A new touch point appeared
+    fn down(
+        &mut self,
+        handle: &mut TouchInnerHandle<'_>,
+        slot: Option<TouchSlot>,
+        location: (f64, f64),
+        focus: Option<(WlSurface, (f64, f64))>,
+        serial: u32,
+        time: u32,
+    );
+    /// A touch point was lifted
+    fn up(&mut self, handle: &mut TouchInnerHandle<'_>, slot: Option<TouchSlot>, serial: u32, time: u32);
+    /// A touch point moved
+    fn motion(
+        &mut self,
+        handle: &mut TouchInnerHandle<'_>,
+        slot: Option<TouchSlot>,
+        location: (f64, f64),
+        focus: Option<(WlSurface, (f64, f64))>,
+        time: u32,
+    );
+    /// A set of touch events was completed
+    fn frame(&mut self, handle: &mut TouchInnerHandle<'_>);
+    /// The touch sequence was cancelled
+    fn cancel(&mut self, handle: &mut TouchInnerHandle<'_>);
+}
+
+/// This inner handle is accessed from inside a touch grab logic, and directly
+/// sends event to the client
+pub struct TouchInnerHandle<'a> {
+    inner: &'a mut TouchInternal,
+}
+
+impl<'a> TouchInnerHandle<'a> {
+    /// Change the current grab on this touch device to the provided grab
+    ///
+    /// Overwrites any current grab.
+    pub fn set_grab<G: TouchGrab + 'static>(&mut self, serial: u32, grab: G) {
+        self.inner.grab = GrabStatus::Active(serial, Box::new(grab));
+    }
+
+    /// Remove any current grab on this touch device, resetting it to the default behavior
+    pub fn unset_grab(&mut self) {
+        self.inner.grab = GrabStatus::None;
+    }
+
+    /// Access the current focus of the touch point of given slot
+    pub fn current_focus(&self, slot: Option<TouchSlot>) -> Option<&(WlSurface, (f64, f64))> {
+        self.inner.focus.get(&slot_or_default(slot))
+    }
+
+    /// A list of the slots of the touch points currently down
+    ///
+    /// This still includes touch points whose events your grab has intercepted
+    /// and not sent to the client.
+    pub fn current_slots(&self) -> &[TouchSlot] {
+        &self.inner.active_slots
+    }
+
+    /// Notify that a new touch point appeared
+    ///
+    /// The provided surface becomes the focus of this touch point and will receive
+    /// the down event.
+    pub fn down(
+        &mut self,
+        slot: Option<TouchSlot>,
+        (x, y): (f64, f64),
+        focus: Option<(WlSurface, (f64, f64))>,
+        serial: u32,
+        time: u32,
+    ) {
+        let slot = slot_or_default(slot);
+        if let Some((surface, (sx, sy))) = focus {
+            self.inner.with_touches_of(&surface, |touch| {
+                touch.down(serial, time, &surface, slot.id() as i32, x - sx, y - sy);
+            });
+            self.inner.focus.insert(slot, (surface, (sx, sy)));
+        } else {
+            self.inner.focus.remove(&slot);
+        }
+    }
+
+    /// Notify that a touch point moved
+    ///
+    /// The event is sent to the surface this touch point is focused on, if any.
+    pub fn motion(&mut self, slot: Option<TouchSlot>, (x, y): (f64, f64), time: u32) {
+        let slot = slot_or_default(slot);
+        if let Some((surface, (sx, sy))) = self.inner.focus.get(&slot).cloned() {
+            self.inner.with_touches_of(&surface, |touch| {
+                touch.motion(time, slot.id() as i32, x - sx, y - sy);
+            });
+        }
+    }
+
+    /// Notify that a touch point was lifted
+    ///
+    /// This releases the focus associated with this touch point.
+    pub fn up(&mut self, slot: Option<TouchSlot>, serial: u32, time: u32) {
+        let slot = slot_or_default(slot);
+        if let Some((surface, _)) = self.inner.focus.remove(&slot) {
+            self.inner.with_touches_of(&surface, |touch| {
+                touch.up(serial, time, slot.id() as i32);
+            });
+        }
+    }
+
+    /// Notify the end of a set of touch events that belong together
+    ///
+    /// This sends a frame event to all clients that received touch events
+    /// since the last frame.
+    pub fn frame(&mut self) {
+        for touch in self.inner.pending_frame.drain(..) {
+            if touch.as_ref().is_alive() {
+                touch.frame();
+            }
+        }
+    }
+
+    /// Notify that the current touch sequence was cancelled
+    ///
+    /// All focused touch points are released and their clients are notified that
+    /// they should discard the sequence.
+    pub fn cancel(&mut self) {
+        let surfaces = self
+            .inner
+            .focus
+            .drain()
+            .map(|(_, (surface, _))| surface)
+            .collect::<Vec<_>>();
+        let TouchInternal {
+            ref known_touches,
+            ref mut pending_frame,
+            ..
+        } = *self.inner;
+        for touch in known_touches {
+            if surfaces
+                .iter()
+                .any(|surface| touch.as_ref().same_client_as(surface.as_ref()))
+            {
+                touch.cancel();
+            }
+        }
+        // cancel ends the sequence, no frame should follow it
+        pending_frame.clear();
+    }
+}
+
+pub(crate) fn create_touch_handler() -> TouchHandle {
+    TouchHandle {
+        inner: Rc::new(RefCell::new(TouchInternal::new())),
+    }
+}
+
+pub(crate) fn implement_touch(new_touch: NewResource<WlTouch>, handle: Option<&TouchHandle>) -> WlTouch {
+    let destructor = match handle {
+        Some(h) => {
+            let inner = h.inner.clone();
+            Some(move |touch: WlTouch| {
+                let mut guard = inner.borrow_mut();
+                guard
+                    .known_touches
+                    .retain(|t| !t.as_ref().equals(&touch.as_ref()));
+                guard
+                    .pending_frame
+                    .retain(|t| !t.as_ref().equals(&touch.as_ref()));
+            })
+        }
+        None => None,
+    };
+    new_touch.implement_closure(
+        |request, _touch| {
+            match request {
+                Request::Release => {
+                    // Our destructors already handle it
+                }
+                _ => unreachable!(),
+            }
+        },
+        destructor,
+        (),
+    )
+}
+
+/*
+ * Grabs definition
+ */
+
+// The default grab, the behavior when no particular grab is in progress
+struct DefaultGrab;
+
+impl TouchGrab for DefaultGrab {
+    fn down(
+        &mut self,
+        handle: &mut TouchInnerHandle<'_>,
+        slot: Option<TouchSlot>,
+        location: (f64, f64),
+        focus: Option<(WlSurface, (f64, f64))>,
+        serial: u32,
+        time: u32,
+    ) {
+        handle.down(slot, location, focus, serial, time);
+        handle.set_grab(serial, TouchDownGrab);
+    }
+    fn up(&mut self, handle: &mut TouchInnerHandle<'_>, slot: Option<TouchSlot>, serial: u32, time: u32) {
+        handle.up(slot, serial, time);
+    }
+    fn motion(
+        &mut self,
+        handle: &mut TouchInnerHandle<'_>,
+        slot: Option<TouchSlot>,
+        location: (f64, f64),
+        _focus: Option<(WlSurface, (f64, f64))>,
+        time: u32,
+    ) {
+        handle.motion(slot, location, time);
+    }
+    fn frame(&mut self, handle: &mut TouchInnerHandle<'_>) {
+        handle.frame();
+    }
+    fn cancel(&mut self, handle: &mut TouchInnerHandle<'_>) {
+        handle.cancel();
+    }
+}
+
+// A touch down grab, basic grab started when an user touches a surface,
+// so that the serial of the down event can be used by the client to start
+// an interactive move or a drag'n'drop.
+//
+// In case the user maintains several simultaneous touch points, release
+// the grab once all are lifted.
+struct TouchDownGrab;
+
+impl TouchGrab for TouchDownGrab {
+    fn down(
+        &mut self,
+        handle: &mut TouchInnerHandle<'_>,
+        slot: Option<TouchSlot>,
+        location: (f64, f64),
+        focus: Option<(WlSurface, (f64, f64))>,
+        serial: u32,
+        time: u32,
+    ) {
+        handle.down(slot, location, focus, serial, time);
+    }
+    fn up(&mut self, handle: &mut TouchInnerHandle<'_>, slot: Option<TouchSlot>, serial: u32, time: u32) {
+        handle.up(slot, serial, time);
+        if handle.current_slots().is_empty() {
+            // no more touch points are down, release the grab
+            handle.unset_grab();
+        }
+    }
+    fn motion(
+        &mut self,
+        handle: &mut TouchInnerHandle<'_>,
+        slot: Option<TouchSlot>,
+        location: (f64, f64),
+        _focus: Option<(WlSurface, (f64, f64))>,
+        time: u32,
+    ) {
+        handle.motion(slot, location, time);
+    }
+    fn frame(&mut self, handle: &mut TouchInnerHandle<'_>) {
+        handle.frame();
+    }
+    fn cancel(&mut self, handle: &mut TouchInnerHandle<'_>) {
+        handle.cancel();
+        handle.unset_grab();
+    }
+}
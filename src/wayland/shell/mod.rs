@@ -1,2 +1,3 @@
 pub mod legacy;
+pub mod wlr_layer;
 pub mod xdg;
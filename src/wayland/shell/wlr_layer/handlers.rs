@@ -0,0 +1,309 @@
+use wayland_protocols::wlr::unstable::layer_shell::v1::server::{zwlr_layer_shell_v1, zwlr_layer_surface_v1};
+use wayland_server::{
+    protocol::{wl_output, wl_surface},
+    NewResource,
+};
+
+use crate::wayland::compositor::roles::Role;
+
+use super::{Anchor, LayerShellRequest, LayerSurface, LayerSurfaceRole, LayerSurfaceState, ShellData};
+
+pub(crate) fn implement_layer_shell<U, R>(
+    shell: NewResource<zwlr_layer_shell_v1::ZwlrLayerShellV1>,
+    shell_data: &ShellData<U, R>,
+) -> zwlr_layer_shell_v1::ZwlrLayerShellV1
+where
+    U: 'static,
+    R: Role<LayerSurfaceRole> + 'static,
+{
+    let shell_data = shell_data.clone();
+    shell.implement_closure(
+        move |request, shell| match request {
+            zwlr_layer_shell_v1::Request::GetLayerSurface {
+                id,
+                surface,
+                output,
+                layer,
+                namespace,
+            } => {
+                let role_data = LayerSurfaceRole {
+                    layer,
+                    namespace: namespace.clone(),
+                    pending_configures: Vec::new(),
+                    configured: false,
+                };
+                let token = shell_data.compositor_token;
+                if token.has_role::<LayerSurfaceRole>(&surface) {
+                    // the surface keeps its role when its layer surface is destroyed, it can
+                    // be reused with a fresh state as long as no layer surface is alive for it
+                    let constructed = shell_data
+                        .shell_state
+                        .lock()
+                        .unwrap()
+                        .known_layers
+                        .iter()
+                        .any(|other| other.alive() && other.wl_surface.as_ref().equals(surface.as_ref()));
+                    if constructed {
+                        shell.as_ref().post_error(
+                            zwlr_layer_shell_v1::Error::Role as u32,
+                            "Surface already has a layer surface.".into(),
+                        );
+                        return;
+                    }
+                    token
+                        .with_role_data::<LayerSurfaceRole, _, _>(&surface, |data| *data = role_data)
+                        .expect("surface has the layer_surface role but no role data?!");
+                    token.with_pending_state::<LayerSurfaceState, _, _>(&surface, |state| {
+                        *state = Default::default()
+                    });
+                    token.with_current_state::<LayerSurfaceState, _, _>(&surface, |state| {
+                        *state = Default::default()
+                    });
+                } else if token.give_role_with(&surface, role_data).is_ok() {
+                    // the role is kept for the whole lifetime of the surface, and so is this hook
+                    let hook_data = shell_data.clone();
+                    token.add_pre_commit_hook(&surface, move |surface| {
+                        check_pending_state(&hook_data, surface)
+                    });
+                } else {
+                    shell.as_ref().post_error(
+                        zwlr_layer_shell_v1::Error::Role as u32,
+                        "Surface already has a role.".into(),
+                    );
+                    return;
+                }
+                let layer_surface = id.implement_closure(
+                    layer_surface_implementation::<U, R>,
+                    Some(destroy_layer_surface::<U, R>),
+                    LayerSurfaceUserData {
+                        shell_data: shell_data.clone(),
+                        wl_surface: surface,
+                        output: output.clone(),
+                    },
+                );
+
+                trace!(shell_data.log, "New layer surface"; "namespace" => &namespace);
+                shell_data
+                    .shell_state
+                    .lock()
+                    .unwrap()
+                    .known_layers
+                    .push(make_layer_handle(&layer_surface));
+
+                let handle = make_layer_handle(&layer_surface);
+                let mut user_impl = shell_data.user_impl.borrow_mut();
+                (&mut *user_impl)(LayerShellRequest::NewLayerSurface {
+                    surface: handle,
+                    output,
+                    layer,
+                    namespace,
+                });
+            }
+            _ => unreachable!(),
+        },
+        None::<fn(_)>,
+        (),
+    )
+}
+
+// Post a protocol error if the state about to be committed by a layer surface is invalid
+fn check_pending_state<U, R>(shell_data: &ShellData<U, R>, surface: &wl_surface::WlSurface)
+where
+    U: 'static,
+    R: Role<LayerSurfaceRole> + 'static,
+{
+    let layer_surface = shell_data
+        .shell_state
+        .lock()
+        .unwrap()
+        .known_layers
+        .iter()
+        .find(|layer| layer.alive() && layer.wl_surface.as_ref().equals(surface.as_ref()))
+        .map(|layer| layer.shell_surface.clone());
+    let layer_surface = match layer_surface {
+        Some(layer_surface) => layer_surface,
+        // the layer surface of this surface was destroyed
+        None => return,
+    };
+    let pending = shell_data
+        .compositor_token
+        .with_pending_state::<LayerSurfaceState, _, _>(surface, |state| *state);
+    if pending.size.0 == 0 && !pending.anchor.contains(Anchor::Left | Anchor::Right) {
+        layer_surface.as_ref().post_error(
+            zwlr_layer_surface_v1::Error::InvalidSize as u32,
+            "Width is 0 but the surface is not anchored to both the left and right edges.".into(),
+        );
+    } else if pending.size.1 == 0 && !pending.anchor.contains(Anchor::Top | Anchor::Bottom) {
+        layer_surface.as_ref().post_error(
+            zwlr_layer_surface_v1::Error::InvalidSize as u32,
+            "Height is 0 but the surface is not anchored to both the top and bottom edges.".into(),
+        );
+    }
+}
+
+/*
+ * zwlr_layer_surface_v1
+ */
+
+pub(crate) struct LayerSurfaceUserData<U, R> {
+    shell_data: ShellData<U, R>,
+    wl_surface: wl_surface::WlSurface,
+    pub(crate) output: Option<wl_output::WlOutput>,
+}
+
+fn make_layer_handle<U: 'static, R: 'static>(
+    resource: &zwlr_layer_surface_v1::ZwlrLayerSurfaceV1,
+) -> LayerSurface<U, R> {
+    let data = resource
+        .as_ref()
+        .user_data::<LayerSurfaceUserData<U, R>>()
+        .unwrap();
+    LayerSurface {
+        wl_surface: data.wl_surface.clone(),
+        shell_surface: resource.clone(),
+        token: data.shell_data.compositor_token,
+    }
+}
+
+// Utility function allowing to factor out the handling of the double-buffered state
+fn with_pending_state<U, R, F>(layer_surface: &zwlr_layer_surface_v1::ZwlrLayerSurfaceV1, f: F)
+where
+    U: 'static,
+    R: Role<LayerSurfaceRole> + 'static,
+    F: FnOnce(&mut LayerSurfaceState),
+{
+    let data = layer_surface
+        .as_ref()
+        .user_data::<LayerSurfaceUserData<U, R>>()
+        .unwrap();
+    data.shell_data
+        .compositor_token
+        .with_pending_state(&data.wl_surface, f);
+}
+
+fn layer_surface_implementation<U, R>(
+    request: zwlr_layer_surface_v1::Request,
+    layer_surface: zwlr_layer_surface_v1::ZwlrLayerSurfaceV1,
+) where
+    U: 'static,
+    R: Role<LayerSurfaceRole> + 'static,
+{
+    let data = layer_surface
+        .as_ref()
+        .user_data::<LayerSurfaceUserData<U, R>>()
+        .unwrap();
+    match request {
+        zwlr_layer_surface_v1::Request::SetSize { width, height } => {
+            with_pending_state::<U, R, _>(&layer_surface, |state| {
+                state.size = (width, height);
+            });
+        }
+        zwlr_layer_surface_v1::Request::SetAnchor { anchor } => {
+            // invalid anchor bits are already rejected when parsing the request
+            with_pending_state::<U, R, _>(&layer_surface, |state| {
+                state.anchor = anchor;
+            });
+        }
+        zwlr_layer_surface_v1::Request::SetExclusiveZone { zone } => {
+            with_pending_state::<U, R, _>(&layer_surface, |state| {
+                state.exclusive_zone = zone;
+            });
+        }
+        zwlr_layer_surface_v1::Request::SetMargin {
+            top,
+            right,
+            bottom,
+            left,
+        } => {
+            with_pending_state::<U, R, _>(&layer_surface, |state| {
+                state.margin = super::Margins {
+                    top,
+                    right,
+                    bottom,
+                    left,
+                };
+            });
+        }
+        zwlr_layer_surface_v1::Request::SetKeyboardInteractivity {
+            keyboard_interactivity,
+        } => {
+            with_pending_state::<U, R, _>(&layer_surface, |state| {
+                state.keyboard_interactivity = keyboard_interactivity != 0;
+            });
+        }
+        zwlr_layer_surface_v1::Request::GetPopup { popup } => {
+            let handle = make_layer_handle(&layer_surface);
+            let mut user_impl = data.shell_data.user_impl.borrow_mut();
+            (&mut *user_impl)(LayerShellRequest::NewPopup {
+                parent: handle,
+                popup,
+            });
+        }
+        zwlr_layer_surface_v1::Request::AckConfigure { serial } => {
+            let found = data
+                .shell_data
+                .compositor_token
+                .with_role_data::<LayerSurfaceRole, _, _>(&data.wl_surface, |role_data| {
+                    let mut found = false;
+                    role_data.pending_configures.retain(|&s| {
+                        if s == serial {
+                            found = true;
+                        }
+                        s > serial
+                    });
+                    if found {
+                        role_data.configured = true;
+                    }
+                    found
+                })
+                .expect("layer_surface exists but surface has not layer_surface role?!");
+            if !found {
+                // client responded to a non-existing configure
+                layer_surface.as_ref().post_error(
+                    zwlr_layer_surface_v1::Error::InvalidSurfaceState as u32,
+                    format!("Wrong configure serial: {}", serial),
+                );
+                return;
+            }
+            let handle = make_layer_handle(&layer_surface);
+            let mut user_impl = data.shell_data.user_impl.borrow_mut();
+            (&mut *user_impl)(LayerShellRequest::AckConfigure {
+                surface: handle,
+                serial,
+            });
+        }
+        zwlr_layer_surface_v1::Request::Destroy => {
+            // all is handled by our destructor
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn destroy_layer_surface<U, R>(layer_surface: zwlr_layer_surface_v1::ZwlrLayerSurfaceV1)
+where
+    U: 'static,
+    R: Role<LayerSurfaceRole> + 'static,
+{
+    let data = layer_surface
+        .as_ref()
+        .user_data::<LayerSurfaceUserData<U, R>>()
+        .unwrap();
+    if data.wl_surface.as_ref().is_alive() {
+        // the surface keeps its role, but will need to be configured again
+        // if a new layer surface is created for it
+        data.shell_data
+            .compositor_token
+            .with_role_data::<LayerSurfaceRole, _, _>(&data.wl_surface, |data| {
+                data.pending_configures.clear();
+                data.configured = false;
+            })
+            .expect("layer_surface exists but surface has not layer_surface role?!");
+    }
+    // remove this surface from the known ones (as well as any leftover dead surface)
+    data.shell_data
+        .shell_state
+        .lock()
+        .unwrap()
+        .known_layers
+        .retain(|other| other.alive());
+}
@@ -0,0 +1,785 @@
+//! Utilities for handling shell surfaces with the `wlr_layer_shell` protocol
+//!
+//! This protocol allows clients to create surfaces attached to the layers of an output,
+//! below or above the regular windows. It is typically used for desktop components such
+//! as panels, docks, notifications, lock screens or wallpapers.
+//!
+//! ## Why use this implementation
+//!
+//! This implementation can track for you the various layer surfaces defined by the
+//! clients by handling the `zwlr_layer_shell_v1` protocol.
+//!
+//! It allows you to easily access a list of all layer surfaces defined by your clients,
+//! their double-buffered state and their underlying `wl_surface`s. It also provides some
+//! helpers to compute where the layer surfaces should be placed on their output.
+//!
+//! All the actual drawing and positioning logic of the surfaces remains out of its scope.
+//!
+//! ## How to use it
+//!
+//! ### Initialization
+//!
+//! To initialize this handler, simple use the [`wlr_layer_shell_init`](::wayland::shell::wlr_layer::wlr_layer_shell_init)
+//! function provided in this module. You will need to provide it the [`CompositorToken`](::wayland::compositor::CompositorToken)
+//! you retrieved from an instantiation of the compositor global provided by smithay.
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! # #[macro_use] extern crate smithay;
+//! #
+//! use smithay::wayland::compositor::roles::*;
+//! use smithay::wayland::shell::wlr_layer::{wlr_layer_shell_init, LayerSurfaceRole, LayerShellRequest};
+//!
+//! // define the roles type. You need to integrate the LayerSurface role:
+//! define_roles!(MyRoles =>
+//!     [LayerSurface, LayerSurfaceRole]
+//! );
+//!
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! # let (compositor_token, _, _) = smithay::wayland::compositor::compositor_init::<(), MyRoles, _, _>(
+//! #     &mut display,
+//! #     |_, _, _| {},
+//! #     None
+//! # );
+//! let (shell_state, _) = wlr_layer_shell_init(
+//!     &mut display,
+//!     // token from the compositor implementation
+//!     compositor_token,
+//!     // your implementation
+//!     |event: LayerShellRequest<_, _>| { /* ... */ },
+//!     None  // put a logger if you want
+//! );
+//!
+//! // You're now ready to go!
+//! # }
+//! ```
+//!
+//! ### Surface state and configure sequence
+//!
+//! The anchor, size, margins, exclusive zone and keyboard interactivity of the layer surfaces
+//! are double-buffered: they are stored as a [`LayerSurfaceState`](::wayland::shell::wlr_layer::LayerSurfaceState)
+//! in the state of the `wl_surface`, and are applied with the rest of its state when it is
+//! committed. You can then retrieve them with
+//! [`LayerSurface::get_current_state`](::wayland::shell::wlr_layer::LayerSurface::get_current_state).
+//!
+//! After its first commit, a layer surface expects a configure event providing its size,
+//! which you can send with [`LayerSurface::send_configure`](::wayland::shell::wlr_layer::LayerSurface::send_configure).
+//! To choose this size and the location of the surface, you can use
+//! [`LayerSurfaceState::geometry`](::wayland::shell::wlr_layer::LayerSurfaceState::geometry) and
+//! [`usable_area`](::wayland::shell::wlr_layer::usable_area).
+
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use crate::utils::Rectangle;
+use crate::wayland::compositor::{roles::Role, Cacheable, CompositorToken};
+
+use wayland_protocols::{
+    wlr::unstable::layer_shell::v1::server::{zwlr_layer_shell_v1, zwlr_layer_surface_v1},
+    xdg_shell::server::xdg_popup,
+};
+use wayland_server::{
+    protocol::{wl_output, wl_surface},
+    Display, Global,
+};
+
+// handlers for the wlr_layer_shell protocol
+mod handlers;
+
+pub use wayland_protocols::wlr::unstable::layer_shell::v1::server::{
+    zwlr_layer_shell_v1::Layer, zwlr_layer_surface_v1::Anchor,
+};
+
+/// Metadata associated with the `layer_surface` role
+pub struct LayerSurfaceRole {
+    /// The layer of the output this surface belongs to
+    pub layer: Layer,
+    /// Namespace of the surface, as provided by the client
+    ///
+    /// It defines the purpose of the surface, for example `"panel"` or `"wallpaper"`.
+    pub namespace: String,
+    /// List of non-acked configures pending
+    ///
+    /// Whenever a configure is acked by the client, all configure
+    /// older than it are discarded as well. As such, this `Vec` contains
+    /// the serials of all the configure send to this surface that are
+    /// newer than the last ack received.
+    pub pending_configures: Vec<u32>,
+    /// Has this surface acked at least one configure?
+    ///
+    /// `wlr_layer_shell` defines it as illegal to attach a buffer to a surface
+    /// that has not yet acked a configure.
+    pub configured: bool,
+}
+
+/// Margins of a layer surface, relative to its anchor edges
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Margins {
+    /// Distance to the top edge
+    pub top: i32,
+    /// Distance to the right edge
+    pub right: i32,
+    /// Distance to the bottom edge
+    pub bottom: i32,
+    /// Distance to the left edge
+    pub left: i32,
+}
+
+/// Double-buffered state of a layer surface
+///
+/// It is checked when the surface is committed: a size of 0 on an axis the surface is
+/// not anchored to both edges of is a protocol error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayerSurfaceState {
+    /// Size requested by the client
+    ///
+    /// A value of 0 on an axis means the client lets the compositor choose it, in which
+    /// case the surface is anchored to both edges of this axis.
+    pub size: (u32, u32),
+    /// Edges of the output this surface is anchored to
+    pub anchor: Anchor,
+    /// Size of the area reserved by this surface on its anchor edge
+    ///
+    /// A positive value reserves that much space, a value of 0 asks for the surface to
+    /// be placed in the area left by the other surfaces, and a value of -1 asks for it to
+    /// extend to the edges of the output, ignoring the other surfaces.
+    pub exclusive_zone: i32,
+    /// Margins of the surface from its anchor edges
+    pub margin: Margins,
+    /// Whether this surface wants to receive the keyboard focus
+    pub keyboard_interactivity: bool,
+}
+
+impl Default for LayerSurfaceState {
+    fn default() -> LayerSurfaceState {
+        LayerSurfaceState {
+            size: (0, 0),
+            anchor: Anchor::empty(),
+            exclusive_zone: 0,
+            margin: Margins::default(),
+            keyboard_interactivity: false,
+        }
+    }
+}
+
+impl Cacheable for LayerSurfaceState {
+    fn commit(&mut self) -> LayerSurfaceState {
+        *self
+    }
+
+    fn merge_into(self, into: &mut LayerSurfaceState) {
+        *into = self;
+    }
+}
+
+impl LayerSurfaceState {
+    /// The edge of the output on which this surface reserves an exclusive zone, if any
+    ///
+    /// A surface reserves space if it has a positive exclusive zone and is anchored either
+    /// to a single edge, or to one edge and both of its perpendicular edges.
+    pub fn exclusive_edge(&self) -> Option<Anchor> {
+        if self.exclusive_zone <= 0 {
+            return None;
+        }
+        let horizontal = self.anchor & (Anchor::Left | Anchor::Right);
+        let vertical = self.anchor & (Anchor::Top | Anchor::Bottom);
+        let spans_horizontal = horizontal.is_empty() || horizontal == Anchor::Left | Anchor::Right;
+        let spans_vertical = vertical.is_empty() || vertical == Anchor::Top | Anchor::Bottom;
+        if vertical == Anchor::Top && spans_horizontal {
+            Some(Anchor::Top)
+        } else if vertical == Anchor::Bottom && spans_horizontal {
+            Some(Anchor::Bottom)
+        } else if horizontal == Anchor::Left && spans_vertical {
+            Some(Anchor::Left)
+        } else if horizontal == Anchor::Right && spans_vertical {
+            Some(Anchor::Right)
+        } else {
+            None
+        }
+    }
+
+    /// Compute the geometry of a surface with this state, placed in given area
+    ///
+    /// The area should be the full output for surfaces with an exclusive zone of -1,
+    /// and the [`usable_area`] of the output otherwise.
+    ///
+    /// ```
+    /// # extern crate smithay;
+    /// use smithay::utils::Rectangle;
+    /// use smithay::wayland::shell::wlr_layer::{Anchor, LayerSurfaceState, Margins};
+    ///
+    /// # fn main() {
+    /// let panel = LayerSurfaceState {
+    ///     size: (0, 30),
+    ///     anchor: Anchor::Top | Anchor::Left | Anchor::Right,
+    ///     margin: Margins { top: 5, right: 0, bottom: 0, left: 0 },
+    ///     ..Default::default()
+    /// };
+    /// let geometry = panel.geometry(Rectangle { x: 0, y: 0, width: 1920, height: 1080 });
+    /// assert_eq!((geometry.x, geometry.y, geometry.width, geometry.height), (0, 5, 1920, 30));
+    /// # }
+    /// ```
+    pub fn geometry(&self, area: Rectangle) -> Rectangle {
+        let (x, width) = place_on_axis(
+            area.x,
+            area.width,
+            self.size.0 as i32,
+            self.margin.left,
+            self.margin.right,
+            self.anchor.contains(Anchor::Left),
+            self.anchor.contains(Anchor::Right),
+        );
+        let (y, height) = place_on_axis(
+            area.y,
+            area.height,
+            self.size.1 as i32,
+            self.margin.top,
+            self.margin.bottom,
+            self.anchor.contains(Anchor::Top),
+            self.anchor.contains(Anchor::Bottom),
+        );
+        Rectangle { x, y, width, height }
+    }
+}
+
+// place a surface on one axis, returning its position and size
+fn place_on_axis(
+    start: i32,
+    length: i32,
+    size: i32,
+    margin_start: i32,
+    margin_end: i32,
+    anchor_start: bool,
+    anchor_end: bool,
+) -> (i32, i32) {
+    let size = if size == 0 {
+        length - margin_start - margin_end
+    } else {
+        size
+    };
+    let position = match (anchor_start, anchor_end) {
+        (true, false) => start + margin_start,
+        (false, true) => start + length - margin_end - size,
+        (true, true) => start + margin_start + (length - margin_start - margin_end - size) / 2,
+        (false, false) => start + (length - size) / 2,
+    };
+    (position, size)
+}
+
+/// Compute the area of an output that is not reserved by the exclusive zones of its layer surfaces
+///
+/// The exclusive zone of each surface, plus its margin on its anchor edge, is removed
+/// from the corresponding edge of the output.
+///
+/// ```
+/// # extern crate smithay;
+/// use smithay::utils::Rectangle;
+/// use smithay::wayland::shell::wlr_layer::{usable_area, Anchor, LayerSurfaceState};
+///
+/// # fn main() {
+/// let panel = LayerSurfaceState {
+///     size: (0, 30),
+///     anchor: Anchor::Top | Anchor::Left | Anchor::Right,
+///     exclusive_zone: 30,
+///     ..Default::default()
+/// };
+/// let dock = LayerSurfaceState {
+///     size: (64, 0),
+///     anchor: Anchor::Left | Anchor::Top | Anchor::Bottom,
+///     exclusive_zone: 64,
+///     ..Default::default()
+/// };
+/// let area = usable_area(Rectangle { x: 0, y: 0, width: 1920, height: 1080 }, &[panel, dock]);
+/// assert_eq!((area.x, area.y, area.width, area.height), (64, 30, 1856, 1050));
+/// # }
+/// ```
+pub fn usable_area<'a, I>(output: Rectangle, states: I) -> Rectangle
+where
+    I: IntoIterator<Item = &'a LayerSurfaceState>,
+{
+    let mut area = output;
+    for state in states {
+        let edge = match state.exclusive_edge() {
+            Some(edge) => edge,
+            None => continue,
+        };
+        if edge == Anchor::Top {
+            let reserved = state.exclusive_zone + state.margin.top;
+            area.y += reserved;
+            area.height -= reserved;
+        } else if edge == Anchor::Bottom {
+            area.height -= state.exclusive_zone + state.margin.bottom;
+        } else if edge == Anchor::Left {
+            let reserved = state.exclusive_zone + state.margin.left;
+            area.x += reserved;
+            area.width -= reserved;
+        } else if edge == Anchor::Right {
+            area.width -= state.exclusive_zone + state.margin.right;
+        }
+    }
+    area.width = ::std::cmp::max(area.width, 0);
+    area.height = ::std::cmp::max(area.height, 0);
+    area
+}
+
+pub(crate) struct ShellData<U, R> {
+    log: ::slog::Logger,
+    compositor_token: CompositorToken<U, R>,
+    user_impl: Rc<RefCell<dyn FnMut(LayerShellRequest<U, R>)>>,
+    shell_state: Arc<Mutex<ShellState<U, R>>>,
+}
+
+impl<U, R> Clone for ShellData<U, R> {
+    fn clone(&self) -> Self {
+        ShellData {
+            log: self.log.clone(),
+            compositor_token: self.compositor_token,
+            user_impl: self.user_impl.clone(),
+            shell_state: self.shell_state.clone(),
+        }
+    }
+}
+
+/// A handle to a layer surface
+pub struct LayerSurface<U, R> {
+    wl_surface: wl_surface::WlSurface,
+    shell_surface: zwlr_layer_surface_v1::ZwlrLayerSurfaceV1,
+    token: CompositorToken<U, R>,
+}
+
+impl<U, R> LayerSurface<U, R>
+where
+    U: 'static,
+    R: Role<LayerSurfaceRole> + 'static,
+{
+    /// Is the layer surface referred by this handle still alive?
+    pub fn alive(&self) -> bool {
+        self.shell_surface.as_ref().is_alive() && self.wl_surface.as_ref().is_alive()
+    }
+
+    /// Do this handle and the other one actually refer to the same layer surface?
+    pub fn equals(&self, other: &Self) -> bool {
+        self.alive() && other.alive() && self.wl_surface.as_ref().equals(&other.wl_surface.as_ref())
+    }
+
+    /// Send a configure event to this layer surface to provide it its size
+    ///
+    /// The serial of this configure will be tracked waiting for the client to ACK it.
+    pub fn send_configure(&self, cfg: LayerSurfaceConfigure) {
+        if !self.alive() {
+            return;
+        }
+        self.shell_surface.configure(cfg.serial, cfg.size.0, cfg.size.1);
+        self.token
+            .with_role_data::<LayerSurfaceRole, _, _>(&self.wl_surface, |data| {
+                data.pending_configures.push(cfg.serial)
+            })
+            .expect("A layer surface object exists but the surface does not have the layer_surface role ?!");
+    }
+
+    /// Make sure this surface was configured
+    ///
+    /// Returns `true` if it was, if not, returns `false` and raise
+    /// a protocol error to the associated client. Also returns `false`
+    /// if the surface is already destroyed.
+    ///
+    /// `wlr_layer_shell` mandates that a client acks a configure before attaching
+    /// a buffer to its surface.
+    pub fn ensure_configured(&self) -> bool {
+        if !self.alive() {
+            return false;
+        }
+        let configured = self
+            .token
+            .with_role_data::<LayerSurfaceRole, _, _>(&self.wl_surface, |data| data.configured)
+            .expect("A layer surface object exists but the surface does not have the layer_surface role ?!");
+        if !configured {
+            self.shell_surface.as_ref().post_error(
+                zwlr_layer_surface_v1::Error::InvalidSurfaceState as u32,
+                "Surface has not been configured yet.".into(),
+            );
+        }
+        configured
+    }
+
+    /// Send a "closed" event to the client
+    ///
+    /// The client should destroy the surface, and will not be shown any longer.
+    pub fn send_close(&self) {
+        self.shell_surface.closed()
+    }
+
+    /// Access the underlying `wl_surface` of this layer surface
+    ///
+    /// Returns `None` if the layer surface actually no longer exists.
+    pub fn get_surface(&self) -> Option<&wl_surface::WlSurface> {
+        if self.alive() {
+            Some(&self.wl_surface)
+        } else {
+            None
+        }
+    }
+
+    /// The output this surface was created on, if the client chose one
+    ///
+    /// Returns `None` if the layer surface actually no longer exists.
+    pub fn get_output(&self) -> Option<wl_output::WlOutput> {
+        if !self.alive() {
+            return None;
+        }
+        self.shell_surface
+            .as_ref()
+            .user_data::<self::handlers::LayerSurfaceUserData<U, R>>()
+            .and_then(|data| data.output.clone())
+    }
+
+    /// Retrieve a copy of the current state of this layer surface
+    ///
+    /// Returns `None` if the layer surface actually no longer exists.
+    pub fn get_current_state(&self) -> Option<LayerSurfaceState> {
+        if !self.alive() {
+            return None;
+        }
+        Some(
+            self.token
+                .with_current_state::<LayerSurfaceState, _, _>(&self.wl_surface, |state| *state),
+        )
+    }
+
+    /// Retrieve the layer of this surface
+    ///
+    /// Returns `None` if the layer surface actually no longer exists.
+    pub fn get_layer(&self) -> Option<Layer> {
+        if !self.alive() {
+            return None;
+        }
+        self.token
+            .with_role_data::<LayerSurfaceRole, _, _>(&self.wl_surface, |data| data.layer)
+            .ok()
+    }
+}
+
+/// A configure message for layer surfaces
+pub struct LayerSurfaceConfigure {
+    /// The size chosen for the surface
+    ///
+    /// A value of 0 on an axis means the client is free to choose the size on this axis.
+    pub size: (u32, u32),
+    /// A serial number to track ACK from the client
+    ///
+    /// This should be an ever increasing number, as the ACK-ing
+    /// from a client for a serial will validate all pending lower
+    /// serials.
+    pub serial: u32,
+}
+
+/// Events generated by layer shell surfaces
+///
+/// Depending on what you want to do, you might ignore some of them
+pub enum LayerShellRequest<U, R> {
+    /// A new layer surface was created
+    ///
+    /// It needs to be sent a [`LayerSurfaceConfigure`] after its first commit.
+    NewLayerSurface {
+        /// the surface
+        surface: LayerSurface<U, R>,
+        /// The output the client wants the surface to be shown on
+        ///
+        /// If `None`, the compositor is free to choose it.
+        output: Option<wl_output::WlOutput>,
+        /// The layer of the output the surface is on
+        layer: Layer,
+        /// The namespace of the surface
+        namespace: String,
+    },
+    /// A popup was assigned this layer surface as its parent
+    NewPopup {
+        /// The parent layer surface
+        parent: LayerSurface<U, R>,
+        /// The `xdg_popup` object of the popup
+        popup: xdg_popup::XdgPopup,
+    },
+    /// A layer surface acked a configure
+    AckConfigure {
+        /// The surface
+        surface: LayerSurface<U, R>,
+        /// The serial of the acked configure
+        serial: u32,
+    },
+}
+
+/// Layer shell global state
+///
+/// This state allows you to retrieve a list of surfaces
+/// currently known to the shell global.
+pub struct ShellState<U, R> {
+    known_layers: Vec<LayerSurface<U, R>>,
+}
+
+impl<U, R> ShellState<U, R>
+where
+    U: 'static,
+    R: Role<LayerSurfaceRole> + 'static,
+{
+    /// Access all the layer surfaces known by this handler
+    pub fn layer_surfaces(&self) -> &[LayerSurface<U, R>] {
+        &self.known_layers[..]
+    }
+}
+
+/// Create a new `wlr_layer_shell` global
+pub fn wlr_layer_shell_init<U, R, L, Impl>(
+    display: &mut Display,
+    ctoken: CompositorToken<U, R>,
+    implementation: Impl,
+    logger: L,
+) -> (
+    Arc<Mutex<ShellState<U, R>>>,
+    Global<zwlr_layer_shell_v1::ZwlrLayerShellV1>,
+)
+where
+    U: 'static,
+    R: Role<LayerSurfaceRole> + 'static,
+    L: Into<Option<::slog::Logger>>,
+    Impl: FnMut(LayerShellRequest<U, R>) + 'static,
+{
+    let log = crate::slog_or_stdlog(logger);
+    let shell_state = Arc::new(Mutex::new(ShellState {
+        known_layers: Vec::new(),
+    }));
+
+    let shell_data = ShellData {
+        log: log.new(o!("smithay_module" => "wlr_layer_shell_handler")),
+        compositor_token: ctoken,
+        user_impl: Rc::new(RefCell::new(implementation)),
+        shell_state: shell_state.clone(),
+    };
+
+    let global = display.create_global(1, move |shell, _version| {
+        self::handlers::implement_layer_shell(shell, &shell_data);
+    });
+
+    (shell_state, global)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{define_roles, wayland::compositor::compositor_init};
+    use std::{
+        os::unix::{io::IntoRawFd, net::UnixStream},
+        sync::atomic::{AtomicBool, Ordering},
+        thread,
+        time::Duration,
+    };
+    use wayland_client::{protocol::wl_compositor::WlCompositor, GlobalManager};
+    use wayland_protocols::wlr::unstable::layer_shell::v1::client::{
+        zwlr_layer_shell_v1::{self as client_shell, ZwlrLayerShellV1},
+        zwlr_layer_surface_v1::Anchor as ClientAnchor,
+    };
+    use wayland_server::calloop::EventLoop;
+
+    define_roles!(Roles => [LayerSurface, LayerSurfaceRole]);
+
+    struct Outcome {
+        // layer and namespace of the new layer surfaces
+        surfaces: Vec<(Layer, String)>,
+        // current state of the layer surfaces alive at the end
+        states: Vec<LayerSurfaceState>,
+        // protocol error of the client
+        error: Option<u32>,
+    }
+
+    // Run a compositor providing the layer shell until the client running in its own thread returns
+    fn run_client<F>(client: F) -> Outcome
+    where
+        F: FnOnce(&ZwlrLayerShellV1, &WlCompositor, &mut wayland_client::EventQueue) + Send + 'static,
+    {
+        let mut event_loop = EventLoop::<()>::new().unwrap();
+        let mut display = Display::new(event_loop.handle());
+        let (token, _, _) = compositor_init::<(), Roles, _, _>(&mut display, |_, _, _| {}, None);
+        let surfaces = Rc::new(RefCell::new(Vec::new()));
+        let seen = surfaces.clone();
+        let (shell_state, _global) = wlr_layer_shell_init(
+            &mut display,
+            token,
+            move |request| {
+                if let LayerShellRequest::NewLayerSurface {
+                    surface, namespace, ..
+                } = request
+                {
+                    // read back the role data, to check it matches the new layer surface
+                    let layer = surface.get_layer().unwrap();
+                    seen.borrow_mut().push((layer, namespace));
+                }
+            },
+            None,
+        );
+
+        let (server_end, client_end) = UnixStream::pair().unwrap();
+        let _client = unsafe { display.create_client(server_end.into_raw_fd()) };
+        let done = Arc::new(AtomicBool::new(false));
+        let client_done = done.clone();
+        let client_thread = thread::spawn(move || {
+            let (client_display, mut queue) =
+                unsafe { wayland_client::Display::from_fd(client_end.into_raw_fd()) }.unwrap();
+            let globals = GlobalManager::new(&client_display);
+            queue.sync_roundtrip().unwrap();
+            let compositor = globals
+                .instantiate_exact::<WlCompositor, _>(1, |compositor| compositor.implement_dummy())
+                .unwrap();
+            let shell = globals
+                .instantiate_exact::<ZwlrLayerShellV1, _>(1, |shell| shell.implement_dummy())
+                .unwrap();
+            client(&shell, &compositor, &mut queue);
+            let _ = queue.sync_roundtrip();
+            client_done.store(true, Ordering::SeqCst);
+            client_display.protocol_error().map(|error| error.code)
+        });
+
+        while !done.load(Ordering::SeqCst) {
+            event_loop
+                .dispatch(Some(Duration::from_millis(10)), &mut ())
+                .unwrap();
+            display.flush_clients();
+        }
+        let error = client_thread.join().unwrap();
+        let states = shell_state
+            .lock()
+            .unwrap()
+            .layer_surfaces()
+            .iter()
+            .filter_map(|layer| layer.get_current_state())
+            .collect();
+        let surfaces = surfaces.borrow().clone();
+        Outcome {
+            surfaces,
+            states,
+            error,
+        }
+    }
+
+    #[test]
+    fn layer_surface_can_be_recreated() {
+        let outcome = run_client(|shell, compositor, queue| {
+            let surface = compositor
+                .create_surface(|surface| surface.implement_dummy())
+                .unwrap();
+            let layer_surface = shell
+                .get_layer_surface(
+                    &surface,
+                    None,
+                    client_shell::Layer::Top,
+                    "panel".into(),
+                    |layer| layer.implement_dummy(),
+                )
+                .unwrap();
+            layer_surface.set_size(10, 10);
+            surface.commit();
+            queue.sync_roundtrip().unwrap();
+            layer_surface.destroy();
+            shell
+                .get_layer_surface(
+                    &surface,
+                    None,
+                    client_shell::Layer::Bottom,
+                    "dock".into(),
+                    |layer| layer.implement_dummy(),
+                )
+                .unwrap();
+        });
+        assert_eq!(outcome.error, None);
+        assert_eq!(
+            outcome.surfaces,
+            vec![(Layer::Top, "panel".into()), (Layer::Bottom, "dock".into())]
+        );
+        // the state of the previous layer surface was reset
+        assert_eq!(outcome.states, vec![LayerSurfaceState::default()]);
+    }
+
+    #[test]
+    fn layer_surface_is_not_constructed_twice() {
+        let outcome = run_client(|shell, compositor, _| {
+            let surface = compositor
+                .create_surface(|surface| surface.implement_dummy())
+                .unwrap();
+            for namespace in &["panel", "dock"] {
+                shell
+                    .get_layer_surface(
+                        &surface,
+                        None,
+                        client_shell::Layer::Top,
+                        namespace.to_string(),
+                        |layer| layer.implement_dummy(),
+                    )
+                    .unwrap();
+            }
+        });
+        assert_eq!(outcome.error, Some(zwlr_layer_shell_v1::Error::Role as u32));
+        assert_eq!(outcome.surfaces, vec![(Layer::Top, "panel".into())]);
+    }
+
+    #[test]
+    fn layer_state_is_applied_on_commit() {
+        let outcome = run_client(|shell, compositor, _| {
+            let surface = compositor
+                .create_surface(|surface| surface.implement_dummy())
+                .unwrap();
+            let layer_surface = shell
+                .get_layer_surface(
+                    &surface,
+                    None,
+                    client_shell::Layer::Top,
+                    "panel".into(),
+                    |layer| layer.implement_dummy(),
+                )
+                .unwrap();
+            layer_surface.set_size(0, 30);
+            layer_surface.set_anchor(ClientAnchor::Top | ClientAnchor::Left | ClientAnchor::Right);
+            layer_surface.set_exclusive_zone(30);
+            surface.commit();
+            // not committed
+            layer_surface.set_size(100, 30);
+        });
+        assert_eq!(outcome.error, None);
+        assert_eq!(
+            outcome.states,
+            vec![LayerSurfaceState {
+                size: (0, 30),
+                anchor: Anchor::Top | Anchor::Left | Anchor::Right,
+                exclusive_zone: 30,
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_size_is_rejected_on_commit() {
+        let outcome = run_client(|shell, compositor, _| {
+            let surface = compositor
+                .create_surface(|surface| surface.implement_dummy())
+                .unwrap();
+            let layer_surface = shell
+                .get_layer_surface(
+                    &surface,
+                    None,
+                    client_shell::Layer::Top,
+                    "panel".into(),
+                    |layer| layer.implement_dummy(),
+                )
+                .unwrap();
+            layer_surface.set_size(0, 30);
+            layer_surface.set_anchor(ClientAnchor::Top);
+            surface.commit();
+        });
+        assert_eq!(
+            outcome.error,
+            Some(zwlr_layer_surface_v1::Error::InvalidSize as u32)
+        );
+    }
+}
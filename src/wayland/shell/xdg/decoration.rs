@@ -0,0 +1,227 @@
+//! Utilities for handling the `zxdg_decoration_manager_v1` protocol
+//!
+//! This protocol allows the compositor and the clients to negotiate whether the
+//! decorations of the toplevel surfaces (titlebars, borders...) should be drawn by
+//! the client or by the compositor.
+//!
+//! ## How to use it
+//!
+//! Initialize the global with [`init_xdg_decoration_manager`], providing a callback that
+//! will receive the [`XdgDecorationRequest`]s of the clients. Whenever a client creates a
+//! decoration object or changes the mode it prefers, you need to choose the decoration mode
+//! of the toplevel surface and send it as part of its configure sequence, using the
+//! `decoration_mode` field of the [`ToplevelConfigure`](super::ToplevelConfigure).
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! # #[macro_use] extern crate smithay;
+//! use smithay::wayland::compositor::roles::*;
+//! use smithay::wayland::shell::xdg::{ToplevelConfigure, XdgSurfaceRole};
+//! use smithay::wayland::shell::xdg::decoration::{
+//!     init_xdg_decoration_manager, Mode, XdgDecorationRequest,
+//! };
+//!
+//! # define_roles!(MyRoles => [XdgSurface, XdgSurfaceRole]);
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! let decoration_global = init_xdg_decoration_manager::<(), MyRoles, (), _, _>(
+//!     &mut display,
+//!     |request| match request {
+//!         // this compositor always draws the decorations itself
+//!         XdgDecorationRequest::NewToplevelDecoration { toplevel }
+//!         | XdgDecorationRequest::SetMode { toplevel, .. }
+//!         | XdgDecorationRequest::UnsetMode { toplevel } => {
+//!             toplevel.send_configure(ToplevelConfigure {
+//!                 size: None,
+//!                 states: vec![],
+//!                 serial: 42,
+//!                 decoration_mode: Some(Mode::ServerSide),
+//!             });
+//!         }
+//!     },
+//!     None // put a logger here
+//! );
+//! # }
+//! ```
+
+use std::{cell::RefCell, rc::Rc};
+
+use wayland_protocols::{
+    unstable::xdg_decoration::v1::server::{
+        zxdg_decoration_manager_v1::{self, ZxdgDecorationManagerV1},
+        zxdg_toplevel_decoration_v1::{self, ZxdgToplevelDecorationV1},
+    },
+    xdg_shell::server::xdg_toplevel::XdgToplevel,
+};
+use wayland_server::{Display, Global, NewResource};
+
+use crate::wayland::compositor::{roles::Role, SurfaceState};
+
+use super::{
+    xdg_handlers::{make_toplevel_handle, ShellSurfaceUserData},
+    ToplevelSurface, XdgSurfaceRole,
+};
+
+pub use wayland_protocols::unstable::xdg_decoration::v1::server::zxdg_toplevel_decoration_v1::Mode;
+
+/// Events generated by the decoration objects of the clients
+pub enum XdgDecorationRequest<U, R, SD> {
+    /// A client created a decoration object for this toplevel surface
+    ///
+    /// You should send it a configure with the decoration mode you chose for it.
+    NewToplevelDecoration {
+        /// The toplevel surface
+        toplevel: ToplevelSurface<U, R, SD>,
+    },
+    /// The client prefers the decorations of this toplevel surface to be in given mode
+    ///
+    /// You are free to ignore this preference, but you need to answer by sending a
+    /// configure with the decoration mode you chose for the surface.
+    SetMode {
+        /// The toplevel surface
+        toplevel: ToplevelSurface<U, R, SD>,
+        /// The decoration mode requested by the client
+        mode: Mode,
+    },
+    /// The client no longer has a preference regarding the decoration mode of this
+    /// toplevel surface
+    ///
+    /// You need to answer by sending a configure with the decoration mode you chose
+    /// for the surface.
+    UnsetMode {
+        /// The toplevel surface
+        toplevel: ToplevelSurface<U, R, SD>,
+    },
+}
+
+/// Create a new `zxdg_decoration_manager_v1` global
+///
+/// The decoration objects are attached to the toplevel surfaces of the `xdg_wm_base`
+/// global created by [`xdg_shell_init`](super::xdg_shell_init), so the type parameters
+/// must match the ones used to initialize it.
+///
+/// The global is directly created on the provided [`Display`](wayland_server::Display),
+/// and this function returns the global handle, in case you wish to remove this global in
+/// the future.
+pub fn init_xdg_decoration_manager<U, R, SD, L, Impl>(
+    display: &mut Display,
+    implementation: Impl,
+    logger: L,
+) -> Global<ZxdgDecorationManagerV1>
+where
+    U: 'static,
+    R: Role<XdgSurfaceRole> + 'static,
+    SD: 'static,
+    L: Into<Option<::slog::Logger>>,
+    Impl: FnMut(XdgDecorationRequest<U, R, SD>) + 'static,
+{
+    let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "xdg_decoration_handler"));
+    let implementation = Rc::new(RefCell::new(implementation));
+
+    display.create_global::<ZxdgDecorationManagerV1, _>(1, move |new_manager, _version| {
+        let implementation = implementation.clone();
+        let log = log.clone();
+        new_manager.implement_closure(
+            move |request, manager| match request {
+                zxdg_decoration_manager_v1::Request::GetToplevelDecoration { id, toplevel } => {
+                    let data = toplevel
+                        .as_ref()
+                        .user_data::<ShellSurfaceUserData<U, R, SD>>()
+                        .unwrap();
+                    if data.decoration.borrow().is_some() {
+                        manager.as_ref().post_error(
+                            zxdg_toplevel_decoration_v1::Error::AlreadyConstructed as u32,
+                            "This toplevel already has a decoration object.".into(),
+                        );
+                        return;
+                    }
+                    // the decoration mode must be negotiated before the toplevel gets any content
+                    let token = data.shell_data.compositor_token;
+                    let has_buffer =
+                        token.with_pending_state(&data.wl_surface, |state: &mut SurfaceState| {
+                            match state.buffer {
+                                Some(Some(_)) => true,
+                                _ => false,
+                            }
+                        }) || token.with_surface_data(&data.wl_surface, |attributes| {
+                            match attributes.buffer {
+                                Some(Some(_)) => true,
+                                _ => false,
+                            }
+                        });
+                    if has_buffer {
+                        manager.as_ref().post_error(
+                            zxdg_toplevel_decoration_v1::Error::UnconfiguredBuffer as u32,
+                            "This toplevel already has a buffer attached.".into(),
+                        );
+                        return;
+                    }
+                    let decoration =
+                        implement_decoration::<U, R, SD, Impl>(id, toplevel.clone(), implementation.clone());
+                    *data.decoration.borrow_mut() = Some(decoration);
+                    trace!(log, "New toplevel decoration");
+                    let handle = make_toplevel_handle::<U, R, SD>(&toplevel);
+                    let mut user_impl = implementation.borrow_mut();
+                    (&mut *user_impl)(XdgDecorationRequest::NewToplevelDecoration { toplevel: handle });
+                }
+                zxdg_decoration_manager_v1::Request::Destroy => {
+                    // nothing to do
+                }
+                _ => unreachable!(),
+            },
+            None::<fn(_)>,
+            (),
+        );
+    })
+}
+
+fn implement_decoration<U, R, SD, Impl>(
+    decoration: NewResource<ZxdgToplevelDecorationV1>,
+    toplevel: XdgToplevel,
+    implementation: Rc<RefCell<Impl>>,
+) -> ZxdgToplevelDecorationV1
+where
+    U: 'static,
+    R: Role<XdgSurfaceRole> + 'static,
+    SD: 'static,
+    Impl: FnMut(XdgDecorationRequest<U, R, SD>) + 'static,
+{
+    decoration.implement_closure(
+        move |request, decoration| {
+            let toplevel = decoration.as_ref().user_data::<XdgToplevel>().unwrap();
+            if !toplevel.as_ref().is_alive() {
+                // the orphaned decoration is inert
+                return;
+            }
+            let handle = make_toplevel_handle::<U, R, SD>(toplevel);
+            let mut user_impl = implementation.borrow_mut();
+            match request {
+                zxdg_toplevel_decoration_v1::Request::SetMode { mode } => {
+                    (&mut *user_impl)(XdgDecorationRequest::SetMode {
+                        toplevel: handle,
+                        mode,
+                    });
+                }
+                zxdg_toplevel_decoration_v1::Request::UnsetMode => {
+                    (&mut *user_impl)(XdgDecorationRequest::UnsetMode { toplevel: handle });
+                }
+                zxdg_toplevel_decoration_v1::Request::Destroy => {
+                    // our destructor already handles it
+                }
+                _ => unreachable!(),
+            }
+        },
+        Some(|decoration: ZxdgToplevelDecorationV1| {
+            let toplevel = decoration.as_ref().user_data::<XdgToplevel>().unwrap();
+            if toplevel.as_ref().is_alive() {
+                let data = toplevel
+                    .as_ref()
+                    .user_data::<ShellSurfaceUserData<U, R, SD>>()
+                    .unwrap();
+                *data.decoration.borrow_mut() = None;
+            }
+        }),
+        toplevel,
+    )
+}
@@ -110,6 +110,12 @@ use wayland_server::{
 mod xdg_handlers;
 // compatibility handlers for the zxdg_shell_v6 protocol, its earlier version
 mod zxdgv6_handlers;
+// server-side decoration negotiation
+pub mod decoration;
+// ready-made popup grab
+mod popup_grab;
+
+pub use self::popup_grab::PopupGrabError;
 
 /// Metadata associated with the `xdg_surface` role
 pub struct XdgSurfaceRole {
@@ -638,6 +644,7 @@ where
     }
 }
 
+#[derive(Clone)]
 pub(crate) enum PopupKind {
     Xdg(xdg_popup::XdgPopup),
     ZxdgV6(zxdg_popup_v6::ZxdgPopupV6),
@@ -654,6 +661,17 @@ pub struct PopupSurface<U, R, SD> {
     _shell_data: ::std::marker::PhantomData<SD>,
 }
 
+impl<U, R, SD> Clone for PopupSurface<U, R, SD> {
+    fn clone(&self) -> Self {
+        PopupSurface {
+            wl_surface: self.wl_surface.clone(),
+            shell_surface: self.shell_surface.clone(),
+            token: self.token,
+            _shell_data: ::std::marker::PhantomData,
+        }
+    }
+}
+
 impl<U, R, SD> PopupSurface<U, R, SD>
 where
     U: 'static,
@@ -806,6 +824,9 @@ where
 }
 
 /// A configure message for toplevel surfaces
+///
+/// Its default value suggests no size, no state and no decoration mode.
+#[derive(Default)]
 pub struct ToplevelConfigure {
     /// A suggestion for a new size for the surface
     pub size: Option<(i32, i32)>,
@@ -820,6 +841,12 @@ pub struct ToplevelConfigure {
     /// from a client for a serial will validate all pending lower
     /// serials.
     pub serial: u32,
+    /// The decoration mode chosen for this surface
+    ///
+    /// It is only sent if the client created a decoration object for this
+    /// surface using the [`decoration`](::wayland::shell::xdg::decoration) global,
+    /// and ignored for `zxdg_shell_v6` surfaces.
+    pub decoration_mode: Option<decoration::Mode>,
 }
 
 /// A configure message for popup surface
@@ -896,7 +923,7 @@ pub enum XdgRequest<U, R, SD> {
     /// This popup requests a grab of the pointer
     ///
     /// This means it requests to be sent a `popup_done` event when the pointer leaves
-    /// the grab area.
+    /// the grab area. You can use [`PopupSurface::grab`] to run the grab on the seat.
     Grab {
         /// The surface
         surface: PopupSurface<U, R, SD>,
@@ -0,0 +1,402 @@
+//! Implementation of the `xdg_popup` grab
+//!
+//! When a popup is grabbed, the pointer and keyboard events are only delivered to the
+//! client owning the popup, and a click on any other surface dismisses the whole stack
+//! of grabbing popups of the seat.
+
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use wayland_server::protocol::{
+    wl_keyboard::KeyState as WlKeyState, wl_pointer::ButtonState, wl_surface::WlSurface,
+};
+
+use crate::wayland::{
+    compositor::roles::Role,
+    seat::{
+        AxisFrame, KeyboardGrab, KeyboardHandle, KeyboardInnerHandle, PointerGrab, PointerHandle,
+        PointerInnerHandle, Seat,
+    },
+};
+
+use super::{PopupSurface, XdgSurfaceRole};
+
+/// Errors that can occur when starting a popup grab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupGrabError {
+    /// The serial does not match an ongoing implicit grab of the seat
+    InvalidSerial,
+    /// The parent of the popup is not the topmost popup of the current grab
+    NotTheTopmostPopup,
+    /// The popup surface has already been destroyed
+    PopupDestroyed,
+}
+
+impl fmt::Display for PopupGrabError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        use std::error::Error;
+        write!(formatter, "{}", self.description())
+    }
+}
+
+impl ::std::error::Error for PopupGrabError {
+    fn description(&self) -> &str {
+        match *self {
+            PopupGrabError::InvalidSerial => "The grab serial is not valid",
+            PopupGrabError::NotTheTopmostPopup => "The parent of the popup is not the topmost grabbing popup",
+            PopupGrabError::PopupDestroyed => "The popup surface has been destroyed",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn ::std::error::Error> {
+        None
+    }
+}
+
+// The stack of grabbing popups of a seat, stored in its user data
+struct PopupGrabStack<U, R, SD> {
+    inner: Rc<RefCell<PopupGrabInner<U, R, SD>>>,
+}
+
+struct PopupGrabInner<U, R, SD> {
+    popups: Vec<PopupSurface<U, R, SD>>,
+    // serial of the grabs installed on the pointer and keyboard
+    serial: u32,
+    // serial of the last button press delivered to the grabbing client
+    last_press: Option<u32>,
+    // keyboard focus to restore once the grab ends
+    restore_focus: Option<WlSurface>,
+}
+
+impl<U, R, SD> PopupGrabInner<U, R, SD>
+where
+    U: 'static,
+    R: Role<XdgSurfaceRole> + 'static,
+    SD: 'static,
+{
+    fn is_active(&self) -> bool {
+        !self.popups.is_empty()
+    }
+
+    fn prune(&mut self) {
+        // a destroyed popup takes its children with it
+        if let Some(idx) = self.popups.iter().position(|popup| !popup.alive()) {
+            for popup in self.popups.drain(idx..).rev() {
+                popup.send_popup_done();
+            }
+        }
+    }
+
+    fn topmost(&self) -> Option<&WlSurface> {
+        self.popups.last().and_then(|popup| popup.get_surface())
+    }
+
+    fn contains(&self, surface: &WlSurface) -> bool {
+        self.popups
+            .iter()
+            .filter_map(|popup| popup.get_surface())
+            .any(|s| s.as_ref().equals(surface.as_ref()))
+    }
+
+    fn owns(&self, surface: &WlSurface) -> bool {
+        self.popups
+            .first()
+            .and_then(|popup| popup.get_surface())
+            .map(|s| s.as_ref().same_client_as(surface.as_ref()))
+            .unwrap_or(false)
+    }
+
+    // dismiss all the popups, topmost first, and return the focus to restore
+    fn dismiss(&mut self) -> Option<WlSurface> {
+        for popup in self.popups.drain(..).rev() {
+            popup.send_popup_done();
+        }
+        self.last_press = None;
+        self.restore_focus.take()
+    }
+}
+
+impl<U, R, SD> PopupSurface<U, R, SD>
+where
+    U: 'static,
+    R: Role<XdgSurfaceRole> + 'static,
+    SD: 'static,
+{
+    /// Start a popup grab for this popup on given seat
+    ///
+    /// This is typically done in response to a [`XdgRequest::Grab`](super::XdgRequest::Grab).
+    ///
+    /// The serial must be the one of an implicit grab of the pointer or touch of the seat, or
+    /// of a button press delivered to a popup of an ongoing popup grab. If the seat already
+    /// has grabbing popups, the parent of this popup must be the topmost of them.
+    ///
+    /// While the grab is active, pointer events are only delivered to the surfaces of the
+    /// client owning the popup and the keyboard focus is given to the topmost popup. Clicking
+    /// on any other surface dismisses all the grabbing popups of the seat, by sending them a
+    /// `popup_done` event, and ends the grab.
+    ///
+    /// If the grab cannot be started, this popup is dismissed and an error is returned.
+    pub fn grab(&self, seat: &Seat, serial: u32) -> Result<(), PopupGrabError> {
+        let ret = self.start_grab(seat, serial);
+        if ret.is_err() {
+            self.send_popup_done();
+        }
+        ret
+    }
+
+    fn start_grab(&self, seat: &Seat, serial: u32) -> Result<(), PopupGrabError> {
+        let surface = match self.get_surface() {
+            Some(surface) => surface.clone(),
+            None => return Err(PopupGrabError::PopupDestroyed),
+        };
+        let parent = self.get_pending_state().and_then(|state| state.parent);
+
+        seat.user_data().insert_if_missing(|| PopupGrabStack::<U, R, SD> {
+            inner: Rc::new(RefCell::new(PopupGrabInner {
+                popups: Vec::new(),
+                serial: 0,
+                last_press: None,
+                restore_focus: None,
+            })),
+        });
+        let stack = seat
+            .user_data()
+            .get::<PopupGrabStack<U, R, SD>>()
+            .unwrap()
+            .inner
+            .clone();
+        let pointer = seat.get_pointer();
+        let keyboard = seat.get_keyboard();
+
+        let mut guard = stack.borrow_mut();
+        guard.prune();
+        // the focus to restore, if we took over the grab of another client
+        let mut taken_over = None;
+        if guard.is_active() && !guard.owns(&surface) {
+            // a popup of another client requests a grab, the previous grab is over
+            let old_serial = guard.serial;
+            taken_over = Some(guard.dismiss());
+            if let Some(ref pointer) = pointer {
+                if pointer.has_grab(old_serial) {
+                    pointer.unset_grab();
+                }
+            }
+            if let Some(ref keyboard) = keyboard {
+                if keyboard.has_grab(old_serial) {
+                    keyboard.unset_grab();
+                }
+            }
+        }
+
+        let nested = guard.is_active();
+        let valid_serial = pointer.as_ref().map(|p| p.has_grab(serial)).unwrap_or(false)
+            || seat.get_touch().map(|t| t.has_grab(serial)).unwrap_or(false)
+            || (nested && guard.last_press == Some(serial));
+        if !valid_serial {
+            ::std::mem::drop(guard);
+            if let (Some(keyboard), Some(restore_focus)) = (keyboard, taken_over) {
+                keyboard.set_focus(restore_focus.as_ref(), serial);
+            }
+            return Err(PopupGrabError::InvalidSerial);
+        }
+
+        if nested {
+            let is_topmost = match (guard.topmost(), parent.as_ref()) {
+                (Some(top), Some(parent)) => top.as_ref().equals(parent.as_ref()),
+                _ => false,
+            };
+            if !is_topmost {
+                return Err(PopupGrabError::NotTheTopmostPopup);
+            }
+        } else {
+            guard.serial = serial;
+            // when taking over, the keyboard is still focused on the dismissed popups
+            guard.restore_focus = match taken_over {
+                Some(restore_focus) => restore_focus,
+                None => keyboard.as_ref().and_then(|k| k.current_focus()),
+            };
+        }
+        guard.popups.push(self.clone());
+        let grab_serial = guard.serial;
+        ::std::mem::drop(guard);
+
+        if !nested {
+            if let Some(ref pointer) = pointer {
+                pointer.set_grab(
+                    PopupPointerGrab {
+                        stack: stack.clone(),
+                        keyboard: keyboard.clone(),
+                    },
+                    grab_serial,
+                );
+            }
+            if let Some(ref keyboard) = keyboard {
+                keyboard.set_grab(
+                    PopupKeyboardGrab {
+                        stack: stack.clone(),
+                        pointer: pointer.clone(),
+                    },
+                    grab_serial,
+                );
+            }
+        }
+        if let Some(keyboard) = keyboard {
+            keyboard.set_focus(Some(&surface), serial);
+        }
+        Ok(())
+    }
+}
+
+// The pointer side of the popup grab
+struct PopupPointerGrab<U, R, SD> {
+    stack: Rc<RefCell<PopupGrabInner<U, R, SD>>>,
+    keyboard: Option<KeyboardHandle>,
+}
+
+impl<U, R, SD> PopupPointerGrab<U, R, SD>
+where
+    U: 'static,
+    R: Role<XdgSurfaceRole> + 'static,
+    SD: 'static,
+{
+    // returns false if the grab was ended
+    fn check_alive(&self, handle: &mut PointerInnerHandle<'_>, serial: u32, time: u32) -> bool {
+        self.stack.borrow_mut().prune();
+        if self.stack.borrow().is_active() {
+            true
+        } else {
+            self.end(handle, serial, time);
+            false
+        }
+    }
+
+    fn end(&self, handle: &mut PointerInnerHandle<'_>, serial: u32, time: u32) {
+        let (grab_serial, restore_focus) = {
+            let mut guard = self.stack.borrow_mut();
+            (guard.serial, guard.dismiss())
+        };
+        if let Some(ref keyboard) = self.keyboard {
+            if keyboard.has_grab(grab_serial) {
+                keyboard.unset_grab();
+                keyboard.set_focus(restore_focus.as_ref(), serial);
+            }
+        }
+        handle.unset_grab(serial, time);
+    }
+}
+
+impl<U, R, SD> PointerGrab for PopupPointerGrab<U, R, SD>
+where
+    U: 'static,
+    R: Role<XdgSurfaceRole> + 'static,
+    SD: 'static,
+{
+    fn motion(
+        &mut self,
+        handle: &mut PointerInnerHandle<'_>,
+        location: (f64, f64),
+        focus: Option<(WlSurface, (f64, f64))>,
+        serial: u32,
+        time: u32,
+    ) {
+        if !self.check_alive(handle, serial, time) {
+            return;
+        }
+        // only the surfaces of the grabbing client can get the focus
+        let focus = focus.filter(|&(ref surface, _)| self.stack.borrow().owns(surface));
+        handle.motion(location, focus, serial, time);
+    }
+
+    fn button(
+        &mut self,
+        handle: &mut PointerInnerHandle<'_>,
+        button: u32,
+        state: ButtonState,
+        serial: u32,
+        time: u32,
+    ) {
+        if !self.check_alive(handle, serial, time) {
+            return;
+        }
+        if state == ButtonState::Pressed {
+            if handle.current_focus().is_none() {
+                // click outside of the grabbing client, dismiss the popups
+                self.end(handle, serial, time);
+                return;
+            }
+            self.stack.borrow_mut().last_press = Some(serial);
+        }
+        handle.button(button, state, serial, time);
+    }
+
+    fn axis(&mut self, handle: &mut PointerInnerHandle<'_>, details: AxisFrame) {
+        handle.axis(details);
+    }
+}
+
+// The keyboard side of the popup grab
+struct PopupKeyboardGrab<U, R, SD> {
+    stack: Rc<RefCell<PopupGrabInner<U, R, SD>>>,
+    pointer: Option<PointerHandle>,
+}
+
+impl<U, R, SD> PopupKeyboardGrab<U, R, SD>
+where
+    U: 'static,
+    R: Role<XdgSurfaceRole> + 'static,
+    SD: 'static,
+{
+    // returns false if the grab was ended
+    fn check_alive(&self, handle: &mut KeyboardInnerHandle<'_>, serial: u32) -> bool {
+        let mut guard = self.stack.borrow_mut();
+        guard.prune();
+        if guard.is_active() {
+            // the focus goes back to the parent when a popup is destroyed
+            let topmost = guard.topmost().cloned();
+            ::std::mem::drop(guard);
+            handle.set_focus(topmost.as_ref(), serial);
+            return true;
+        }
+        let grab_serial = guard.serial;
+        let restore_focus = guard.dismiss();
+        ::std::mem::drop(guard);
+        if let Some(ref pointer) = self.pointer {
+            if pointer.has_grab(grab_serial) {
+                pointer.unset_grab();
+            }
+        }
+        handle.unset_grab();
+        handle.set_focus(restore_focus.as_ref(), serial);
+        false
+    }
+}
+
+impl<U, R, SD> KeyboardGrab for PopupKeyboardGrab<U, R, SD>
+where
+    U: 'static,
+    R: Role<XdgSurfaceRole> + 'static,
+    SD: 'static,
+{
+    fn input(
+        &mut self,
+        handle: &mut KeyboardInnerHandle<'_>,
+        keycode: u32,
+        state: WlKeyState,
+        modifiers: Option<(u32, u32, u32, u32)>,
+        serial: u32,
+        time: u32,
+    ) {
+        if self.check_alive(handle, serial) {
+            handle.input(keycode, state, modifiers, serial, time);
+        }
+    }
+
+    fn set_focus(&mut self, handle: &mut KeyboardInnerHandle<'_>, focus: Option<&WlSurface>, serial: u32) {
+        let is_popup = focus.map(|s| self.stack.borrow().contains(s)).unwrap_or(false);
+        if is_popup {
+            handle.set_focus(focus, serial);
+        } else {
+            // keep the focus on the popups, but remember where to return once the grab ends
+            self.stack.borrow_mut().restore_focus = focus.cloned();
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use std::{cell::RefCell, sync::Mutex};
 
 use crate::wayland::compositor::{roles::*, CompositorToken};
-use wayland_protocols::xdg_shell::server::{
-    xdg_popup, xdg_positioner, xdg_surface, xdg_toplevel, xdg_wm_base,
+use wayland_protocols::{
+    unstable::xdg_decoration::v1::server::zxdg_toplevel_decoration_v1,
+    xdg_shell::server::{xdg_popup, xdg_positioner, xdg_surface, xdg_toplevel, xdg_wm_base},
 };
 use wayland_server::{protocol::wl_surface, NewResource};
 
@@ -260,6 +261,7 @@ where
                     wl_surface: data.wl_surface.clone(),
                     xdg_surface: xdg_surface.clone(),
                     wm_base: data.wm_base.clone(),
+                    decoration: RefCell::new(None),
                 },
             );
 
@@ -308,6 +310,7 @@ where
                     wl_surface: data.wl_surface.clone(),
                     xdg_surface: xdg_surface.clone(),
                     wm_base: data.wm_base.clone(),
+                    decoration: RefCell::new(None),
                 },
             );
 
@@ -365,6 +368,8 @@ pub(crate) struct ShellSurfaceUserData<U, R, SD> {
     pub(crate) wl_surface: wl_surface::WlSurface,
     pub(crate) wm_base: xdg_wm_base::XdgWmBase,
     pub(crate) xdg_surface: xdg_surface::XdgSurface,
+    // the decoration object of a toplevel, if any
+    pub(crate) decoration: RefCell<Option<zxdg_toplevel_decoration_v1::ZxdgToplevelDecorationV1>>,
 }
 
 // Utility functions allowing to factor out a lot of the upcoming logic
@@ -413,6 +418,9 @@ where
     };
     let serial = configure.serial;
     resource.configure(width, height, states);
+    if let (Some(mode), Some(decoration)) = (configure.decoration_mode, &*data.decoration.borrow()) {
+        decoration.configure(mode);
+    }
     data.xdg_surface.configure(serial);
     // Add the configure as pending
     data.shell_data
@@ -421,7 +429,7 @@ where
         .expect("xdg_toplevel exists but surface has not shell_surface role?!");
 }
 
-fn make_toplevel_handle<U: 'static, R: 'static, SD: 'static>(
+pub(crate) fn make_toplevel_handle<U: 'static, R: 'static, SD: 'static>(
     resource: &xdg_toplevel::XdgToplevel,
 ) -> super::ToplevelSurface<U, R, SD> {
     let data = resource
@@ -559,6 +567,12 @@ where
         // trying to change the role but it's a cleanup (possibly a
         // disconnecting client), ignore the protocol check.
     } else {
+        if let Some(ref decoration) = *data.decoration.borrow() {
+            decoration.as_ref().post_error(
+                zxdg_toplevel_decoration_v1::Error::Orphaned as u32,
+                "xdg_toplevel was destroyed before its decoration object.".into(),
+            );
+        }
         data.shell_data
             .compositor_token
             .with_role_data::<XdgSurfaceRole, _, _>(&data.wl_surface, |data| {
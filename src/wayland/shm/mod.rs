@@ -184,6 +184,14 @@ where
     }
 }
 
+// The size of a buffer managed by the shm global, without accessing its contents
+pub(crate) fn buffer_size(buffer: &wl_buffer::WlBuffer) -> Option<(i32, i32)> {
+    buffer
+        .as_ref()
+        .user_data::<InternalBufferData>()
+        .map(|data| (data.data.width, data.data.height))
+}
+
 impl ShmGlobalData {
     fn receive_shm_message(&mut self, request: wl_shm::Request, shm: wl_shm::WlShm) {
         use self::wl_shm::{Error, Request};
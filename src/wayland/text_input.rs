@@ -0,0 +1,431 @@
+//! Utilities for handling the `zwp_text_input_v3` protocol
+//!
+//! This protocol allows clients to receive text from an input method rather than raw key
+//! presses, which is required to type in languages needing composition, like Chinese or
+//! Japanese, and is also used by on-screen keyboards.
+//!
+//! The text inputs of a seat follow the focus of its keyboard: when a surface gains the
+//! keyboard focus, the text inputs of its client are sent an `enter` event, and they are
+//! sent a `leave` event when it loses the focus.
+//!
+//! ## How to use it
+//!
+//! Initialize the global with [`init_text_input_manager_global`], providing a callback that
+//! will be notified when clients enable, update and disable their text inputs. The state
+//! they provide (surrounding text, content type, cursor rectangle...) is available in the
+//! [`TextInputState`] of these events.
+//!
+//! Your input method then sends text to the focused text input of a seat through its
+//! [`TextInputHandle`], that you can retrieve with
+//! [`Seat::get_text_input`](::wayland::seat::Seat::get_text_input):
+//!
+//! ```
+//! # extern crate smithay;
+//! # use smithay::wayland::seat::Seat;
+//! # fn wrap(seat: &Seat) {
+//! let text_input = seat.get_text_input();
+//! // show the text being composed
+//! text_input.set_preedit_string(Some("にほ".into()), 0, 6);
+//! text_input.done();
+//! // then insert the result of the composition
+//! text_input.set_preedit_string(None, 0, 0);
+//! text_input.commit_string(Some("日本".into()));
+//! text_input.done();
+//! # }
+//! # fn main() {}
+//! ```
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # extern crate smithay;
+//! use smithay::wayland::text_input::{init_text_input_manager_global, TextInputEvent};
+//!
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! let text_input_global = init_text_input_manager_global(
+//!     &mut display,
+//!     |event| match event {
+//!         TextInputEvent::Enabled { .. } => { /* show the input method */ },
+//!         TextInputEvent::Updated { .. } => { /* forward the new state to the input method */ },
+//!         TextInputEvent::Disabled { .. } => { /* hide the input method */ },
+//!     },
+//!     None // put a logger here
+//! );
+//! # }
+//! ```
+
+use std::{cell::RefCell, rc::Rc};
+
+use wayland_protocols::unstable::text_input::v3::server::{
+    zwp_text_input_manager_v3::{self, ZwpTextInputManagerV3},
+    zwp_text_input_v3::{self, ZwpTextInputV3},
+};
+use wayland_server::{protocol::wl_surface::WlSurface, Display, Global, NewResource};
+
+use crate::utils::Rectangle;
+use crate::wayland::seat::Seat;
+
+pub use wayland_protocols::unstable::text_input::v3::server::zwp_text_input_v3::{
+    ChangeCause, ContentHint, ContentPurpose,
+};
+
+/// The surrounding text of the cursor, as provided by a client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurroundingText {
+    /// The text around the cursor
+    pub text: String,
+    /// Position of the cursor in the text, in bytes
+    pub cursor: i32,
+    /// Position of the selection anchor in the text, in bytes
+    ///
+    /// It is equal to the cursor if there is no selection.
+    pub anchor: i32,
+}
+
+/// The state of a text input, as set by its client
+#[derive(Debug, Clone)]
+pub struct TextInputState {
+    /// Whether the text input is enabled
+    pub enabled: bool,
+    /// The text around the cursor, if the client provided it
+    pub surrounding_text: Option<SurroundingText>,
+    /// What caused the last change of the surrounding text
+    pub change_cause: ChangeCause,
+    /// Hints about the expected content
+    pub content_hint: ContentHint,
+    /// The purpose of the text input
+    pub content_purpose: ContentPurpose,
+    /// The area of the cursor, in surface coordinates
+    ///
+    /// The input method can use it to place its candidates window.
+    pub cursor_rectangle: Option<Rectangle>,
+}
+
+impl Default for TextInputState {
+    fn default() -> TextInputState {
+        TextInputState {
+            enabled: false,
+            surrounding_text: None,
+            change_cause: ChangeCause::InputMethod,
+            content_hint: ContentHint::None,
+            content_purpose: ContentPurpose::Normal,
+            cursor_rectangle: None,
+        }
+    }
+}
+
+/// Events generated by the text inputs of the clients
+pub enum TextInputEvent {
+    /// A focused text input was enabled
+    Enabled {
+        /// The seat of the text input
+        seat: Seat,
+        /// The surface containing the text input
+        surface: WlSurface,
+        /// The state of the text input
+        state: TextInputState,
+    },
+    /// An enabled text input committed a new state
+    Updated {
+        /// The seat of the text input
+        seat: Seat,
+        /// The surface containing the text input
+        surface: WlSurface,
+        /// The new state of the text input
+        state: TextInputState,
+    },
+    /// A focused text input was disabled or destroyed
+    ///
+    /// Note that this event is not generated when the text input loses the keyboard
+    /// focus, even though it is implicitly disabled.
+    Disabled {
+        /// The seat of the text input
+        seat: Seat,
+        /// The surface containing the text input
+        surface: WlSurface,
+    },
+}
+
+struct TextInputData {
+    seat: Seat,
+    state: RefCell<TextInputInstanceState>,
+}
+
+struct TextInputInstanceState {
+    pending: TextInputState,
+    current: TextInputState,
+    // number of commits received, used as serial of the done events
+    serial: u32,
+    // the surface this text input entered
+    focus: Option<WlSurface>,
+}
+
+#[derive(Default)]
+struct TextInputInner {
+    instances: Vec<ZwpTextInputV3>,
+    focus: Option<WlSurface>,
+}
+
+impl TextInputInner {
+    // call the closure with each enabled text input of the focused surface
+    fn with_active<F>(&self, mut f: F)
+    where
+        F: FnMut(&ZwpTextInputV3, &TextInputInstanceState),
+    {
+        for ti in &self.instances {
+            let data = ti.as_ref().user_data::<TextInputData>().unwrap();
+            let state = data.state.borrow();
+            if state.focus.is_some() && state.current.enabled {
+                f(ti, &state);
+            }
+        }
+    }
+}
+
+/// A handle to the text inputs of a seat
+///
+/// It is used by the input method to send text to the text input focused by the keyboard
+/// of the seat. The text is only sent to text inputs that are currently enabled.
+///
+/// The events are double-buffered: they are applied by the client when you call
+/// [`done`](TextInputHandle::done).
+#[derive(Clone, Default)]
+pub struct TextInputHandle {
+    inner: Rc<RefCell<TextInputInner>>,
+}
+
+impl TextInputHandle {
+    /// Set the text being composed, replacing any previous one
+    ///
+    /// The cursor positions are offsets in bytes in the text, they are both `-1` if the
+    /// cursor should be hidden.
+    pub fn set_preedit_string(&self, text: Option<String>, cursor_begin: i32, cursor_end: i32) {
+        self.inner.borrow().with_active(|ti, _| {
+            ti.preedit_string(text.clone(), cursor_begin, cursor_end);
+        });
+    }
+
+    /// Insert text at the position of the cursor
+    pub fn commit_string(&self, text: Option<String>) {
+        self.inner.borrow().with_active(|ti, _| {
+            ti.commit_string(text.clone());
+        });
+    }
+
+    /// Delete text around the cursor or selection
+    ///
+    /// The lengths are in bytes.
+    pub fn delete_surrounding_text(&self, before_length: u32, after_length: u32) {
+        self.inner.borrow().with_active(|ti, _| {
+            ti.delete_surrounding_text(before_length, after_length);
+        });
+    }
+
+    /// Ask the client to apply the previously sent events
+    pub fn done(&self) {
+        self.inner.borrow().with_active(|ti, state| {
+            ti.done(state.serial);
+        });
+    }
+
+    /// The state of the enabled text input of the focused surface, if any
+    pub fn focused_state(&self) -> Option<TextInputState> {
+        let mut ret = None;
+        self.inner.borrow().with_active(|_, state| {
+            ret = Some(state.current.clone());
+        });
+        ret
+    }
+
+    /// Update the focus of the text inputs
+    ///
+    /// This is automatically called when the keyboard focus of the seat changes.
+    pub(crate) fn set_focus(&self, focus: Option<&WlSurface>) {
+        let mut inner = self.inner.borrow_mut();
+        inner.instances.retain(|ti| ti.as_ref().is_alive());
+        inner.focus = focus.cloned();
+        for ti in &inner.instances {
+            update_focus(ti, focus);
+        }
+    }
+
+    fn add_instance(&self, ti: ZwpTextInputV3) {
+        let mut inner = self.inner.borrow_mut();
+        update_focus(&ti, inner.focus.as_ref());
+        inner.instances.push(ti);
+    }
+
+    fn remove_instance(&self, ti: &ZwpTextInputV3) {
+        self.inner
+            .borrow_mut()
+            .instances
+            .retain(|other| other.as_ref().is_alive() && !other.as_ref().equals(ti.as_ref()));
+    }
+}
+
+// send leave and enter events to a text input for a new focus
+fn update_focus(ti: &ZwpTextInputV3, focus: Option<&WlSurface>) {
+    let data = ti.as_ref().user_data::<TextInputData>().unwrap();
+    let mut state = data.state.borrow_mut();
+    if let Some(old) = state.focus.take() {
+        if focus.map(|f| f.as_ref().equals(old.as_ref())).unwrap_or(false) {
+            // the focus did not change
+            state.focus = Some(old);
+            return;
+        }
+        if old.as_ref().is_alive() {
+            ti.leave(&old);
+        }
+        // leaving the surface implicitly disables the text input
+        state.pending.enabled = false;
+        state.current.enabled = false;
+    }
+    if let Some(surface) = focus {
+        if surface.as_ref().same_client_as(ti.as_ref()) {
+            ti.enter(surface);
+            state.focus = Some(surface.clone());
+        }
+    }
+}
+
+/// Create a new `zwp_text_input_manager_v3` global
+///
+/// The global is directly created on the provided [`Display`](wayland_server::Display),
+/// and this function returns the global handle, in case you wish to remove this global in
+/// the future.
+pub fn init_text_input_manager_global<F, L>(
+    display: &mut Display,
+    implementation: F,
+    logger: L,
+) -> Global<ZwpTextInputManagerV3>
+where
+    F: FnMut(TextInputEvent) + 'static,
+    L: Into<Option<::slog::Logger>>,
+{
+    let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "text_input_handler"));
+    let implementation = Rc::new(RefCell::new(implementation));
+
+    display.create_global::<ZwpTextInputManagerV3, _>(1, move |new_manager, _version| {
+        let implementation = implementation.clone();
+        let log = log.clone();
+        new_manager.implement_closure(
+            move |request, _manager| match request {
+                zwp_text_input_manager_v3::Request::GetTextInput { id, seat } => {
+                    match Seat::from_resource(&seat) {
+                        Some(seat) => {
+                            implement_text_input(id, seat, implementation.clone());
+                        }
+                        None => {
+                            // this text input will never be focused
+                            warn!(log, "Unmanaged seat given to a text input.");
+                            id.implement_closure(|_, _| {}, None::<fn(_)>, ());
+                        }
+                    }
+                }
+                zwp_text_input_manager_v3::Request::Destroy => {
+                    // nothing to do
+                }
+                _ => unreachable!(),
+            },
+            None::<fn(_)>,
+            (),
+        );
+    })
+}
+
+fn implement_text_input<F>(
+    new_ti: NewResource<ZwpTextInputV3>,
+    seat: Seat,
+    implementation: Rc<RefCell<F>>,
+) -> ZwpTextInputV3
+where
+    F: FnMut(TextInputEvent) + 'static,
+{
+    let destroy_implementation = implementation.clone();
+    let ti = new_ti.implement_closure(
+        move |request, ti| {
+            let data = ti.as_ref().user_data::<TextInputData>().unwrap();
+            let mut state = data.state.borrow_mut();
+            match request {
+                zwp_text_input_v3::Request::Enable => {
+                    // enabling resets the state of the text input
+                    state.pending = TextInputState {
+                        enabled: true,
+                        ..Default::default()
+                    };
+                }
+                zwp_text_input_v3::Request::Disable => {
+                    state.pending.enabled = false;
+                }
+                zwp_text_input_v3::Request::SetSurroundingText { text, cursor, anchor } => {
+                    state.pending.surrounding_text = Some(SurroundingText { text, cursor, anchor });
+                }
+                zwp_text_input_v3::Request::SetTextChangeCause { cause } => {
+                    state.pending.change_cause = cause;
+                }
+                zwp_text_input_v3::Request::SetContentType { hint, purpose } => {
+                    state.pending.content_hint = hint;
+                    state.pending.content_purpose = purpose;
+                }
+                zwp_text_input_v3::Request::SetCursorRectangle { x, y, width, height } => {
+                    state.pending.cursor_rectangle = Some(Rectangle { x, y, width, height });
+                }
+                zwp_text_input_v3::Request::Commit => {
+                    state.serial = state.serial.wrapping_add(1);
+                    let was_enabled = state.current.enabled;
+                    state.current = state.pending.clone();
+                    let surface = match state.focus.clone() {
+                        Some(surface) => surface,
+                        // only the text inputs of the focused surface are active
+                        None => return,
+                    };
+                    let seat = data.seat.clone();
+                    let event = match (was_enabled, state.current.enabled) {
+                        (false, true) => TextInputEvent::Enabled {
+                            seat,
+                            surface,
+                            state: state.current.clone(),
+                        },
+                        (true, true) => TextInputEvent::Updated {
+                            seat,
+                            surface,
+                            state: state.current.clone(),
+                        },
+                        (true, false) => TextInputEvent::Disabled { seat, surface },
+                        (false, false) => return,
+                    };
+                    // release the state before calling the user callback
+                    ::std::mem::drop(state);
+                    (&mut *implementation.borrow_mut())(event);
+                }
+                zwp_text_input_v3::Request::Destroy => {
+                    // our destructor already handles it
+                }
+                _ => unreachable!(),
+            }
+        },
+        Some(move |ti: ZwpTextInputV3| {
+            let data = ti.as_ref().user_data::<TextInputData>().unwrap();
+            data.seat.get_text_input().remove_instance(&ti);
+            let state = data.state.borrow();
+            if let (true, Some(surface)) = (state.current.enabled, state.focus.clone()) {
+                (&mut *destroy_implementation.borrow_mut())(TextInputEvent::Disabled {
+                    seat: data.seat.clone(),
+                    surface,
+                });
+            }
+        }),
+        TextInputData {
+            seat: seat.clone(),
+            state: RefCell::new(TextInputInstanceState {
+                pending: TextInputState::default(),
+                current: TextInputState::default(),
+                serial: 0,
+                focus: None,
+            }),
+        },
+    );
+    seat.get_text_input().add_instance(ti.clone());
+    ti
+}
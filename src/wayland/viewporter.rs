@@ -0,0 +1,357 @@
+//! Utilities for handling the `wp_viewporter` protocol
+//!
+//! This protocol allows clients to crop and scale the contents of their surfaces
+//! independently of the size of their buffers. It is notably used by video players
+//! and web browsers to avoid reallocating buffers.
+//!
+//! ## How to use it
+//!
+//! Initialize the global with [`init_viewporter_global`]. The crop and scale state set
+//! by the clients is then stored in the [`viewport`](::wayland::compositor::SurfaceAttributes::viewport)
+//! field of the attributes of their surfaces, like the rest of their double-buffered state.
+//!
+//! The size of a surface, taking into account its buffer scale and transform as well as its
+//! viewport, is given by [`SurfaceAttributes::surface_size`](::wayland::compositor::SurfaceAttributes::surface_size).
+//!
+//! When a surface with a viewport is committed, its source rectangle is checked against the
+//! size of its buffer, and the protocol error is sent to clients providing an invalid one. This
+//! is only possible for the buffers whose size is known to smithay: the shm and dmabuf buffers.
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # #[macro_use] extern crate smithay;
+//! use smithay::wayland::viewporter::init_viewporter_global;
+//! # use smithay::wayland::compositor::compositor_init;
+//!
+//! # define_roles!(Roles);
+//! # fn main() {
+//! # let mut event_loop = wayland_server::calloop::EventLoop::<()>::new().unwrap();
+//! # let mut display = wayland_server::Display::new(event_loop.handle());
+//! # let (compositor_token, _, _) = compositor_init::<(), Roles, _, _>(&mut display, |_, _, _| {}, None);
+//! let viewporter_global = init_viewporter_global(
+//!     &mut display,
+//!     compositor_token,
+//!     None // put a logger here
+//! );
+//! # }
+//! ```
+
+use wayland_protocols::viewporter::server::{
+    wp_viewport::{self, WpViewport},
+    wp_viewporter::{self, WpViewporter},
+};
+use wayland_server::{
+    protocol::{wl_buffer::WlBuffer, wl_surface::WlSurface},
+    Display, Global,
+};
+
+use crate::wayland::{
+    compositor::{Cacheable, CompositorToken, SurfaceState, ViewportSource},
+    dmabuf, shm,
+};
+
+// The size of the last buffer attached to a surface with a viewport
+//
+// It is kept up to date in the pending state by the commit hook checking the viewport,
+// which is registered once per surface. It is never applied.
+#[derive(Default)]
+struct ViewportBuffer {
+    hooked: bool,
+    size: Option<(i32, i32)>,
+}
+
+impl Cacheable for ViewportBuffer {
+    fn commit(&mut self) -> ViewportBuffer {
+        ViewportBuffer::default()
+    }
+
+    fn merge_into(self, _into: &mut ViewportBuffer) {}
+}
+
+/// Create a new `wp_viewporter` global
+///
+/// The global is directly created on the provided [`Display`](wayland_server::Display),
+/// and this function returns the global handle, in case you wish to remove this global in
+/// the future.
+pub fn init_viewporter_global<U, R, L>(
+    display: &mut Display,
+    token: CompositorToken<U, R>,
+    logger: L,
+) -> Global<WpViewporter>
+where
+    U: 'static,
+    R: 'static,
+    L: Into<Option<::slog::Logger>>,
+{
+    let log = crate::slog_or_stdlog(logger).new(o!("smithay_module" => "viewporter_handler"));
+
+    display.create_global::<WpViewporter, _>(1, move |new_viewporter, _version| {
+        let log = log.clone();
+        new_viewporter.implement_closure(
+            move |request, viewporter| match request {
+                wp_viewporter::Request::GetViewport { id, surface } => {
+                    let exists = token.with_pending_state(&surface, |state: &mut SurfaceState| {
+                        state.viewport.resource.is_some()
+                    });
+                    if exists {
+                        viewporter.as_ref().post_error(
+                            wp_viewporter::Error::ViewportExists as u32,
+                            "Surface already has a viewport.".into(),
+                        );
+                        return;
+                    }
+                    trace!(log, "New viewport");
+                    let viewport = id.implement_closure(
+                        move |request, viewport| viewport_implementation(request, viewport, token),
+                        Some(move |viewport: WpViewport| destroy_viewport(&viewport, token)),
+                        surface.clone(),
+                    );
+                    token.with_pending_state(&surface, |state: &mut SurfaceState| {
+                        state.viewport.resource = Some(viewport)
+                    });
+                    let hooked = token.with_pending_state(&surface, |buffer: &mut ViewportBuffer| {
+                        ::std::mem::replace(&mut buffer.hooked, true)
+                    });
+                    if !hooked {
+                        token.add_pre_commit_hook(&surface, move |surface| check_viewport(surface, token));
+                    }
+                }
+                wp_viewporter::Request::Destroy => {
+                    // nothing to do
+                }
+                _ => unreachable!(),
+            },
+            None::<fn(_)>,
+            (),
+        );
+    })
+}
+
+// The size of a buffer, if it is known to smithay
+fn buffer_size(buffer: &WlBuffer) -> Option<(i32, i32)> {
+    match dmabuf::get_dmabuf(buffer) {
+        Some(dmabuf) => Some((dmabuf.width, dmabuf.height)),
+        None => shm::buffer_size(buffer),
+    }
+}
+
+// Check the source rectangle of the viewport about to be committed against the size of the buffer
+fn check_viewport<U: 'static, R: 'static>(surface: &WlSurface, token: CompositorToken<U, R>) {
+    let (attached, viewport, buffer_scale, buffer_transform) =
+        token.with_pending_state(surface, |state: &mut SurfaceState| {
+            let attached = state
+                .buffer
+                .as_ref()
+                .map(|buffer| buffer.as_ref().and_then(|&(ref buffer, _)| buffer_size(buffer)));
+            (
+                attached,
+                state.viewport.clone(),
+                state.buffer_scale,
+                state.buffer_transform,
+            )
+        });
+    let size = token.with_pending_state(surface, |buffer: &mut ViewportBuffer| {
+        if let Some(size) = attached {
+            buffer.size = size;
+        }
+        buffer.size
+    });
+    if let Some(size) = size {
+        viewport.ensure_in_buffer(size, buffer_scale, buffer_transform);
+    }
+}
+
+fn viewport_implementation<U: 'static, R: 'static>(
+    request: wp_viewport::Request,
+    viewport: WpViewport,
+    token: CompositorToken<U, R>,
+) {
+    let surface = viewport.as_ref().user_data::<WlSurface>().unwrap();
+    if !surface.as_ref().is_alive() {
+        if let wp_viewport::Request::Destroy = request {
+            return;
+        }
+        viewport.as_ref().post_error(
+            wp_viewport::Error::NoSurface as u32,
+            "The surface of this viewport was destroyed.".into(),
+        );
+        return;
+    }
+    match request {
+        wp_viewport::Request::SetSource { x, y, width, height } => {
+            let source = if x == -1.0 && y == -1.0 && width == -1.0 && height == -1.0 {
+                None
+            } else if x < 0.0 || y < 0.0 || width <= 0.0 || height <= 0.0 {
+                viewport.as_ref().post_error(
+                    wp_viewport::Error::BadValue as u32,
+                    format!("Invalid source rectangle: {}x{} at ({}, {})", width, height, x, y),
+                );
+                return;
+            } else {
+                Some(ViewportSource { x, y, width, height })
+            };
+            token.with_pending_state(surface, |state: &mut SurfaceState| state.viewport.source = source);
+        }
+        wp_viewport::Request::SetDestination { width, height } => {
+            let destination = if width == -1 && height == -1 {
+                None
+            } else if width <= 0 || height <= 0 {
+                viewport.as_ref().post_error(
+                    wp_viewport::Error::BadValue as u32,
+                    format!("Invalid destination size: {}x{}", width, height),
+                );
+                return;
+            } else {
+                Some((width, height))
+            };
+            token.with_pending_state(surface, |state: &mut SurfaceState| {
+                state.viewport.destination = destination
+            });
+        }
+        wp_viewport::Request::Destroy => {
+            // our destructor already handles it
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn destroy_viewport<U: 'static, R: 'static>(viewport: &WpViewport, token: CompositorToken<U, R>) {
+    let surface = viewport.as_ref().user_data::<WlSurface>().unwrap();
+    if surface.as_ref().is_alive() {
+        // the crop and scale state is removed from the surface at its next commit
+        token.with_pending_state(surface, |state: &mut SurfaceState| {
+            state.viewport.source = None;
+            state.viewport.destination = None;
+            state.viewport.resource = None;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        define_roles,
+        wayland::{compositor::compositor_init, shm::init_shm_global},
+    };
+    use std::{
+        os::unix::{
+            io::{AsRawFd, IntoRawFd},
+            net::UnixStream,
+        },
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+    use wayland_client::{
+        protocol::{
+            wl_buffer::WlBuffer as ClientBuffer, wl_compositor::WlCompositor, wl_shm,
+            wl_surface::WlSurface as ClientSurface,
+        },
+        GlobalManager,
+    };
+    use wayland_protocols::viewporter::client::{
+        wp_viewport::WpViewport as ClientViewport, wp_viewporter::WpViewporter as ClientViewporter,
+    };
+    use wayland_server::calloop::EventLoop;
+
+    define_roles!(Roles);
+
+    // Run a compositor providing the viewporter until the client running in its own thread
+    // returns, with a 20x10 shm buffer and a surface with a viewport, and give back the
+    // protocol error of the client
+    fn run_client<F>(client: F) -> Option<u32>
+    where
+        F: FnOnce(&ClientSurface, &ClientViewport, &ClientBuffer) + Send + 'static,
+    {
+        let mut event_loop = EventLoop::<()>::new().unwrap();
+        let mut display = Display::new(event_loop.handle());
+        let (token, _, _) = compositor_init::<(), Roles, _, _>(&mut display, |_, _, _| {}, None);
+        let _shm = init_shm_global(&mut display, Vec::new(), None);
+        let _viewporter = init_viewporter_global(&mut display, token, None);
+
+        let (server_end, client_end) = UnixStream::pair().unwrap();
+        let _client = unsafe { display.create_client(server_end.into_raw_fd()) };
+        let done = Arc::new(AtomicBool::new(false));
+        let client_done = done.clone();
+        let client_thread = thread::spawn(move || {
+            let (client_display, mut queue) =
+                unsafe { wayland_client::Display::from_fd(client_end.into_raw_fd()) }.unwrap();
+            let globals = GlobalManager::new(&client_display);
+            queue.sync_roundtrip().unwrap();
+            let compositor = globals
+                .instantiate_exact::<WlCompositor, _>(3, |compositor| compositor.implement_dummy())
+                .unwrap();
+            let shm = globals
+                .instantiate_exact::<wl_shm::WlShm, _>(1, |shm| shm.implement_dummy())
+                .unwrap();
+            let viewporter = globals
+                .instantiate_exact::<ClientViewporter, _>(1, |viewporter| viewporter.implement_dummy())
+                .unwrap();
+            let file = ::tempfile::tempfile().unwrap();
+            file.set_len(20 * 10 * 4).unwrap();
+            let pool = shm
+                .create_pool(file.as_raw_fd(), 20 * 10 * 4, |pool| pool.implement_dummy())
+                .unwrap();
+            let buffer = pool
+                .create_buffer(0, 20, 10, 20 * 4, wl_shm::Format::Argb8888, |buffer| {
+                    buffer.implement_dummy()
+                })
+                .unwrap();
+            let surface = compositor
+                .create_surface(|surface| surface.implement_dummy())
+                .unwrap();
+            let viewport = viewporter
+                .get_viewport(&surface, |viewport| viewport.implement_dummy())
+                .unwrap();
+            client(&surface, &viewport, &buffer);
+            let _ = queue.sync_roundtrip();
+            client_done.store(true, Ordering::SeqCst);
+            client_display.protocol_error().map(|error| error.code)
+        });
+
+        while !done.load(Ordering::SeqCst) {
+            event_loop
+                .dispatch(Some(Duration::from_millis(10)), &mut ())
+                .unwrap();
+            display.flush_clients();
+        }
+        client_thread.join().unwrap()
+    }
+
+    #[test]
+    fn source_in_buffer() {
+        let error = run_client(|surface, viewport, buffer| {
+            viewport.set_source(5.0, 0.0, 15.0, 10.0);
+            surface.attach(Some(buffer), 0, 0);
+            surface.commit();
+        });
+        assert_eq!(error, None);
+    }
+
+    #[test]
+    fn source_out_of_new_buffer() {
+        let error = run_client(|surface, viewport, buffer| {
+            viewport.set_source(5.0, 0.0, 20.0, 10.0);
+            surface.attach(Some(buffer), 0, 0);
+            surface.commit();
+        });
+        assert_eq!(error, Some(wp_viewport::Error::OutOfBuffer as u32));
+    }
+
+    #[test]
+    fn source_out_of_current_buffer() {
+        let error = run_client(|surface, viewport, buffer| {
+            surface.attach(Some(buffer), 0, 0);
+            surface.commit();
+            // the buffer is scaled down to 10x5
+            viewport.set_source(0.0, 0.0, 10.0, 10.0);
+            surface.set_buffer_scale(2);
+            surface.commit();
+        });
+        assert_eq!(error, Some(wp_viewport::Error::OutOfBuffer as u32));
+    }
+}